@@ -0,0 +1,2676 @@
+//! Decomposition of the valid colors into categories based on the satisfied properties.
+//!
+//! The result of the decomposition is kept in memory, so that it can be further analysed (or
+//! written to disk using the functions in `write_output`).
+
+use crate::errors::ClassifierError;
+use crate::write_output::bool_vec_to_string;
+
+use biodivine_lib_bdd::{Bdd, BddVariable};
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{GraphColoredVertices, GraphColors};
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Zero};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Symbolic set that can be decomposed into categories, i.e., [GraphColors] (the default) or
+/// [GraphColoredVertices] (which also keep the state component of the property results).
+pub trait ClassifiedSet: Set + Send + Sync {
+    /// Plural noun for the elements of the set, used in the reports (e.g., `colors`).
+    const ELEMENTS: &'static str;
+
+    /// Approximate number of elements in the set.
+    fn approx_cardinality(&self) -> f64;
+
+    /// Exact number of elements in the set.
+    fn exact_cardinality(&self) -> BigInt;
+
+    /// The underlying BDD of the set.
+    fn as_bdd(&self) -> &Bdd;
+
+    /// Set with a single element of this set (or empty, if this set is empty).
+    fn pick_singleton(&self) -> Self;
+
+    /// Colors of the elements of the set.
+    fn colors(&self) -> GraphColors;
+}
+
+impl ClassifiedSet for GraphColors {
+    const ELEMENTS: &'static str = "colors";
+
+    fn approx_cardinality(&self) -> f64 {
+        GraphColors::approx_cardinality(self)
+    }
+
+    fn exact_cardinality(&self) -> BigInt {
+        GraphColors::exact_cardinality(self)
+    }
+
+    fn as_bdd(&self) -> &Bdd {
+        GraphColors::as_bdd(self)
+    }
+
+    fn pick_singleton(&self) -> Self {
+        GraphColors::pick_singleton(self)
+    }
+
+    fn colors(&self) -> GraphColors {
+        self.clone()
+    }
+}
+
+impl ClassifiedSet for GraphColoredVertices {
+    const ELEMENTS: &'static str = "colored vertices";
+
+    fn approx_cardinality(&self) -> f64 {
+        GraphColoredVertices::approx_cardinality(self)
+    }
+
+    fn exact_cardinality(&self) -> BigInt {
+        GraphColoredVertices::exact_cardinality(self)
+    }
+
+    fn as_bdd(&self) -> &Bdd {
+        GraphColoredVertices::as_bdd(self)
+    }
+
+    fn pick_singleton(&self) -> Self {
+        GraphColoredVertices::pick_singleton(self)
+    }
+
+    fn colors(&self) -> GraphColors {
+        GraphColoredVertices::colors(self)
+    }
+}
+
+/// Result of a single classification property.
+#[derive(Clone)]
+pub struct PropertyResult<S = GraphColors> {
+    /// Name of the property.
+    pub name: String,
+    /// HCTL formula of the property.
+    pub formula: String,
+    /// Colors (universally) satisfying the property (or other elements, see [ClassifiedSet]).
+    pub colors: S,
+    /// Approximate number of colors satisfying the property.
+    pub cardinality: f64,
+    /// Exact number of colors satisfying the property.
+    pub exact_cardinality: BigInt,
+    /// False if the property holds for all valid colors or for none of them, i.e., it does not
+    /// split the valid colors into categories at all.
+    pub discriminating: bool,
+}
+
+/// One category of the color decomposition, i.e., the set of colors that satisfy exactly the
+/// same properties.
+#[derive(Clone)]
+pub struct Category<S = GraphColors> {
+    /// Validity of each property in this category, in the order of properties in the result.
+    pub validity: Vec<bool>,
+    /// Colors of this category.
+    pub colors: S,
+    /// Approximate number of colors in this category.
+    pub cardinality: f64,
+    /// Exact number of colors in this category.
+    pub exact_cardinality: BigInt,
+}
+
+impl<S: ClassifiedSet> Category<S> {
+    /// True if no color belongs to this category. Unlike the emptiness of its `colors`, this
+    /// also holds for the categories whose colors were not computed (see
+    /// [ClassificationResult::cardinalities_only]).
+    pub fn is_empty(&self) -> bool {
+        self.exact_cardinality.is_zero()
+    }
+
+    /// BDD variables that appear in the BDD of this category (sorted by their order), i.e.,
+    /// the symbolic variables (usually parameters) relevant to distinguishing its colors.
+    pub fn support(&self) -> Vec<BddVariable> {
+        let mut support: Vec<BddVariable> =
+            self.colors.as_bdd().support_set().into_iter().collect();
+        support.sort();
+        support
+    }
+}
+
+/// In-memory result of the whole classification.
+///
+/// By default, the classified elements are colors, but any [ClassifiedSet] can be decomposed
+/// the same way (the writers of the whole archives only accept colors).
+#[derive(Clone)]
+pub struct ClassificationResult<S = GraphColors> {
+    /// List of assertion formulae.
+    pub assertion_formulae: Vec<String>,
+    /// All colors satisfying the assertion formulae.
+    pub valid_colors: S,
+    /// Approximate number of colors satisfying the assertion formulae.
+    pub valid_cardinality: f64,
+    /// Exact number of colors satisfying the assertion formulae.
+    pub valid_exact_cardinality: BigInt,
+    /// Approximate number of all colors of the symbolic graph, i.e., of the parametrisations
+    /// before the assertions are applied (`None` if unknown). When known, the report also
+    /// gives the cardinalities as a fraction of the whole color space.
+    pub total_cardinality: Option<f64>,
+    /// Approximate number of colors satisfying each assertion individually, in the order of
+    /// the assertions (empty if unknown). The report only lists them when the assertions
+    /// cannot be satisfied together.
+    pub assertion_cardinalities: Vec<f64>,
+    /// Results for the individual classification properties.
+    pub properties: Vec<PropertyResult<S>>,
+    /// Results for the properties that were computed, but do not take part in the
+    /// decomposition (see [build_projected_classification_result]).
+    pub projected_properties: Vec<PropertyResult<S>>,
+    /// Maximal number of HCTL variables across all formulae (zero if unknown).
+    pub num_hctl_vars: usize,
+    /// Number of HCTL variables used by each individual formula, given as pairs
+    /// `(formula name, count)` (empty if unknown).
+    pub formula_hctl_vars: Vec<(String, usize)>,
+    /// Time spent enumerating the categories (zero if unknown).
+    pub enumeration_time: Duration,
+    /// Number of nodes of the largest BDD handled by the classification, i.e., of the valid
+    /// colors, the property results and the categories (zero if unknown).
+    pub peak_bdd_size: usize,
+    /// Categories of the decomposition, in the order of their validity vectors (interpreted
+    /// as MSB-first binary numbers). Empty categories are only included if all combinations
+    /// were enumerated (see [EnumerationMode]).
+    pub categories: Vec<Category<S>>,
+    /// True if only the cardinalities of the categories were computed, i.e., their colors are
+    /// left empty (see [build_cardinality_classification_result]). Such categories are still
+    /// reported, but they have no BDD dumps.
+    pub cardinalities_only: bool,
+}
+
+impl<S: ClassifiedSet> ClassificationResult<S> {
+    /// Number of non-empty categories, i.e., of the distinct classes of behaviour.
+    pub fn num_classes(&self) -> usize {
+        self.non_empty_categories().count()
+    }
+
+    /// Approximate total number of colors across all non-empty categories.
+    pub fn classified_cardinality(&self) -> f64 {
+        self.non_empty_categories()
+            .map(|category| category.cardinality)
+            .sum()
+    }
+
+    /// Exact total number of colors across all non-empty categories.
+    pub fn classified_exact_cardinality(&self) -> BigInt {
+        self.non_empty_categories()
+            .map(|category| &category.exact_cardinality)
+            .sum()
+    }
+
+    /// Valid colors which are not covered by any category. For an exact partition (see
+    /// [verify_partition]), this set is empty. Without the colors of the categories (see
+    /// [ClassificationResult::cardinalities_only]), these are all the valid colors.
+    pub fn unclassified_colors(&self) -> S {
+        self.categories
+            .iter()
+            .fold(self.valid_colors.clone(), |remaining, category| {
+                remaining.minus(&category.colors)
+            })
+    }
+
+    /// Validity vectors of the absent classes, i.e., of all combinations of properties that are
+    /// not satisfied by any valid color (in the lexicographic order). Returns `None` if there are
+    /// too many combinations to enumerate (more than [MAX_ABSENT_CLASS_PROPERTIES] properties).
+    pub fn absent_classes(&self) -> Option<Vec<Vec<bool>>> {
+        let num_properties = self.properties.len();
+        if num_properties > MAX_ABSENT_CLASS_PROPERTIES {
+            return None;
+        }
+        let present: HashSet<&[bool]> = self
+            .non_empty_categories()
+            .map(|category| category.validity.as_slice())
+            .collect();
+        let absent = (0..1u64 << num_properties)
+            .map(|i| int_to_bool_vec(i, num_properties))
+            .filter(|validity| !present.contains(validity.as_slice()))
+            .collect();
+        Some(absent)
+    }
+
+    /// Summary of all categories using only primitive types (e.g., to be passed to Python
+    /// bindings): the bit string of each category (MSB first), its approximate cardinality,
+    /// and the number of nodes of its BDD.
+    pub fn flat_summary(&self) -> Vec<(String, f64, usize)> {
+        self.categories
+            .iter()
+            .map(|category| {
+                (
+                    bool_vec_to_string(&category.validity),
+                    category.cardinality,
+                    category.colors.as_bdd().size(),
+                )
+            })
+            .collect()
+    }
+
+    /// Approximate and exact number of the valid colors which are not covered by any category
+    /// (see [ClassificationResult::unclassified_colors]). Without the colors of the categories,
+    /// the number is given by their cardinalities.
+    pub fn unclassified_cardinality(&self) -> (f64, BigInt) {
+        if self.cardinalities_only {
+            let exact = &self.valid_exact_cardinality - self.classified_exact_cardinality();
+            (exact.to_f64().unwrap_or(f64::INFINITY), exact)
+        } else {
+            let unclassified = self.unclassified_colors();
+            (
+                unclassified.approx_cardinality(),
+                unclassified.exact_cardinality(),
+            )
+        }
+    }
+
+    pub(crate) fn non_empty_categories(&self) -> impl Iterator<Item = &Category<S>> {
+        self.categories
+            .iter()
+            .filter(|category| !category.is_empty())
+    }
+}
+
+/// Largest number of properties for which [ClassificationResult::absent_classes] enumerates all
+/// combinations of properties.
+pub const MAX_ABSENT_CLASS_PROPERTIES: usize = 16;
+
+/// Check whether the two color sets are equal.
+///
+/// Unlike a comparison of cardinalities (which can coincide for different sets), this compares
+/// the Boolean functions represented by the underlying BDDs, regardless of the order in which
+/// their nodes are stored. The cardinalities are only compared first as a cheap pre-check.
+pub fn colors_equal<S: ClassifiedSet>(x: &S, y: &S) -> bool {
+    x.approx_cardinality() == y.approx_cardinality() && x.as_bdd().iff(y.as_bdd()).is_true()
+}
+
+/// Transform integer into a corresponding binary number of the given length.
+///
+/// If the integer "bit width" is larger than the given length, it is truncated. If it is smaller,
+/// the result is padded with zeroes to ensure `result.len() == bits_num`.
+///
+/// The result is given in MSB first (most significant bit first) format (as opposed to LSB, which
+/// is a bit more common in other applications). This means that when the vector is printed (with
+/// first element being the left-most printed item), it can be read left-to-right as the binary
+/// representation of the input `number`.
+///
+/// Since the input has only 64 bits, any bits beyond that (when `bits_num > 64`) are zero.
+pub(crate) fn int_to_bool_vec(number: u64, bits_num: usize) -> Vec<bool> {
+    let mut bits = vec![false; bits_num]; // Pre-allocate the values in one operation.
+    for i in 0..bits_num.min(64) {
+        let msb_index = bits_num - i - 1; // Invert index to ensure MSB bit order.
+        bits[msb_index] = ((number >> i) & 1) == 1;
+    }
+    bits
+}
+
+/// Transform a binary number into the corresponding integer, i.e., the inverse of
+/// [int_to_bool_vec].
+///
+/// The `bits` are given in the MSB first format (e.g., parsed from the `010` part of the
+/// `bdd_dump_010.txt` file name). Only the last 64 bits fit into the result, any bits before
+/// them are ignored.
+pub fn bool_vec_to_int(bits: &[bool]) -> u64 {
+    let start = bits.len().saturating_sub(64);
+    bits[start..]
+        .iter()
+        .fold(0, |number, bit| (number << 1) | u64::from(*bit))
+}
+
+/// Strategy used to enumerate the categories of the decomposition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnumerationMode {
+    /// Explicitly go through all `2^n` property combinations (including the empty ones).
+    /// Only feasible for less than 31 properties.
+    Exhaustive,
+    /// Only go through the categories that actually occur, by repeatedly picking a witness
+    /// color and carving out its whole category. Works for any number of properties.
+    Observed,
+    /// Same as [EnumerationMode::Exhaustive], but the non-discriminating properties (see
+    /// [PropertyResult::discriminating]) only take their one (constant) value, so they do not
+    /// double the number of (empty) categories.
+    Discriminating,
+}
+
+/// Decompose `all_valid_colors` into categories based on the satisfied properties, and collect
+/// all the results into a [ClassificationResult].
+///
+///  - `assertion_formulae`: list of assertion formulae
+///  - `all_valid_colors`: represents a "unit color set", i.e. all colors satisfying the
+///    assertion formulae.
+///  - `named_property_formulae`: lists the property names with their HCTL formula strings.
+///  - `property_results`: lists the symbolic color set results for each property.
+///
+/// Each result category is given by a set of colors that satisfy exactly the same properties.
+/// Fails if the number of property names and property results differs.
+/// All `2^n` combinations are enumerated if there are at most [DEFAULT_MAX_CLASSES] of them,
+/// otherwise only the categories that actually occur are computed (see [EnumerationMode]).
+///
+/// As an exception, if no property holds for any valid color, the result only contains the
+/// single (all-zeros) category of all valid colors, since every other combination is empty.
+pub fn build_classification_result<S: ClassifiedSet>(
+    assertion_formulae: &[String],
+    all_valid_colors: &S,
+    named_property_formulae: &[(String, String)],
+    property_results: &[S],
+) -> Result<ClassificationResult<S>, ClassifierError> {
+    build_classification_result_with_limit(
+        assertion_formulae,
+        all_valid_colors,
+        named_property_formulae,
+        property_results,
+        DEFAULT_MAX_CLASSES,
+    )
+}
+
+/// Default limit on the number of (mostly empty) categories that are enumerated exhaustively,
+/// i.e., all combinations are only enumerated for at most 20 properties.
+pub const DEFAULT_MAX_CLASSES: usize = 1 << 20;
+
+/// True if the `2^n` combinations of `num_properties` properties are more than `max_classes`
+/// (or too many to enumerate at all, i.e., for 31 or more properties).
+pub fn exceeds_max_classes(num_properties: usize, max_classes: usize) -> bool {
+    num_properties >= 31 || (1usize << num_properties) > max_classes
+}
+
+/// Number of the combinations of `arity` outcomes of `num_properties` properties (i.e., of the
+/// categories of the exhaustive enumeration), saturated at [usize::MAX].
+pub(crate) fn num_combinations(arity: usize, num_properties: usize) -> usize {
+    u32::try_from(num_properties)
+        .ok()
+        .and_then(|n| arity.checked_pow(n))
+        .unwrap_or(usize::MAX)
+}
+
+/// Number of the binary combinations (of the same length) up to and including `validity`, in
+/// the order of the categories (saturated at [usize::MAX]).
+pub(crate) fn num_combinations_through(validity: &[bool]) -> usize {
+    validity
+        .iter()
+        .fold(0usize, |rank, valid| {
+            rank.saturating_mul(2).saturating_add(usize::from(*valid))
+        })
+        .saturating_add(1)
+}
+
+/// Same as [build_classification_result], but all `2^n` combinations are only enumerated if
+/// there are at most `max_classes` of them (see [exceeds_max_classes]). Otherwise, only the
+/// categories that actually occur are computed, which prevents an accidental enumeration of
+/// (e.g.) the `2^30` combinations of 30 properties, most of which are empty.
+pub fn build_classification_result_with_limit<S: ClassifiedSet>(
+    assertion_formulae: &[String],
+    all_valid_colors: &S,
+    named_property_formulae: &[(String, String)],
+    property_results: &[S],
+    max_classes: usize,
+) -> Result<ClassificationResult<S>, ClassifierError> {
+    let nothing_holds = property_results
+        .iter()
+        .all(|property| property.intersect(all_valid_colors).is_empty());
+    let mode = if nothing_holds {
+        EnumerationMode::Discriminating
+    } else if exceeds_max_classes(property_results.len(), max_classes) {
+        EnumerationMode::Observed
+    } else {
+        EnumerationMode::Exhaustive
+    };
+    build_classification_result_with_mode(
+        assertion_formulae,
+        all_valid_colors,
+        named_property_formulae,
+        property_results,
+        mode,
+        None,
+    )
+}
+
+/// Same as [build_classification_result], but the categories are enumerated using the given
+/// [EnumerationMode].
+///
+/// If `progress` is given, it is called as `progress(classes_done, classes_total)` whenever
+/// some categories are finished, where `classes_total` is the number of all `2^n` combinations
+/// of the properties in every mode (see [num_combinations]). The empty categories count as
+/// well: once a branch of the enumeration turns out to be empty, all the categories below it
+/// are finished at once. In the observed mode, the combinations that were not observed are only
+/// known to be empty at the end, so `classes_done` counts the categories found so far and then
+/// jumps to `classes_total`.
+pub fn build_classification_result_with_mode<S: ClassifiedSet>(
+    assertion_formulae: &[String],
+    all_valid_colors: &S,
+    named_property_formulae: &[(String, String)],
+    property_results: &[S],
+    mode: EnumerationMode,
+    progress: Option<&dyn Fn(usize, usize)>,
+) -> Result<ClassificationResult<S>, ClassifierError> {
+    if named_property_formulae.len() != property_results.len() {
+        return Err(ClassifierError::PropertyCountMismatch {
+            names: named_property_formulae.len(),
+            results: property_results.len(),
+        });
+    }
+
+    let properties = named_property_formulae
+        .iter()
+        .zip(property_results.iter())
+        .map(|(named_formula, colors)| property_result(all_valid_colors, named_formula, colors))
+        .collect();
+
+    let enumeration_start = Instant::now();
+    let categories = match mode {
+        EnumerationMode::Exhaustive => {
+            enumerate_all_categories(all_valid_colors, property_results, &[], progress)
+        }
+        EnumerationMode::Discriminating => {
+            let constants: Vec<Option<bool>> = property_results
+                .iter()
+                .map(|colors| constant_validity(all_valid_colors, colors))
+                .collect();
+            if let Some(validity) = constants.iter().copied().collect::<Option<Vec<bool>>>() {
+                // All properties are constant, so there is only one (consistent) category.
+                if let Some(progress) = progress {
+                    let total = num_combinations(2, property_results.len());
+                    progress(total, total);
+                }
+                vec![Category {
+                    validity,
+                    colors: all_valid_colors.clone(),
+                    cardinality: all_valid_colors.approx_cardinality(),
+                    exact_cardinality: all_valid_colors.exact_cardinality(),
+                }]
+            } else {
+                enumerate_all_categories(all_valid_colors, property_results, &constants, progress)
+            }
+        }
+        EnumerationMode::Observed => {
+            enumerate_observed_categories(all_valid_colors, property_results, progress)
+        }
+    };
+    let enumeration_time = enumeration_start.elapsed();
+    let peak_bdd_size = std::iter::once(all_valid_colors)
+        .chain(property_results)
+        .chain(categories.iter().map(|category| &category.colors))
+        .map(|colors| colors.as_bdd().size())
+        .max()
+        .unwrap_or(0);
+
+    Ok(ClassificationResult {
+        assertion_formulae: assertion_formulae.to_vec(),
+        valid_colors: all_valid_colors.clone(),
+        valid_cardinality: all_valid_colors.approx_cardinality(),
+        valid_exact_cardinality: all_valid_colors.exact_cardinality(),
+        total_cardinality: None,
+        assertion_cardinalities: Vec::new(),
+        properties,
+        projected_properties: Vec::new(),
+        num_hctl_vars: 0,
+        formula_hctl_vars: Vec::new(),
+        enumeration_time,
+        peak_bdd_size,
+        categories,
+        cardinalities_only: false,
+    })
+}
+
+/// Role of a formula in a combined list of [TaggedProperty] results.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PropertyRole {
+    /// The formula restricts the valid colors (i.e., it must be satisfied).
+    Assertion,
+    /// The formula is used to decompose the valid colors into categories.
+    Classify,
+}
+
+/// Precomputed result of one formula, tagged with its [PropertyRole].
+#[derive(Clone)]
+pub struct TaggedProperty<S = GraphColors> {
+    /// Name of the formula (only used for the classification properties).
+    pub name: String,
+    /// HCTL formula.
+    pub formula: String,
+    /// Role of the formula in the classification.
+    pub role: PropertyRole,
+    /// Colors (universally) satisfying the formula.
+    pub colors: S,
+}
+
+/// Same as [build_classification_result], but the assertions and the classification properties
+/// are given in one list of `tagged_properties`, so that the role of a formula is changed just
+/// by its tag.
+///
+/// The valid colors are the `unit_colors` satisfying all the [PropertyRole::Assertion]
+/// formulae, and they are decomposed by the [PropertyRole::Classify] formulae (in the order of
+/// the list). The colors of the classification properties are restricted to the valid colors,
+/// so that their cardinalities only count the valid colors.
+pub fn build_tagged_classification_result<S: ClassifiedSet>(
+    unit_colors: &S,
+    tagged_properties: &[TaggedProperty<S>],
+) -> Result<ClassificationResult<S>, ClassifierError> {
+    let mut assertion_formulae = Vec::new();
+    let mut valid_colors = unit_colors.clone();
+    let mut named_property_formulae = Vec::new();
+    let mut property_results = Vec::new();
+    for property in tagged_properties {
+        match property.role {
+            PropertyRole::Assertion => {
+                assertion_formulae.push(property.formula.clone());
+                valid_colors = valid_colors.intersect(&property.colors);
+            }
+            PropertyRole::Classify => {
+                named_property_formulae.push((property.name.clone(), property.formula.clone()));
+                property_results.push(&property.colors);
+            }
+        }
+    }
+    // The assertions can follow the properties, so the colors are only restricted at the end.
+    let property_results: Vec<S> = property_results
+        .into_iter()
+        .map(|colors| colors.intersect(&valid_colors))
+        .collect();
+    build_classification_result(
+        &assertion_formulae,
+        &valid_colors,
+        &named_property_formulae,
+        &property_results,
+    )
+}
+
+/// Same as [build_classification_result], but only the properties at the `selected` indices
+/// (in the given order) take part in the decomposition, i.e., the validity vectors of the
+/// categories have `selected.len()` bits.
+///
+/// The remaining properties are projected out: they are only listed (with their cardinalities)
+/// in [ClassificationResult::projected_properties].
+pub fn build_projected_classification_result(
+    assertion_formulae: &[String],
+    all_valid_colors: &GraphColors,
+    named_property_formulae: &[(String, String)],
+    property_results: &[GraphColors],
+    selected: &[usize],
+) -> Result<ClassificationResult, ClassifierError> {
+    if named_property_formulae.len() != property_results.len() {
+        return Err(ClassifierError::PropertyCountMismatch {
+            names: named_property_formulae.len(),
+            results: property_results.len(),
+        });
+    }
+    for (i, index) in selected.iter().enumerate() {
+        if *index >= property_results.len() {
+            return Err(ClassifierError::InvalidPropertySelection(format!(
+                "index {index} is out of range for {} properties",
+                property_results.len()
+            )));
+        }
+        if selected[..i].contains(index) {
+            return Err(ClassifierError::InvalidPropertySelection(format!(
+                "index {index} is selected more than once"
+            )));
+        }
+    }
+
+    let selected_formulae: Vec<(String, String)> = selected
+        .iter()
+        .map(|i| named_property_formulae[*i].clone())
+        .collect();
+    let selected_results: Vec<GraphColors> = selected
+        .iter()
+        .map(|i| property_results[*i].clone())
+        .collect();
+    let mut result = build_classification_result(
+        assertion_formulae,
+        all_valid_colors,
+        &selected_formulae,
+        &selected_results,
+    )?;
+    result.projected_properties = named_property_formulae
+        .iter()
+        .zip(property_results.iter())
+        .enumerate()
+        .filter(|(i, _)| !selected.contains(i))
+        .map(|(_, (named_formula, colors))| {
+            property_result(all_valid_colors, named_formula, colors)
+        })
+        .collect();
+    Ok(result)
+}
+
+/// Find the indices of the `named_property_formulae` in the canonical `order` of their names,
+/// so that the bit positions of the properties do not depend on the order in which they were
+/// given (the indices can be used as the selection of [build_projected_classification_result]).
+///
+/// The `order` must contain the name of each property exactly once. Unknown, missing or
+/// repeated names are reported as [ClassifierError::InvalidPropertySelection].
+pub fn property_order_indices(
+    named_property_formulae: &[(String, String)],
+    order: &[String],
+) -> Result<Vec<usize>, ClassifierError> {
+    let mut indices = Vec::with_capacity(order.len());
+    for name in order {
+        let Some(index) = named_property_formulae
+            .iter()
+            .position(|(it, _)| it == name)
+        else {
+            return Err(ClassifierError::InvalidPropertySelection(format!(
+                "unknown property `{name}` in the property order"
+            )));
+        };
+        if indices.contains(&index) {
+            return Err(ClassifierError::InvalidPropertySelection(format!(
+                "property `{name}` appears more than once in the property order"
+            )));
+        }
+        indices.push(index);
+    }
+    if let Some((name, _)) = named_property_formulae
+        .iter()
+        .enumerate()
+        .find(|(i, _)| !indices.contains(i))
+        .map(|(_, named_formula)| named_formula)
+    {
+        return Err(ClassifierError::InvalidPropertySelection(format!(
+            "property `{name}` is missing in the property order"
+        )));
+    }
+    Ok(indices)
+}
+
+/// Summarize the `colors` satisfying the property given by its `(name, formula)` pair.
+fn property_result<S: ClassifiedSet>(
+    all_valid_colors: &S,
+    (name, formula): &(String, String),
+    colors: &S,
+) -> PropertyResult<S> {
+    PropertyResult {
+        name: name.clone(),
+        formula: formula.clone(),
+        colors: colors.clone(),
+        cardinality: colors.approx_cardinality(),
+        exact_cardinality: colors.exact_cardinality(),
+        discriminating: constant_validity(all_valid_colors, colors).is_none(),
+    }
+}
+
+/// Verify that the categories of the `result` form an exact partition of its valid colors, i.e.,
+/// the categories are pairwise disjoint and together cover all the valid colors.
+pub fn verify_partition<S: ClassifiedSet>(
+    result: &ClassificationResult<S>,
+) -> Result<(), ClassifierError> {
+    let valid_colors = &result.valid_colors;
+    let mut covered = valid_colors.minus(valid_colors);
+    for category in &result.categories {
+        // Disjointness with the union of the previous categories implies pairwise disjointness.
+        if !category.colors.intersect(&covered).is_empty() {
+            return Err(ClassifierError::PartitionInvariantViolated(format!(
+                "category `{:?}` overlaps with other categories",
+                category.validity
+            )));
+        }
+        covered = covered.union(&category.colors);
+    }
+    if !colors_equal(&covered, valid_colors) {
+        return Err(ClassifierError::PartitionInvariantViolated(format!(
+            "categories cover {:.0} {elements}, but there are {:.0} valid {elements}",
+            covered.approx_cardinality(),
+            valid_colors.approx_cardinality(),
+            elements = S::ELEMENTS
+        )));
+    }
+    Ok(())
+}
+
+/// Logical relationship of two properties (given by their indices in
+/// [ClassificationResult::properties]), see [property_relations].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PropertyRelation {
+    /// The two properties never hold together, i.e., they are mutually exclusive.
+    NeverBothTrue(usize, usize),
+    /// The two properties never fail together, i.e., at least one of them always holds.
+    NeverBothFalse(usize, usize),
+}
+
+/// Find the pairs of properties that never hold together, or never fail together, in any
+/// non-empty category of the `result`.
+///
+/// The non-discriminating properties are skipped, since their relationships with all other
+/// properties are trivial. The pairs are listed in the order of the property indices.
+pub fn property_relations<S: ClassifiedSet>(
+    result: &ClassificationResult<S>,
+) -> Vec<PropertyRelation> {
+    let validities: Vec<&Vec<bool>> = result
+        .non_empty_categories()
+        .map(|category| &category.validity)
+        .collect();
+    let discriminating: Vec<usize> = result
+        .properties
+        .iter()
+        .enumerate()
+        .filter(|(_, property)| property.discriminating)
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut relations = Vec::new();
+    for (k, i) in discriminating.iter().enumerate() {
+        for j in &discriminating[(k + 1)..] {
+            let occurs = |value: bool| {
+                validities
+                    .iter()
+                    .any(|it| it[*i] == value && it[*j] == value)
+            };
+            if !occurs(true) {
+                relations.push(PropertyRelation::NeverBothTrue(*i, *j));
+            }
+            if !occurs(false) {
+                relations.push(PropertyRelation::NeverBothFalse(*i, *j));
+            }
+        }
+    }
+    relations
+}
+
+/// Find the implications between the properties of the `result`, i.e., the ordered pairs
+/// `(i, j)` such that the `j`-th property holds in every non-empty category in which the `i`-th
+/// property holds (so it holds for every valid color satisfying the `i`-th property).
+///
+/// Same as in [property_relations], the non-discriminating properties are skipped. The pairs
+/// are listed in the order of the property indices.
+pub fn property_implications<S: ClassifiedSet>(
+    result: &ClassificationResult<S>,
+) -> Vec<(usize, usize)> {
+    let validities: Vec<&Vec<bool>> = result
+        .non_empty_categories()
+        .map(|category| &category.validity)
+        .collect();
+    let discriminating: Vec<usize> = result
+        .properties
+        .iter()
+        .enumerate()
+        .filter(|(_, property)| property.discriminating)
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut implications = Vec::new();
+    for i in &discriminating {
+        for j in discriminating.iter().filter(|j| *j != i) {
+            if validities.iter().all(|it| !it[*i] || it[*j]) {
+                implications.push((*i, *j));
+            }
+        }
+    }
+    implications
+}
+
+/// Change of the cardinality of a class which is non-empty in both compared results (see
+/// [ClassificationDiff]).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClassChange {
+    /// Validity vector of the class.
+    pub validity: Vec<bool>,
+    /// Exact number of colors of the class in the first result.
+    pub before: BigInt,
+    /// Exact number of colors of the class in the second result.
+    pub after: BigInt,
+}
+
+impl ClassChange {
+    /// Difference of the cardinalities, i.e., `after - before`.
+    pub fn delta(&self) -> BigInt {
+        &self.after - &self.before
+    }
+}
+
+/// Difference of two classification results (see [diff_results]). All lists are sorted by the
+/// validity vectors of the classes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClassificationDiff {
+    /// Classes that are only non-empty in the second result, with their exact cardinality.
+    pub added: Vec<(Vec<bool>, BigInt)>,
+    /// Classes that are only non-empty in the first result, with their exact cardinality.
+    pub removed: Vec<(Vec<bool>, BigInt)>,
+    /// Classes that are non-empty in both results, but with a different cardinality.
+    pub changed: Vec<ClassChange>,
+}
+
+impl ClassificationDiff {
+    /// Check if both results have the same non-empty classes with the same cardinalities.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare the non-empty classes of the results `a` and `b` (e.g., before and after a change
+/// of a property formula). The classes are matched by their validity vectors (i.e., by their bit
+/// strings), so with a different number of properties, no classes match.
+///
+/// Only the cardinalities are compared, not the colors themselves (the results may be encoded
+/// in different symbolic graphs).
+pub fn diff_results<S: ClassifiedSet>(
+    a: &ClassificationResult<S>,
+    b: &ClassificationResult<S>,
+) -> ClassificationDiff {
+    let classes = |result: &ClassificationResult<S>| -> BTreeMap<Vec<bool>, BigInt> {
+        result
+            .non_empty_categories()
+            .map(|category| {
+                (
+                    category.validity.clone(),
+                    category.exact_cardinality.clone(),
+                )
+            })
+            .collect()
+    };
+    let before = classes(a);
+    let mut after = classes(b);
+    let mut diff = ClassificationDiff::default();
+    for (validity, before) in before {
+        match after.remove(&validity) {
+            None => diff.removed.push((validity, before)),
+            Some(after) if after != before => diff.changed.push(ClassChange {
+                validity,
+                before,
+                after,
+            }),
+            Some(_) => (),
+        }
+    }
+    diff.added = after.into_iter().collect();
+    diff
+}
+
+/// Compute the symmetric matrix of the pairwise overlaps of the properties of the `result`, i.e.,
+/// the exact number of valid colors satisfying both the `i`-th and the `j`-th property. The
+/// diagonal contains the numbers of valid colors satisfying the individual properties.
+pub fn property_overlaps(result: &ClassificationResult) -> Vec<Vec<BigInt>> {
+    let valid_properties: Vec<GraphColors> = result
+        .properties
+        .iter()
+        .map(|property| property.colors.intersect(&result.valid_colors))
+        .collect();
+    let num_properties = valid_properties.len();
+    let mut overlaps = vec![vec![BigInt::from(0); num_properties]; num_properties];
+    for i in 0..num_properties {
+        for j in i..num_properties {
+            let overlap = valid_properties[i]
+                .intersect(&valid_properties[j])
+                .exact_cardinality();
+            overlaps[j][i] = overlap.clone();
+            overlaps[i][j] = overlap;
+        }
+    }
+    overlaps
+}
+
+/// Compute a preview of the decomposition: the validity vector and the (approximate) cardinality
+/// of each non-empty category, in the order of the validity vectors.
+///
+/// Unlike [build_classification_result], the color sets of the categories are not kept, so this
+/// can be used to check the number and sizes of the categories before the full output is written.
+pub fn classify_summary(
+    all_valid_colors: &GraphColors,
+    property_results: &[GraphColors],
+) -> Vec<(Vec<bool>, f64)> {
+    let categories = if property_results.len() < 31 {
+        enumerate_non_empty_categories(all_valid_colors, property_results, None)
+    } else {
+        enumerate_observed_categories(all_valid_colors, property_results, None)
+    };
+    categories
+        .into_iter()
+        .map(|category| (category.validity, category.cardinality))
+        .collect()
+}
+
+/// The maximal number of properties for which [classify_cardinalities] can compute the class
+/// cardinalities (it keeps a table with one entry per each subset of properties).
+pub const MAX_STATS_ONLY_PROPERTIES: usize = 20;
+
+/// Compute the exact cardinality of each non-empty category without materializing the color
+/// sets of the categories, in the order of the validity vectors.
+///
+/// Only the intersections of the valid colors with the sets of properties that hold together
+/// are computed (an empty intersection also rules out all its supersets). The cardinalities of
+/// the categories are then obtained by inclusion-exclusion over the cardinalities of these
+/// intersections. For more than [MAX_STATS_ONLY_PROPERTIES] properties, the result is `None`.
+pub fn classify_cardinalities(
+    all_valid_colors: &GraphColors,
+    property_results: &[GraphColors],
+) -> Option<Vec<(Vec<bool>, BigInt)>> {
+    let num_properties = property_results.len();
+    if num_properties > MAX_STATS_ONLY_PROPERTIES {
+        return None;
+    }
+
+    // `counts[mask]` is the number of valid colors satisfying (at least) the properties in `mask`.
+    let mut counts = vec![BigInt::from(0); 1 << num_properties];
+    let mut stack = vec![(0usize, 0usize, all_valid_colors.clone())];
+    while let Some((mask, next_property, colors)) = stack.pop() {
+        counts[mask] = colors.exact_cardinality();
+        for (i, property) in property_results.iter().enumerate().skip(next_property) {
+            let intersection = colors.intersect(property);
+            if !intersection.is_empty() {
+                stack.push((mask | (1 << i), i + 1, intersection));
+            }
+        }
+    }
+
+    // Turn the "at least" counts into the "exactly" counts (Möbius inversion over supersets).
+    for i in 0..num_properties {
+        for mask in 0..counts.len() {
+            if mask & (1 << i) == 0 {
+                let superset = counts[mask | (1 << i)].clone();
+                counts[mask] -= superset;
+            }
+        }
+    }
+
+    let mut categories: Vec<(Vec<bool>, BigInt)> = counts
+        .into_iter()
+        .enumerate()
+        .filter(|(_, count)| *count != BigInt::from(0))
+        .map(|(mask, count)| {
+            let validity = (0..num_properties).map(|i| mask & (1 << i) != 0).collect();
+            (validity, count)
+        })
+        .collect();
+    categories.sort();
+    Some(categories)
+}
+
+/// Same as [build_classification_result], but only the cardinalities of the non-empty
+/// categories are computed by [classify_cardinalities], and their colors are left empty (see
+/// [ClassificationResult::cardinalities_only]). For more than [MAX_STATS_ONLY_PROPERTIES]
+/// properties, the result is `None`.
+pub fn build_cardinality_classification_result(
+    assertion_formulae: &[String],
+    all_valid_colors: &GraphColors,
+    named_property_formulae: &[(String, String)],
+    property_results: &[GraphColors],
+) -> Result<Option<ClassificationResult>, ClassifierError> {
+    if named_property_formulae.len() != property_results.len() {
+        return Err(ClassifierError::PropertyCountMismatch {
+            names: named_property_formulae.len(),
+            results: property_results.len(),
+        });
+    }
+
+    let enumeration_start = Instant::now();
+    let Some(cardinalities) = classify_cardinalities(all_valid_colors, property_results) else {
+        return Ok(None);
+    };
+    let enumeration_time = enumeration_start.elapsed();
+    let empty_colors = all_valid_colors.minus(all_valid_colors);
+    let categories = cardinalities
+        .into_iter()
+        .map(|(validity, exact_cardinality)| Category {
+            validity,
+            colors: empty_colors.clone(),
+            cardinality: exact_cardinality.to_f64().unwrap_or(f64::INFINITY),
+            exact_cardinality,
+        })
+        .collect();
+    let properties = named_property_formulae
+        .iter()
+        .zip(property_results.iter())
+        .map(|(named_formula, colors)| property_result(all_valid_colors, named_formula, colors))
+        .collect();
+    let peak_bdd_size = std::iter::once(all_valid_colors)
+        .chain(property_results)
+        .map(|colors| colors.as_bdd().size())
+        .max()
+        .unwrap_or(0);
+
+    Ok(Some(ClassificationResult {
+        assertion_formulae: assertion_formulae.to_vec(),
+        valid_colors: all_valid_colors.clone(),
+        valid_cardinality: all_valid_colors.approx_cardinality(),
+        valid_exact_cardinality: all_valid_colors.exact_cardinality(),
+        total_cardinality: None,
+        assertion_cardinalities: Vec::new(),
+        properties,
+        projected_properties: Vec::new(),
+        num_hctl_vars: 0,
+        formula_hctl_vars: Vec::new(),
+        enumeration_time,
+        peak_bdd_size,
+        categories,
+        cardinalities_only: true,
+    }))
+}
+
+/// Update a previously computed decomposition (e.g., loaded by `read_classification_archive`)
+/// after the results of some properties changed.
+///
+/// Each of the `changed_properties` is given by its index (i.e., bit position in the validity
+/// vectors) and its new color set. Only the bits of the changed properties are recomputed:
+/// every baseline category is split along the new property results, and the parts that end up
+/// with the same validity are merged. A category that is not affected by the change (i.e.,
+/// it is fully inside or fully outside the new color set, with the same validity as before)
+/// is carried over unchanged.
+///
+/// The resulting non-empty categories are returned in the order of their validity vectors.
+/// Fails if the baseline validity vectors have different lengths, or if some index of the
+/// changed properties is not a bit position in them.
+pub fn reclassify_changed_properties(
+    baseline: &[(Vec<bool>, GraphColors)],
+    changed_properties: &[(usize, GraphColors)],
+) -> Result<Vec<(Vec<bool>, GraphColors)>, ClassifierError> {
+    let num_properties = baseline.first().map_or(0, |(validity, _)| validity.len());
+    if let Some((validity, _)) = baseline
+        .iter()
+        .find(|(validity, _)| validity.len() != num_properties)
+    {
+        return Err(ClassifierError::InvalidPropertySelection(format!(
+            "baseline category `{validity:?}` does not have {num_properties} properties"
+        )));
+    }
+    if let Some((index, _)) = changed_properties
+        .iter()
+        .find(|(index, _)| *index >= num_properties)
+    {
+        return Err(ClassifierError::InvalidPropertySelection(format!(
+            "property index {index} is out of range for {num_properties} properties"
+        )));
+    }
+    let mut categories = baseline.to_vec();
+    for (index, property) in changed_properties {
+        let mut updated: BTreeMap<Vec<bool>, GraphColors> = BTreeMap::new();
+        for (validity, colors) in categories {
+            let (invalid_colors, valid_colors) = split_by_property(colors, property);
+            for (is_valid, part) in [(false, invalid_colors), (true, valid_colors)] {
+                if part.is_empty() {
+                    continue;
+                }
+                let mut validity = validity.clone();
+                validity[*index] = is_valid;
+                match updated.entry(validity) {
+                    Entry::Vacant(entry) => {
+                        entry.insert(part);
+                    }
+                    Entry::Occupied(mut entry) => {
+                        let merged = entry.get().union(&part);
+                        entry.insert(merged);
+                    }
+                }
+            }
+        }
+        categories = updated.into_iter().collect();
+    }
+    Ok(categories)
+}
+
+/// If the `property` colors cover all `all_valid_colors` (or none of them), return the constant
+/// validity of the property (`true` or `false`, respectively). Otherwise, the property is
+/// discriminating and the result is `None`.
+fn constant_validity<S: ClassifiedSet>(all_valid_colors: &S, property: &S) -> Option<bool> {
+    let valid_property = all_valid_colors.intersect(property);
+    if valid_property.is_empty() {
+        Some(false)
+    } else if valid_property.exact_cardinality() == all_valid_colors.exact_cardinality() {
+        // Since `valid_property` is a subset of `all_valid_colors`, the sets are equal.
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Compute the colors of the category given by the `validity` vector.
+fn build_category_colors<S: ClassifiedSet>(
+    all_valid_colors: &S,
+    property_results: &[S],
+    validity: &[bool],
+) -> S {
+    let mut category_colors = all_valid_colors.clone();
+    for (set, is_valid) in property_results.iter().zip(validity.iter()) {
+        if *is_valid {
+            category_colors = category_colors.intersect(set);
+        } else {
+            category_colors = category_colors.minus(set);
+        }
+    }
+    category_colors
+}
+
+/// Split `colors` into the colors that do not satisfy the `property` and those that do.
+///
+/// If all colors fall into one of the two halves, `colors` are reused for that half without
+/// computing the (otherwise equivalent) difference.
+fn split_by_property<S: ClassifiedSet>(colors: S, property: &S) -> (S, S) {
+    let valid = colors.intersect(property);
+    if valid.is_empty() {
+        (colors, valid)
+    } else if valid.as_bdd() == colors.as_bdd() {
+        (colors.minus(&colors), colors)
+    } else {
+        (colors.minus(&valid), valid)
+    }
+}
+
+/// Enumerate all `2^n` categories explicitly, including the empty ones.
+///
+/// The non-empty categories are computed by [enumerate_non_empty_categories], the remaining
+/// ones are simply filled in as empty sets. If `constants` are given (one per property), the
+/// combinations where a property with a constant validity takes the other value are skipped.
+fn enumerate_all_categories<S: ClassifiedSet>(
+    all_valid_colors: &S,
+    property_results: &[S],
+    constants: &[Option<bool>],
+    progress: Option<&dyn Fn(usize, usize)>,
+) -> Vec<Category<S>> {
+    // If this is broken, the number of properties is too high
+    // to enumerate the combinations explicitly.
+    assert!(property_results.len() < 31);
+    let number_of_combinations: u64 = 1 << property_results.len();
+
+    let empty_colors = all_valid_colors.minus(all_valid_colors);
+    let mut non_empty =
+        enumerate_non_empty_categories(all_valid_colors, property_results, progress)
+            .into_iter()
+            .peekable();
+
+    let mut categories = Vec::with_capacity(number_of_combinations as usize);
+    for i in 0..number_of_combinations {
+        let validity = int_to_bool_vec(i, property_results.len());
+        let is_inconsistent = constants
+            .iter()
+            .zip(&validity)
+            .any(|(constant, valid)| constant.is_some_and(|it| it != *valid));
+        if is_inconsistent {
+            // Such categories are always empty.
+            continue;
+        }
+        match non_empty.next_if(|category| category.validity == validity) {
+            Some(category) => categories.push(category),
+            None => categories.push(Category {
+                validity,
+                colors: empty_colors.clone(),
+                cardinality: 0.0,
+                exact_cardinality: BigInt::from(0),
+            }),
+        }
+    }
+    categories
+}
+
+/// Enumerate only the non-empty categories, in the order of their validity vectors.
+///
+/// This is the multi-threaded version of the enumeration (enabled by the `parallel` feature):
+/// the two branches of each property are explored in parallel by [split_non_empty_categories].
+/// The `progress` is reported in terms of all `2^n` combinations (see
+/// [build_classification_result_with_mode]) after each finished branch, always by the calling
+/// thread (so that the callback does not have to be thread-safe).
+#[cfg(feature = "parallel")]
+fn enumerate_non_empty_categories<S: ClassifiedSet>(
+    all_valid_colors: &S,
+    property_results: &[S],
+    progress: Option<&dyn Fn(usize, usize)>,
+) -> Vec<Category<S>> {
+    let Some(progress) = progress else {
+        return split_non_empty_categories(
+            all_valid_colors.clone(),
+            Vec::new(),
+            property_results,
+            None,
+        );
+    };
+    // The workers send the number of combinations of each finished branch, and the channel is
+    // closed once the whole enumeration is done (or has panicked).
+    let total = num_combinations(2, property_results.len());
+    let (finished_sender, finished) = std::sync::mpsc::channel();
+    std::thread::scope(|scope| {
+        let worker = scope.spawn(move || {
+            split_non_empty_categories(
+                all_valid_colors.clone(),
+                Vec::new(),
+                property_results,
+                Some(&finished_sender),
+            )
+        });
+        let mut done = 0usize;
+        for combinations in finished {
+            done = done.saturating_add(combinations);
+            progress(done, total);
+        }
+        worker
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+/// Recursively split `colors` (already constrained by the first `validity.len()` properties)
+/// into the non-empty categories, exploring the two branches of the next property in parallel.
+/// The number of combinations of each finished branch is sent to `finished`, if given.
+#[cfg(feature = "parallel")]
+fn split_non_empty_categories<S: ClassifiedSet>(
+    colors: S,
+    validity: Vec<bool>,
+    property_results: &[S],
+    finished: Option<&std::sync::mpsc::Sender<usize>>,
+) -> Vec<Category<S>> {
+    if colors.is_empty() || validity.len() == property_results.len() {
+        if let Some(finished) = finished {
+            // The receiver only stops listening once the enumeration is done.
+            let _ = finished.send(num_combinations(2, property_results.len() - validity.len()));
+        }
+    }
+    if colors.is_empty() {
+        return Vec::new();
+    }
+    let Some(property) = property_results.get(validity.len()) else {
+        return vec![Category {
+            validity,
+            cardinality: colors.approx_cardinality(),
+            exact_cardinality: colors.exact_cardinality(),
+            colors,
+        }];
+    };
+
+    let mut valid = validity.clone();
+    valid.push(true);
+    let mut invalid = validity;
+    invalid.push(false);
+    let (invalid_colors, valid_colors) = split_by_property(colors, property);
+    let (mut categories, valid_categories) = rayon::join(
+        || split_non_empty_categories(invalid_colors, invalid, property_results, finished),
+        || split_non_empty_categories(valid_colors, valid, property_results, finished),
+    );
+    categories.extend(valid_categories);
+    categories
+}
+
+/// Enumerate only the non-empty categories, in the order of their validity vectors.
+///
+/// The categories are collected from the depth-first worklist of [classify_iter], so the amount
+/// of work is proportional to the number of non-empty categories (times the number of
+/// properties), not to the number of all combinations.
+///
+/// The `progress` is reported in terms of all `2^n` combinations (see
+/// [build_classification_result_with_mode]) after each finished branch.
+#[cfg(not(feature = "parallel"))]
+fn enumerate_non_empty_categories<S: ClassifiedSet>(
+    all_valid_colors: &S,
+    property_results: &[S],
+    progress: Option<&dyn Fn(usize, usize)>,
+) -> Vec<Category<S>> {
+    classify_iter(all_valid_colors, property_results)
+        .with_progress(progress)
+        .map(|(validity, colors)| Category {
+            validity,
+            cardinality: colors.approx_cardinality(),
+            exact_cardinality: colors.exact_cardinality(),
+            colors,
+        })
+        .collect()
+}
+
+/// Depth-first worklist of partially constrained sets, which are split by the `N` outcomes of
+/// one property after another (e.g., the colors that do not and that do satisfy the property),
+/// and a branch is only split further if its set is non-empty. The branches are explored in
+/// the order of the outcomes, so the categories are finished in the order of their validity
+/// vectors.
+struct SplitWorklist<'a, S, O, V, const N: usize> {
+    /// Outcomes of each property.
+    properties: &'a [O],
+    /// Split a set by the outcomes of one property.
+    split: fn(&O, S) -> [(V, S); N],
+    worklist: Vec<(Vec<V>, S)>,
+}
+
+impl<'a, S: ClassifiedSet, O, V: Clone, const N: usize> SplitWorklist<'a, S, O, V, N> {
+    fn new(all_valid_colors: &S, properties: &'a [O], split: fn(&O, S) -> [(V, S); N]) -> Self {
+        SplitWorklist {
+            properties,
+            split,
+            worklist: vec![(Vec::new(), all_valid_colors.clone())],
+        }
+    }
+
+    /// The next finished branch: either one category (all properties are decided), or an empty
+    /// set whose remaining properties are not split on (all its categories are empty). The
+    /// branch is returned together with the number of combinations it finishes.
+    fn next_finished(&mut self) -> Option<(Vec<V>, S, usize)> {
+        while let Some((validity, colors)) = self.worklist.pop() {
+            let property = self.properties.get(validity.len());
+            let Some(property) = property.filter(|_| !colors.is_empty()) else {
+                let finished = num_combinations(N, self.properties.len() - validity.len());
+                return Some((validity, colors, finished));
+            };
+            // The branches are pushed in reverse, so that the first outcome is explored first.
+            for (outcome, branch_colors) in (self.split)(property, colors).into_iter().rev() {
+                let mut branch = validity.clone();
+                branch.push(outcome);
+                self.worklist.push((branch, branch_colors));
+            }
+        }
+        None
+    }
+}
+
+/// The two outcomes (`false` and `true`) of the `property` for a [SplitWorklist] (see
+/// [split_by_property]).
+fn split_binary<S: ClassifiedSet>(property: &S, colors: S) -> [(bool, S); 2] {
+    let (invalid_colors, valid_colors) = split_by_property(colors, property);
+    [(false, invalid_colors), (true, valid_colors)]
+}
+
+/// Lazy iterator over the non-empty categories, see [classify_iter].
+pub struct ClassIter<'a, S = GraphColors> {
+    branches: SplitWorklist<'a, S, S, bool, 2>,
+    /// Callback with the number of finished combinations (out of all `2^n`).
+    progress: Option<&'a dyn Fn(usize, usize)>,
+    /// Number of the combinations finished so far.
+    done: usize,
+}
+
+impl<'a, S: ClassifiedSet> ClassIter<'a, S> {
+    /// Report the `progress` after each finished branch (see [enumerate_non_empty_categories]).
+    #[cfg(not(feature = "parallel"))]
+    fn with_progress(mut self, progress: Option<&'a dyn Fn(usize, usize)>) -> Self {
+        self.progress = progress;
+        self
+    }
+}
+
+impl<S: ClassifiedSet> Iterator for ClassIter<'_, S> {
+    type Item = (Vec<bool>, S);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let total = num_combinations(2, self.branches.properties.len());
+        while let Some((validity, colors, finished)) = self.branches.next_finished() {
+            // Either a whole group of empty categories is pruned, or we reached one category.
+            self.done = self.done.saturating_add(finished);
+            if let Some(progress) = self.progress {
+                progress(self.done, total);
+            }
+            if !colors.is_empty() {
+                return Some((validity, colors));
+            }
+        }
+        None
+    }
+}
+
+/// Lazily enumerate the non-empty categories (their validity vectors and colors), in the order
+/// of their validity vectors.
+///
+/// The iterator keeps a depth-first worklist of partially constrained sets: each step splits
+/// the next set by the next property (the `false` branch first) and drops the empty branches,
+/// so a category is only computed once the previous one has been consumed. All properties are
+/// split on, including the ones with a constant validity. Besides the current category, only
+/// the sets of at most `n` unexplored branches are kept in memory (for `n` properties).
+pub fn classify_iter<'a, S: ClassifiedSet>(
+    all_valid_colors: &S,
+    property_results: &'a [S],
+) -> ClassIter<'a, S> {
+    ClassIter {
+        branches: SplitWorklist::new(all_valid_colors, property_results, split_binary),
+        progress: None,
+        done: 0,
+    }
+}
+
+/// Enumerate only the non-empty categories. In each step, a witness color is picked from the
+/// remaining colors, its category is computed based on the properties it satisfies, and the whole
+/// category is then removed from the remaining colors.
+///
+/// The categories are returned in the same relative order as in the exhaustive enumeration.
+/// The `progress` is reported after each category in terms of all `2^n` combinations (see
+/// [build_classification_result_with_mode]), i.e., the remaining empty combinations are only
+/// finished at the end.
+fn enumerate_observed_categories<S: ClassifiedSet>(
+    all_valid_colors: &S,
+    property_results: &[S],
+    progress: Option<&dyn Fn(usize, usize)>,
+) -> Vec<Category<S>> {
+    let total = num_combinations(2, property_results.len());
+    let mut categories = Vec::new();
+    let mut remaining = all_valid_colors.clone();
+    while !remaining.is_empty() {
+        let witness = remaining.pick_singleton();
+        let validity: Vec<bool> = property_results
+            .iter()
+            .map(|set| !witness.intersect(set).is_empty())
+            .collect();
+
+        let category_colors = build_category_colors(&remaining, property_results, &validity);
+        remaining = remaining.minus(&category_colors);
+        categories.push(Category {
+            validity,
+            cardinality: category_colors.approx_cardinality(),
+            exact_cardinality: category_colors.exact_cardinality(),
+            colors: category_colors,
+        });
+        if let Some(progress) = progress {
+            progress(categories.len().min(total), total);
+        }
+    }
+    if let Some(progress) = progress {
+        progress(total, total);
+    }
+    // Ordering of `Vec<bool>` corresponds to the MSB-first binary numbers.
+    categories.sort_by(|x, y| x.validity.cmp(&y.validity));
+    categories
+}
+
+/// Outcome of one property in a [TernaryCategory].
+///
+/// The outcomes are ordered in the same way as their symbols in the class labels (`0`, `1`, `?`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Verdict {
+    /// The property is refuted for the colors (`0`).
+    Refuted,
+    /// The property is satisfied for the colors (`1`).
+    Satisfied,
+    /// The property is neither satisfied nor refuted, i.e., inconclusive (`?`).
+    Unknown,
+}
+
+impl Verdict {
+    /// Symbol of this outcome in the class labels.
+    pub fn symbol(self) -> char {
+        match self {
+            Verdict::Refuted => '0',
+            Verdict::Satisfied => '1',
+            Verdict::Unknown => '?',
+        }
+    }
+}
+
+/// One category of the ternary decomposition (see [build_ternary_classification_result]),
+/// i.e., the set of colors with exactly the same outcome of each property.
+#[derive(Clone)]
+pub struct TernaryCategory<S = GraphColors> {
+    /// Outcome of each property in this category, in the order of properties in the result.
+    pub validity: Vec<Verdict>,
+    /// Colors of this category.
+    pub colors: S,
+    /// Approximate number of colors in this category.
+    pub cardinality: f64,
+    /// Exact number of colors in this category.
+    pub exact_cardinality: BigInt,
+}
+
+/// In-memory result of the ternary classification (see [build_ternary_classification_result]).
+#[derive(Clone)]
+pub struct TernaryClassificationResult<S = GraphColors> {
+    /// List of assertion formulae.
+    pub assertion_formulae: Vec<String>,
+    /// All colors satisfying the assertion formulae.
+    pub valid_colors: S,
+    /// Approximate number of colors satisfying the assertion formulae.
+    pub valid_cardinality: f64,
+    /// Exact number of colors satisfying the assertion formulae.
+    pub valid_exact_cardinality: BigInt,
+    /// Names and formulae of the properties, in the order of the outcomes of the categories.
+    pub properties: Vec<(String, String)>,
+    /// Categories of the decomposition, in the order of their validity vectors. Empty
+    /// categories are only included if all `3^n` combinations were enumerated (see
+    /// [exceeds_max_ternary_classes]).
+    pub categories: Vec<TernaryCategory<S>>,
+}
+
+/// True if the `3^n` ternary combinations of `num_properties` properties are more than
+/// `max_classes` (see [exceeds_max_classes] for the `2^n` binary combinations).
+pub fn exceeds_max_ternary_classes(num_properties: usize, max_classes: usize) -> bool {
+    u32::try_from(num_properties)
+        .ok()
+        .and_then(|n| 3usize.checked_pow(n))
+        .map_or(true, |combinations| combinations > max_classes)
+}
+
+/// Decompose `all_valid_colors` into categories based on the outcome of each property, where
+/// the colors `satisfied` by the property have the outcome [Verdict::Satisfied], the colors
+/// `refuted` by it have [Verdict::Refuted], and the remaining colors are [Verdict::Unknown]
+/// (e.g., when a bounded check is inconclusive).
+///
+/// Same as [build_classification_result], but with three outcomes per property, i.e., all `3^n`
+/// combinations are enumerated if there are at most [DEFAULT_MAX_CLASSES] of them, and only
+/// the non-empty ones otherwise. Fails if the numbers of names, `satisfied` and `refuted` sets
+/// differ, or if some valid color both satisfies and refutes a property.
+pub fn build_ternary_classification_result<S: ClassifiedSet>(
+    assertion_formulae: &[String],
+    all_valid_colors: &S,
+    named_property_formulae: &[(String, String)],
+    satisfied: &[S],
+    refuted: &[S],
+) -> Result<TernaryClassificationResult<S>, ClassifierError> {
+    build_ternary_classification_result_with_limit(
+        assertion_formulae,
+        all_valid_colors,
+        named_property_formulae,
+        satisfied,
+        refuted,
+        DEFAULT_MAX_CLASSES,
+    )
+}
+
+/// Same as [build_ternary_classification_result], but all `3^n` combinations are only
+/// enumerated if there are at most `max_classes` of them (see [exceeds_max_ternary_classes]).
+pub fn build_ternary_classification_result_with_limit<S: ClassifiedSet>(
+    assertion_formulae: &[String],
+    all_valid_colors: &S,
+    named_property_formulae: &[(String, String)],
+    satisfied: &[S],
+    refuted: &[S],
+    max_classes: usize,
+) -> Result<TernaryClassificationResult<S>, ClassifierError> {
+    let names = named_property_formulae.len();
+    for results in [satisfied.len(), refuted.len()] {
+        if results != names {
+            return Err(ClassifierError::PropertyCountMismatch { names, results });
+        }
+    }
+    // The outcomes of each property, restricted to the valid colors.
+    let mut outcomes = Vec::new();
+    for ((name, _), (satisfied, refuted)) in named_property_formulae
+        .iter()
+        .zip(satisfied.iter().zip(refuted))
+    {
+        let satisfied = satisfied.intersect(all_valid_colors);
+        let refuted = refuted.intersect(all_valid_colors);
+        if !satisfied.intersect(&refuted).is_empty() {
+            return Err(ClassifierError::PartitionInvariantViolated(format!(
+                "Property `{name}` is both satisfied and refuted by some colors."
+            )));
+        }
+        let unknown = all_valid_colors.minus(&satisfied).minus(&refuted);
+        outcomes.push([
+            (Verdict::Refuted, refuted),
+            (Verdict::Satisfied, satisfied),
+            (Verdict::Unknown, unknown),
+        ]);
+    }
+
+    let mut categories = Vec::new();
+    let mut branches = SplitWorklist::new(all_valid_colors, &outcomes, |property, colors| {
+        let [(first, first_colors), (second, second_colors), (third, third_colors)] = property;
+        [
+            (*first, colors.intersect(first_colors)),
+            (*second, colors.intersect(second_colors)),
+            (*third, colors.intersect(third_colors)),
+        ]
+    });
+    while let Some((validity, colors, _)) = branches.next_finished() {
+        if !colors.is_empty() {
+            categories.push(TernaryCategory {
+                validity,
+                cardinality: colors.approx_cardinality(),
+                exact_cardinality: colors.exact_cardinality(),
+                colors,
+            });
+        }
+    }
+    if !exceeds_max_ternary_classes(outcomes.len(), max_classes) {
+        categories = fill_absent_ternary_categories(all_valid_colors, outcomes.len(), categories);
+    }
+
+    Ok(TernaryClassificationResult {
+        assertion_formulae: assertion_formulae.to_vec(),
+        valid_cardinality: all_valid_colors.approx_cardinality(),
+        valid_exact_cardinality: all_valid_colors.exact_cardinality(),
+        valid_colors: all_valid_colors.clone(),
+        properties: named_property_formulae.to_vec(),
+        categories,
+    })
+}
+
+/// Complete the `non_empty` ternary categories of `num_properties` properties (given in the
+/// order of their validity vectors) with the empty categories of the remaining combinations,
+/// similar to [enumerate_all_categories].
+fn fill_absent_ternary_categories<S: ClassifiedSet>(
+    all_valid_colors: &S,
+    num_properties: usize,
+    non_empty: Vec<TernaryCategory<S>>,
+) -> Vec<TernaryCategory<S>> {
+    const VERDICTS: [Verdict; 3] = [Verdict::Refuted, Verdict::Satisfied, Verdict::Unknown];
+    let empty_colors = all_valid_colors.minus(all_valid_colors);
+    let mut non_empty = non_empty.into_iter().peekable();
+    let number_of_combinations = 3usize.pow(num_properties as u32);
+    let mut categories = Vec::with_capacity(number_of_combinations);
+    for i in 0..number_of_combinations {
+        // The digits of `i` in base 3, the most significant one first.
+        let mut validity = vec![Verdict::Refuted; num_properties];
+        let mut rest = i;
+        for verdict in validity.iter_mut().rev() {
+            *verdict = VERDICTS[rest % 3];
+            rest /= 3;
+        }
+        match non_empty.next_if(|category| category.validity == validity) {
+            Some(category) => categories.push(category),
+            None => categories.push(TernaryCategory {
+                validity,
+                colors: empty_colors.clone(),
+                cardinality: 0.0,
+                exact_cardinality: BigInt::from(0),
+            }),
+        }
+    }
+    categories
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::categories::{
+        bool_vec_to_int, build_cardinality_classification_result, build_classification_result,
+        build_classification_result_with_limit, build_classification_result_with_mode,
+        build_projected_classification_result, build_tagged_classification_result,
+        build_ternary_classification_result, build_ternary_classification_result_with_limit,
+        classify_cardinalities, classify_iter, classify_summary, colors_equal, diff_results,
+        enumerate_non_empty_categories, exceeds_max_classes, exceeds_max_ternary_classes,
+        int_to_bool_vec, property_implications, property_order_indices, property_overlaps,
+        property_relations, reclassify_changed_properties, split_by_property, verify_partition,
+        Category, EnumerationMode, PropertyRelation, PropertyRole, TaggedProperty, Verdict,
+        DEFAULT_MAX_CLASSES, MAX_ABSENT_CLASS_PROPERTIES, MAX_STATS_ONLY_PROPERTIES,
+    };
+    use crate::errors::ClassifierError;
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::symbolic_async_graph::{GraphColors, SymbolicAsyncGraph};
+    use biodivine_lib_param_bn::BooleanNetwork;
+    use num_bigint::BigInt;
+    use std::cell::RefCell;
+
+    /// Prepare a symbolic graph with 16 colors, and the color sets where each of its four
+    /// parameter variables is true.
+    pub(crate) fn prepare_graph_and_sets() -> (SymbolicAsyncGraph, Vec<GraphColors>) {
+        let aeon_str = r"
+            a -?? b
+            b -?? a
+        ";
+        let bn = BooleanNetwork::try_from(aeon_str).unwrap();
+        let graph = SymbolicAsyncGraph::new(bn).unwrap();
+        let context = graph.symbolic_context();
+        let sets = context
+            .parameter_variables()
+            .iter()
+            .map(|var| {
+                let bdd = context.bdd_variable_set().mk_var(*var);
+                GraphColors::new(bdd, context)
+            })
+            .collect();
+        (graph, sets)
+    }
+
+    #[test]
+    fn test_int_to_bool_vec() {
+        let expected_vec = vec![false, false, false];
+        assert_eq!(int_to_bool_vec(0, 3), expected_vec);
+
+        let expected_vec = vec![false, true];
+        assert_eq!(int_to_bool_vec(1, 2), expected_vec);
+
+        let expected_vec = vec![false, false, false, true];
+        assert_eq!(int_to_bool_vec(1, 4), expected_vec);
+
+        let expected_vec = vec![false, false, true, false];
+        assert_eq!(int_to_bool_vec(2, 4), expected_vec);
+
+        let expected_vec = vec![true, true, true, true];
+        assert_eq!(int_to_bool_vec(15, 4), expected_vec);
+    }
+
+    #[test]
+    /// Test the MSB-first ordering at the boundaries of the supported bit widths.
+    fn test_int_to_bool_vec_wide() {
+        // Equivalent of `i32::MIN`, i.e., only the 32nd bit set.
+        let mut expected_vec = vec![false; 32];
+        expected_vec[0] = true;
+        assert_eq!(int_to_bool_vec(i32::MIN as u32 as u64, 32), expected_vec);
+
+        let mut expected_vec = vec![false; 64];
+        expected_vec[0] = true;
+        assert_eq!(int_to_bool_vec(1 << 63, 64), expected_vec);
+
+        let mut expected_vec = vec![false; 64];
+        expected_vec[63] = true;
+        assert_eq!(int_to_bool_vec(1, 64), expected_vec);
+
+        assert_eq!(int_to_bool_vec(u64::MAX, 64), vec![true; 64]);
+
+        // Bits beyond 64 are padded with zeroes.
+        let mut expected_vec = vec![true; 66];
+        expected_vec[0] = false;
+        expected_vec[1] = false;
+        assert_eq!(int_to_bool_vec(u64::MAX, 66), expected_vec);
+    }
+
+    #[test]
+    /// Test that the binary numbers are transformed back into the original integers.
+    fn test_bool_vec_to_int() {
+        assert_eq!(bool_vec_to_int(&[]), 0);
+        assert_eq!(bool_vec_to_int(&[false, true, false]), 2);
+        assert_eq!(bool_vec_to_int(&[true, true, false, true]), 13);
+        for width in 0..=10 {
+            for number in 0..(1u64 << width) {
+                assert_eq!(bool_vec_to_int(&int_to_bool_vec(number, width)), number);
+            }
+        }
+        for number in [1 << 63, u64::MAX, i32::MIN as u32 as u64] {
+            assert_eq!(bool_vec_to_int(&int_to_bool_vec(number, 64)), number);
+            assert_eq!(bool_vec_to_int(&int_to_bool_vec(number, 66)), number);
+        }
+    }
+
+    #[test]
+    /// Test that the categories are enumerated in the correct order with correct sets.
+    fn test_build_classification_result() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "false".to_string()),
+        ];
+        let result = build_classification_result(
+            &["true".to_string()],
+            graph.unit_colors(),
+            &properties,
+            &sets[0..2],
+        )
+        .unwrap();
+
+        assert_eq!(result.valid_cardinality, 16.0);
+        assert_eq!(result.properties.len(), 2);
+        assert_eq!(result.properties[1].name, "p2");
+        assert_eq!(result.properties[1].cardinality, 8.0);
+
+        assert_eq!(result.categories.len(), 4);
+        assert_eq!(result.categories[2].validity, vec![true, false]);
+        assert_eq!(result.categories[2].cardinality, 4.0);
+        let expected = sets[0].minus(&sets[1]);
+        assert_eq!(result.categories[2].colors.as_bdd(), expected.as_bdd());
+    }
+
+    #[test]
+    /// Test the number of classes and their total size when some categories are empty.
+    fn test_num_classes() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        // Category `01` is empty.
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+        assert_eq!(result.categories.len(), 4);
+        assert_eq!(result.num_classes(), 3);
+        assert_eq!(result.classified_cardinality(), 16.0);
+        assert_eq!(result.classified_exact_cardinality(), BigInt::from(16));
+        assert!(result.unclassified_colors().is_empty());
+        let summary = result.flat_summary();
+        assert_eq!(summary.len(), 4);
+        assert_eq!(summary[1], ("01".to_string(), 0.0, 1));
+        assert_eq!(summary[2].0, "10");
+        assert_eq!(summary[2].1, 4.0);
+
+        // A category is missing, e.g., due to properties computed in a different universe.
+        let mut broken = result.clone();
+        broken.categories.remove(2);
+        let expected = sets[0].minus(&sets[1]);
+        assert!(colors_equal(&broken.unclassified_colors(), &expected));
+    }
+
+    #[test]
+    /// Test that the absent classes are exactly the empty combinations of properties.
+    fn test_absent_classes() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        // Category `01` is empty.
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+        assert_eq!(result.absent_classes(), Some(vec![vec![false, true]]));
+
+        // In the observed mode, the empty categories are not listed, but they are still absent.
+        let result = build_classification_result_with_mode(
+            &[],
+            graph.unit_colors(),
+            &properties,
+            &property_results,
+            EnumerationMode::Observed,
+            None,
+        )
+        .unwrap();
+        assert_eq!(result.absent_classes(), Some(vec![vec![false, true]]));
+
+        let names: Vec<(String, String)> = (0..=MAX_ABSENT_CLASS_PROPERTIES)
+            .map(|i| (format!("p{i}"), "true".to_string()))
+            .collect();
+        let results = vec![sets[0].clone(); names.len()];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &names, &results).unwrap();
+        assert_eq!(result.absent_classes(), None);
+    }
+
+    #[test]
+    /// Test that the observed enumeration only yields the non-empty categories, and that they
+    /// match the exhaustive enumeration.
+    fn test_observed_enumeration() {
+        let (graph, sets) = prepare_graph_and_sets();
+        // Second property is a subset of the first one, so category `01` is empty.
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "false".to_string()),
+        ];
+
+        let exhaustive = build_classification_result_with_mode(
+            &[],
+            graph.unit_colors(),
+            &properties,
+            &property_results,
+            EnumerationMode::Exhaustive,
+            None,
+        )
+        .unwrap();
+        let observed = build_classification_result_with_mode(
+            &[],
+            graph.unit_colors(),
+            &properties,
+            &property_results,
+            EnumerationMode::Observed,
+            None,
+        )
+        .unwrap();
+
+        let non_empty: Vec<_> = exhaustive
+            .categories
+            .iter()
+            .filter(|it| !it.colors.is_empty())
+            .collect();
+        assert_eq!(observed.categories.len(), 3);
+        for (x, y) in non_empty.iter().zip(observed.categories.iter()) {
+            assert_eq!(x.validity, y.validity);
+            assert_eq!(x.colors.as_bdd(), y.colors.as_bdd());
+        }
+    }
+
+    #[test]
+    /// Test that classification works even for more than 31 properties.
+    fn test_many_properties() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let property_results: Vec<_> = (0..40).map(|i| sets[i % sets.len()].clone()).collect();
+        let properties: Vec<_> = (0..40)
+            .map(|i| (format!("p{i}"), "true".to_string()))
+            .collect();
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+        // Properties repeat the four parameters, so each color is in its own category.
+        assert_eq!(result.categories.len(), 16);
+        assert!(result.categories.iter().all(|it| it.cardinality == 1.0));
+    }
+
+    #[test]
+    /// Test that the pruned enumeration yields exactly the non-empty categories, in order.
+    fn test_non_empty_enumeration() {
+        let (graph, sets) = prepare_graph_and_sets();
+        // First property implies the second and the third is never satisfied.
+        let property_results = vec![
+            sets[0].intersect(&sets[1]),
+            sets[1].clone(),
+            sets[0].minus(&sets[0]),
+        ];
+        let categories =
+            enumerate_non_empty_categories(graph.unit_colors(), &property_results, None);
+        let validity: Vec<Vec<bool>> = categories.iter().map(|it| it.validity.clone()).collect();
+        assert_eq!(
+            validity,
+            vec![
+                vec![false, false, false],
+                vec![false, true, false],
+                vec![true, true, false],
+            ]
+        );
+        assert_eq!(categories[0].cardinality, 8.0);
+        assert_eq!(categories[1].cardinality, 4.0);
+        assert_eq!(categories[2].cardinality, 4.0);
+    }
+
+    #[test]
+    /// Test that the progress callback covers all combinations, including the empty ones.
+    fn test_progress_callback() {
+        let (graph, sets) = prepare_graph_and_sets();
+        // Second property is a subset of the first one, so category `01` is empty.
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "false".to_string()),
+        ];
+
+        let reported = RefCell::new(Vec::new());
+        let progress = |done: usize, total: usize| reported.borrow_mut().push((done, total));
+        build_classification_result_with_mode(
+            &[],
+            graph.unit_colors(),
+            &properties,
+            &property_results,
+            EnumerationMode::Exhaustive,
+            Some(&progress),
+        )
+        .unwrap();
+        // Also the parallel branches are reported one by one.
+        assert_eq!(reported.into_inner(), vec![(1, 4), (2, 4), (3, 4), (4, 4)]);
+
+        // The observed categories use the same total.
+        let reported = RefCell::new(Vec::new());
+        let progress = |done: usize, total: usize| reported.borrow_mut().push((done, total));
+        build_classification_result_with_mode(
+            &[],
+            graph.unit_colors(),
+            &properties,
+            &property_results,
+            EnumerationMode::Observed,
+            Some(&progress),
+        )
+        .unwrap();
+        assert_eq!(reported.into_inner(), vec![(1, 4), (2, 4), (3, 4), (4, 4)]);
+    }
+
+    #[test]
+    /// Test that mismatched property names and results are reported.
+    fn test_property_count_mismatch() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result = build_classification_result(&[], graph.unit_colors(), &properties, &sets);
+        assert!(matches!(
+            result,
+            Err(ClassifierError::PropertyCountMismatch {
+                names: 1,
+                results: 4
+            })
+        ));
+    }
+
+    #[test]
+    /// Test the split of a set by a property, including the degenerate cases.
+    fn test_split_by_property() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let unit = graph.unit_colors().clone();
+
+        let (invalid, valid) = split_by_property(unit.clone(), &sets[0]);
+        assert_eq!(invalid.approx_cardinality(), 8.0);
+        assert_eq!(valid.approx_cardinality(), 8.0);
+
+        let (invalid, valid) = split_by_property(sets[0].clone(), &sets[0]);
+        assert!(invalid.is_empty());
+        assert_eq!(valid.approx_cardinality(), 8.0);
+
+        let (invalid, valid) = split_by_property(sets[0].clone(), &unit.minus(&sets[0]));
+        assert_eq!(invalid.approx_cardinality(), 8.0);
+        assert!(valid.is_empty());
+    }
+
+    #[test]
+    /// Test that non-discriminating properties are detected and do not double the number of
+    /// categories in the discriminating mode.
+    fn test_discriminating_mode() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let unit = graph.unit_colors();
+        let property_results = vec![unit.clone(), sets[0].clone(), unit.minus(unit)];
+        let properties = vec![
+            ("all".to_string(), "true".to_string()),
+            ("p1".to_string(), "true".to_string()),
+            ("none".to_string(), "false".to_string()),
+        ];
+
+        let result = build_classification_result_with_mode(
+            &[],
+            unit,
+            &properties,
+            &property_results,
+            EnumerationMode::Discriminating,
+            None,
+        )
+        .unwrap();
+        let discriminating: Vec<bool> =
+            result.properties.iter().map(|p| p.discriminating).collect();
+        assert_eq!(discriminating, vec![false, true, false]);
+        let validity: Vec<Vec<bool>> = result
+            .categories
+            .iter()
+            .map(|c| c.validity.clone())
+            .collect();
+        assert_eq!(
+            validity,
+            vec![vec![true, false, false], vec![true, true, false]]
+        );
+    }
+
+    #[test]
+    /// Test the equality of color sets, and check that the categories are disjoint and
+    /// cover all valid colors.
+    fn test_colors_equal() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let unit = graph.unit_colors();
+        // Same set built in a different way.
+        assert!(colors_equal(
+            &sets[0],
+            &sets[0].union(&sets[1]).intersect(&sets[0])
+        ));
+        // Different sets of the same size.
+        assert!(!colors_equal(&sets[0], &sets[1]));
+
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let result = build_classification_result(&[], unit, &properties, &sets[0..2]).unwrap();
+        let mut covered = unit.minus(unit);
+        for category in &result.categories {
+            assert!(category.colors.intersect(&covered).is_empty());
+            covered = covered.union(&category.colors);
+        }
+        assert!(colors_equal(&covered, unit));
+    }
+
+    #[test]
+    /// Test the indices of the properties in the canonical order.
+    fn test_property_order_indices() {
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "false".to_string()),
+        ];
+        let order = |names: &[&str]| names.iter().map(|it| it.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            property_order_indices(&properties, &order(&["p2", "p1"])).unwrap(),
+            vec![1, 0]
+        );
+        for invalid in [vec!["p2", "p3"], vec!["p2"], vec!["p1", "p1"]] {
+            let error = property_order_indices(&properties, &order(&invalid)).unwrap_err();
+            assert!(matches!(
+                error,
+                ClassifierError::InvalidPropertySelection(_)
+            ));
+        }
+    }
+
+    #[test]
+    /// Test that the colors which neither satisfy nor refute a property get the unknown outcome,
+    /// and that all `3^n` combinations are enumerated for a few properties.
+    fn test_ternary_classification() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let unit = graph.unit_colors();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let satisfied = vec![sets[0].clone(), sets[2].clone()];
+        let refuted = vec![
+            unit.minus(&sets[0]).intersect(&sets[1]),
+            unit.minus(&sets[2]),
+        ];
+        let result =
+            build_ternary_classification_result(&[], unit, &properties, &satisfied, &refuted)
+                .unwrap();
+        let labels: Vec<(String, f64)> = result
+            .categories
+            .iter()
+            .map(|category| {
+                let label = category.validity.iter().map(|it| it.symbol()).collect();
+                (label, category.cardinality)
+            })
+            .collect();
+        let expected = [
+            ("00", 2.0),
+            ("01", 2.0),
+            ("0?", 0.0),
+            ("10", 4.0),
+            ("11", 4.0),
+            ("1?", 0.0),
+            ("?0", 2.0),
+            ("?1", 2.0),
+            ("??", 0.0),
+        ];
+        let expected: Vec<(String, f64)> = expected
+            .iter()
+            .map(|(label, cardinality)| (label.to_string(), *cardinality))
+            .collect();
+        assert_eq!(labels, expected);
+
+        // With too many combinations, only the non-empty categories are enumerated.
+        assert!(!exceeds_max_ternary_classes(12, DEFAULT_MAX_CLASSES));
+        assert!(exceeds_max_ternary_classes(13, DEFAULT_MAX_CLASSES));
+        assert!(exceeds_max_ternary_classes(usize::MAX, usize::MAX));
+        let many = 13;
+        let properties = vec![("p".to_string(), "true".to_string()); many];
+        let result = build_ternary_classification_result(
+            &[],
+            unit,
+            &properties,
+            &vec![sets[0].clone(); many],
+            &vec![refuted[0].clone(); many],
+        )
+        .unwrap();
+        let validity: Vec<Vec<Verdict>> = result
+            .categories
+            .iter()
+            .map(|category| category.validity.clone())
+            .collect();
+        let expected: Vec<Vec<Verdict>> = [Verdict::Refuted, Verdict::Satisfied, Verdict::Unknown]
+            .into_iter()
+            .map(|verdict| vec![verdict; many])
+            .collect();
+        assert_eq!(validity, expected);
+        let result = build_ternary_classification_result_with_limit(
+            &[],
+            unit,
+            &properties[0..2],
+            &satisfied,
+            &refuted,
+            8,
+        )
+        .unwrap();
+        assert_eq!(result.categories.len(), 6);
+
+        // A color cannot both satisfy and refute a property.
+        let result = build_ternary_classification_result(
+            &[],
+            unit,
+            &properties[0..1],
+            &sets[0..1],
+            &sets[0..1],
+        );
+        assert!(matches!(
+            result,
+            Err(ClassifierError::PartitionInvariantViolated(_))
+        ));
+    }
+
+    #[test]
+    /// Test that the diff matches the classes by their validity and reports the changes.
+    fn test_diff_results() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let unit = graph.unit_colors();
+        let a = build_classification_result(&[], unit, &properties, &sets[0..2]).unwrap();
+        assert!(diff_results(&a, &a).is_empty());
+
+        // The second property now holds exactly when the first one does.
+        let results = vec![sets[0].clone(), sets[0].clone()];
+        let b = build_classification_result(&[], unit, &properties, &results).unwrap();
+        let diff = diff_results(&a, &b);
+        assert_eq!(
+            diff.removed,
+            vec![
+                (vec![false, true], BigInt::from(4)),
+                (vec![true, false], BigInt::from(4))
+            ]
+        );
+        assert!(diff.added.is_empty());
+        let changes: Vec<(Vec<bool>, BigInt)> = diff
+            .changed
+            .iter()
+            .map(|change| (change.validity.clone(), change.delta()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (vec![false, false], BigInt::from(4)),
+                (vec![true, true], BigInt::from(4))
+            ]
+        );
+
+        let diff = diff_results(&b, &a);
+        assert_eq!(diff.added.len(), 2);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed[0].delta(), BigInt::from(-4));
+    }
+
+    #[test]
+    /// Test that the support of each category consists of the parameters of its properties.
+    fn test_category_support() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let parameters = graph.symbolic_context().parameter_variables();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..2])
+                .unwrap();
+        for category in &result.categories {
+            assert_eq!(category.support(), parameters[0..2].to_vec());
+        }
+
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties[0..1], &sets[0..1])
+                .unwrap();
+        for category in &result.categories {
+            assert_eq!(category.support(), vec![parameters[0]]);
+        }
+    }
+
+    #[test]
+    /// Test that satisfiable assertions without any satisfiable property give a single class
+    /// (without going through all the combinations of the properties).
+    fn test_no_property_satisfiable() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let unit = graph.unit_colors();
+        let empty = unit.minus(unit);
+        let names: Vec<(String, String)> = (0..30)
+            .map(|i| (format!("p{i}"), "false".to_string()))
+            .collect();
+        let results = vec![empty; names.len()];
+        let result = build_classification_result(&[], unit, &names, &results).unwrap();
+        assert_eq!(result.categories.len(), 1);
+        assert_eq!(result.categories[0].validity, vec![false; 30]);
+        assert!(colors_equal(&result.categories[0].colors, unit));
+        assert_eq!(result.num_classes(), 1);
+
+        // Also in the discriminating mode, if some of the constant properties hold everywhere.
+        let results = vec![unit.clone(), unit.minus(&sets[0]).intersect(&sets[0])];
+        let result = build_classification_result_with_mode(
+            &[],
+            unit,
+            &names[0..2],
+            &results,
+            EnumerationMode::Discriminating,
+            None,
+        )
+        .unwrap();
+        assert_eq!(result.categories.len(), 1);
+        assert_eq!(result.categories[0].validity, vec![true, false]);
+        assert_eq!(result.categories[0].cardinality, 16.0);
+    }
+
+    #[test]
+    /// Test the pairwise overlaps of the properties.
+    fn test_property_overlaps() {
+        let (graph, sets) = prepare_graph_and_sets();
+        // Second property is a subset of the first one.
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let names = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &names, &property_results)
+                .unwrap();
+        let expected = vec![
+            vec![BigInt::from(8), BigInt::from(4)],
+            vec![BigInt::from(4), BigInt::from(4)],
+        ];
+        assert_eq!(property_overlaps(&result), expected);
+    }
+
+    #[test]
+    /// Test that the summary lists the non-empty categories with their sizes.
+    fn test_classify_summary() {
+        let (graph, sets) = prepare_graph_and_sets();
+        // Second property is a subset of the first one, so category `01` is empty.
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let summary = classify_summary(graph.unit_colors(), &property_results);
+        assert_eq!(
+            summary,
+            vec![
+                (vec![false, false], 8.0),
+                (vec![true, false], 4.0),
+                (vec![true, true], 4.0),
+            ]
+        );
+    }
+
+    #[test]
+    /// Test that the cardinalities computed by inclusion-exclusion match the full classification.
+    fn test_classify_cardinalities() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let unit = graph.unit_colors();
+        let cardinalities = classify_cardinalities(unit, &sets[0..3]).unwrap();
+        let names: Vec<(String, String)> = (0..3)
+            .map(|i| (format!("p{i}"), "true".to_string()))
+            .collect();
+        let result = build_classification_result(&[], unit, &names, &sets[0..3]).unwrap();
+        let expected: Vec<(Vec<bool>, BigInt)> = result
+            .non_empty_categories()
+            .map(|category| {
+                (
+                    category.validity.clone(),
+                    category.exact_cardinality.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(cardinalities, expected);
+
+        // Second property is a subset of the first one, so category `01` is empty.
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let cardinalities = classify_cardinalities(unit, &property_results).unwrap();
+        assert_eq!(cardinalities.len(), 3);
+        assert_eq!(cardinalities[0], (vec![false, false], BigInt::from(8)));
+
+        let too_many = vec![sets[0].clone(); MAX_STATS_ONLY_PROPERTIES + 1];
+        assert!(classify_cardinalities(unit, &too_many).is_none());
+    }
+
+    #[test]
+    /// Test that the result with only the cardinalities has the same classes as the full one,
+    /// but no colors in them.
+    fn test_build_cardinality_classification_result() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let unit = graph.unit_colors();
+        let names: Vec<(String, String)> = (0..2)
+            .map(|i| (format!("p{i}"), "true".to_string()))
+            .collect();
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let full = build_classification_result(&[], unit, &names, &property_results).unwrap();
+        let result = build_cardinality_classification_result(&[], unit, &names, &property_results)
+            .unwrap()
+            .unwrap();
+        assert!(result.cardinalities_only);
+        assert_eq!(result.num_classes(), full.num_classes());
+        assert_eq!(result.classified_exact_cardinality(), BigInt::from(16));
+        assert_eq!(result.unclassified_cardinality().1, BigInt::from(0));
+        assert!(result.categories.iter().all(|it| it.colors.is_empty()));
+        assert_eq!(result.properties[1].cardinality, 4.0);
+
+        let err = build_cardinality_classification_result(&[], unit, &names, &sets[0..1]);
+        assert!(matches!(
+            err,
+            Err(ClassifierError::PropertyCountMismatch { .. })
+        ));
+    }
+
+    #[test]
+    /// Test that the incremental update gives the same categories as a full classification.
+    fn test_reclassify_changed_properties() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let unit = graph.unit_colors();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let baseline = build_classification_result(&[], unit, &properties, &sets[0..2]).unwrap();
+        let baseline: Vec<(Vec<bool>, GraphColors)> = baseline
+            .categories
+            .into_iter()
+            .filter(|category| !category.colors.is_empty())
+            .map(|category| (category.validity, category.colors))
+            .collect();
+
+        let new_results = vec![sets[0].clone(), sets[0].intersect(&sets[2])];
+        let updated =
+            reclassify_changed_properties(&baseline, &[(1, new_results[1].clone())]).unwrap();
+        let expected = build_classification_result(&[], unit, &properties, &new_results).unwrap();
+        let expected: Vec<&Category> = expected
+            .categories
+            .iter()
+            .filter(|category| !category.colors.is_empty())
+            .collect();
+
+        assert_eq!(updated.len(), expected.len());
+        for ((validity, colors), category) in updated.iter().zip(expected) {
+            assert_eq!(validity, &category.validity);
+            assert!(colors_equal(colors, &category.colors));
+        }
+
+        // Invalid property indices and inconsistent baselines are rejected.
+        let out_of_range = reclassify_changed_properties(&baseline, &[(2, sets[2].clone())]);
+        assert!(matches!(
+            out_of_range,
+            Err(ClassifierError::InvalidPropertySelection(_))
+        ));
+        let mut inconsistent = baseline.clone();
+        inconsistent[0].0.push(true);
+        let inconsistent = reclassify_changed_properties(&inconsistent, &[(0, sets[2].clone())]);
+        assert!(matches!(
+            inconsistent,
+            Err(ClassifierError::InvalidPropertySelection(_))
+        ));
+    }
+
+    #[test]
+    /// Test that broken partitions are detected.
+    fn test_verify_partition() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let mut result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+        assert!(verify_partition(&result).is_ok());
+
+        // Overlapping categories.
+        let original = result.categories[0].colors.clone();
+        result.categories[0].colors = original.union(&sets[1]);
+        assert!(matches!(
+            verify_partition(&result),
+            Err(ClassifierError::PartitionInvariantViolated(_))
+        ));
+
+        // Missing colors.
+        result.categories[0].colors = original.minus(&sets[1]);
+        assert!(matches!(
+            verify_partition(&result),
+            Err(ClassifierError::PartitionInvariantViolated(_))
+        ));
+    }
+
+    #[test]
+    /// Test the enumeration with a single property, and without any properties (where there
+    /// is just one category with an empty validity vector).
+    fn test_single_and_no_property() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let unit = graph.unit_colors();
+
+        let result = build_classification_result(&[], unit, &properties, &sets[0..1]).unwrap();
+        let validities: Vec<Vec<bool>> = result
+            .categories
+            .iter()
+            .map(|it| it.validity.clone())
+            .collect();
+        assert_eq!(validities, vec![vec![false], vec![true]]);
+        assert!(colors_equal(&result.categories[1].colors, &sets[0]));
+        assert_eq!(result.categories[0].cardinality, 8.0);
+
+        for mode in [
+            EnumerationMode::Exhaustive,
+            EnumerationMode::Observed,
+            EnumerationMode::Discriminating,
+        ] {
+            let result =
+                build_classification_result_with_mode(&[], unit, &[], &[], mode, None).unwrap();
+            assert_eq!(result.categories.len(), 1);
+            assert!(result.categories[0].validity.is_empty());
+            assert!(colors_equal(&result.categories[0].colors, unit));
+        }
+    }
+
+    #[test]
+    /// Test that the lazy iterator yields exactly the non-empty categories, in order.
+    fn test_classify_iter() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+            ("p3".to_string(), "true".to_string()),
+        ];
+        // Categories `010` and `011` are empty.
+        let property_results = vec![
+            sets[0].clone(),
+            sets[0].intersect(&sets[1]),
+            sets[2].clone(),
+        ];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+        let expected: Vec<&Category> = result
+            .categories
+            .iter()
+            .filter(|it| !it.colors.is_empty())
+            .collect();
+
+        let classes: Vec<(Vec<bool>, GraphColors)> =
+            classify_iter(graph.unit_colors(), &property_results).collect();
+        assert_eq!(classes.len(), expected.len());
+        assert_eq!(classes.len(), 6);
+        for ((validity, colors), category) in classes.iter().zip(expected) {
+            assert_eq!(validity, &category.validity);
+            assert!(colors_equal(colors, &category.colors));
+        }
+
+        let empty = graph.empty_colors();
+        assert_eq!(classify_iter(empty, &property_results).count(), 0);
+        let all: Vec<_> = classify_iter(graph.unit_colors(), &[]).collect();
+        assert_eq!(all.len(), 1);
+        assert!(all[0].0.is_empty());
+    }
+
+    #[test]
+    /// Test that only the selected properties take part in the projected decomposition.
+    fn test_projected_classification() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties: Vec<(String, String)> = (0..4)
+            .map(|i| (format!("p{i}"), "true".to_string()))
+            .collect();
+        let unit = graph.unit_colors();
+        let result =
+            build_projected_classification_result(&[], unit, &properties, &sets, &[2, 0]).unwrap();
+
+        let names: Vec<&str> = result
+            .properties
+            .iter()
+            .map(|it| it.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["p2", "p0"]);
+        let projected: Vec<&str> = result
+            .projected_properties
+            .iter()
+            .map(|it| it.name.as_str())
+            .collect();
+        assert_eq!(projected, vec!["p1", "p3"]);
+        assert_eq!(result.projected_properties[0].cardinality, 8.0);
+
+        assert_eq!(result.categories.len(), 4);
+        assert_eq!(result.categories[1].validity, vec![false, true]);
+        let expected = sets[0].minus(&sets[2]);
+        assert!(colors_equal(&result.categories[1].colors, &expected));
+
+        for selected in [vec![4], vec![1, 1]] {
+            let result =
+                build_projected_classification_result(&[], unit, &properties, &sets, &selected);
+            assert!(matches!(
+                result,
+                Err(ClassifierError::InvalidPropertySelection(_))
+            ));
+        }
+    }
+
+    #[test]
+    /// Test that the exclusive properties are found, and the non-discriminating ones skipped.
+    fn test_property_relations() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties: Vec<(String, String)> = (0..4)
+            .map(|i| (format!("p{i}"), "true".to_string()))
+            .collect();
+        let property_results = vec![
+            sets[0].clone(),
+            graph.unit_colors().minus(&sets[0]),
+            sets[0].union(&sets[1]),
+            graph.empty_colors().clone(),
+        ];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+        assert_eq!(
+            property_relations(&result),
+            vec![
+                PropertyRelation::NeverBothTrue(0, 1),
+                PropertyRelation::NeverBothFalse(0, 1),
+                PropertyRelation::NeverBothFalse(1, 2),
+            ]
+        );
+    }
+
+    #[test]
+    /// Test that the implications are found in both directions, and the non-discriminating
+    /// properties skipped.
+    fn test_property_implications() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties: Vec<(String, String)> = (0..5)
+            .map(|i| (format!("p{i}"), "true".to_string()))
+            .collect();
+        let property_results = vec![
+            sets[0].clone(),
+            graph.unit_colors().minus(&sets[0]),
+            sets[0].union(&sets[1]),
+            graph.empty_colors().clone(),
+            sets[0].union(&sets[1]),
+        ];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+        assert_eq!(
+            property_implications(&result),
+            vec![(0, 2), (0, 4), (2, 4), (4, 2)]
+        );
+    }
+
+    #[test]
+    /// Test that only the non-empty categories are enumerated once the combinations exceed
+    /// the limit.
+    fn test_max_classes() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "false".to_string()),
+        ];
+        assert!(!exceeds_max_classes(2, 4));
+        assert!(exceeds_max_classes(2, 3));
+        assert!(exceeds_max_classes(31, usize::MAX));
+        assert!(!exceeds_max_classes(30, 1 << 30));
+
+        let unit = graph.unit_colors();
+        let all =
+            build_classification_result_with_limit(&[], unit, &properties, &property_results, 4)
+                .unwrap();
+        assert_eq!(all.categories.len(), 4);
+        let limited =
+            build_classification_result_with_limit(&[], unit, &properties, &property_results, 3)
+                .unwrap();
+        assert_eq!(limited.categories.len(), 3);
+        assert!(limited.categories.iter().all(|c| !c.colors.is_empty()));
+        assert_eq!(limited.num_classes(), all.num_classes());
+    }
+
+    #[test]
+    /// Test that the assertions restrict the valid colors, and the remaining formulae decompose
+    /// them, regardless of the order of their tags.
+    fn test_tagged_classification() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let tag = |i: usize, role: PropertyRole| TaggedProperty {
+            name: format!("p{i}"),
+            formula: format!("f{i}"),
+            role,
+            colors: sets[i].clone(),
+        };
+        let tagged = vec![
+            tag(0, PropertyRole::Classify),
+            tag(1, PropertyRole::Assertion),
+            tag(2, PropertyRole::Classify),
+            tag(3, PropertyRole::Assertion),
+        ];
+        let result = build_tagged_classification_result(graph.unit_colors(), &tagged).unwrap();
+        assert_eq!(result.assertion_formulae, vec!["f1", "f3"]);
+        assert_eq!(result.valid_cardinality, 4.0);
+        let names: Vec<&str> = result.properties.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["p0", "p2"]);
+        assert_eq!(result.categories.len(), 4);
+        assert!(result.categories.iter().all(|c| c.cardinality == 1.0));
+        // The property cardinalities only count the valid colors.
+        let cardinalities: Vec<f64> = result.properties.iter().map(|p| p.cardinality).collect();
+        assert_eq!(cardinalities, vec![2.0, 2.0]);
+
+        // Flipping a tag turns the assertion into a classification property.
+        let mut flipped = tagged.clone();
+        flipped[3].role = PropertyRole::Classify;
+        let result = build_tagged_classification_result(graph.unit_colors(), &flipped).unwrap();
+        assert_eq!(result.assertion_formulae, vec!["f1"]);
+        assert_eq!(result.valid_cardinality, 8.0);
+        assert_eq!(result.properties.len(), 3);
+        assert_eq!(result.properties[2].name, "p3");
+    }
+}
@@ -1,7 +1,18 @@
 //! Main high-level functionality regarding the BN classification based on HCTL properties.
 
+use crate::categories::{
+    build_cardinality_classification_result, build_classification_result,
+    build_classification_result_with_limit, build_tagged_classification_result,
+    exceeds_max_classes, property_order_indices, ClassificationResult, TaggedProperty,
+    MAX_STATS_ONLY_PROPERTIES,
+};
+use crate::errors::ClassifierError;
 use crate::load_inputs::*;
-use crate::write_output::{write_classifier_output, write_empty_report};
+use crate::write_output::{
+    source_fingerprint, write_classification_result_to_writer,
+    write_classification_result_with_graph, write_classification_result_with_options,
+    OutputOptions,
+};
 
 use biodivine_hctl_model_checker::mc_utils::{
     collect_unique_hctl_vars, get_extended_symbolic_graph,
@@ -17,9 +28,11 @@ use biodivine_lib_param_bn::biodivine_std::traits::Set;
 use biodivine_lib_param_bn::symbolic_async_graph::{
     GraphColoredVertices, GraphColors, SymbolicAsyncGraph,
 };
-use biodivine_lib_param_bn::{BooleanNetwork, ModelAnnotation};
+use biodivine_lib_param_bn::BooleanNetwork;
 
 use std::cmp::max;
+use std::io::{Seek, Write};
+use std::path::Path;
 
 /// Return the set of colors for which ALL system states are contained in the given color-vertex
 /// set (i.e., if the given relation is a result of model checking a property, get colors for which
@@ -34,6 +47,117 @@ fn get_universal_colors(
     stg.unit_colors().minus(&complement.colors())
 }
 
+/// Model check each of the assertion formulae (given by their syntax `trees`) individually on the
+/// `graph`, and return the colors satisfying each of them (universally).
+///
+/// This is mainly a diagnostic for the case where the combination of all assertions cannot be
+/// satisfied, since it shows which of the assertions are responsible.
+pub fn check_assertions_individually(
+    trees: Vec<HctlTreeNode>,
+    graph: &SymbolicAsyncGraph,
+) -> Result<Vec<GraphColors>, String> {
+    let results = model_check_multiple_trees_dirty(trees, graph)?;
+    Ok(results
+        .iter()
+        .map(|result| get_universal_colors(graph, result))
+        .collect())
+}
+
+/// Reusable configuration for classifying precomputed model-checking results.
+///
+/// The classifier only holds the shared [OutputOptions], while all state of one classification
+/// (such as the zip writer) is local to the individual calls. Hence, one classifier can be
+/// shared between threads (e.g., as an `Arc<Classifier>` used by the handlers of a server).
+#[derive(Clone, Debug, Default)]
+pub struct Classifier {
+    options: OutputOptions,
+}
+
+impl Classifier {
+    /// New classifier which writes the output as configured by the `options`.
+    pub fn new(options: OutputOptions) -> Classifier {
+        Classifier { options }
+    }
+
+    /// The options used to write the output.
+    pub fn options(&self) -> &OutputOptions {
+        &self.options
+    }
+
+    /// Decompose the `all_valid_colors` into categories based on the `property_results` (see
+    /// [build_classification_result_with_limit] and [OutputOptions::max_classes]) and write the
+    /// output into the `archive_name` zip, together with the `original_model_str`.
+    pub fn classify(
+        &self,
+        assertion_formulae: &[String],
+        all_valid_colors: &GraphColors,
+        named_property_formulae: &[(String, String)],
+        property_results: &[GraphColors],
+        original_model_str: &str,
+        archive_name: &str,
+    ) -> Result<(), ClassifierError> {
+        let result = build_classification_result_with_limit(
+            assertion_formulae,
+            all_valid_colors,
+            named_property_formulae,
+            property_results,
+            self.options.max_classes,
+        )?;
+        write_classification_result_with_options(
+            &result,
+            archive_name,
+            original_model_str,
+            &self.options,
+        )
+    }
+
+    /// Same as [Classifier::classify], but the assertions and the classification properties
+    /// are given in one list of `tagged_properties` (see [build_tagged_classification_result]),
+    /// and the valid colors are computed from the `unit_colors`.
+    pub fn classify_tagged(
+        &self,
+        unit_colors: &GraphColors,
+        tagged_properties: &[TaggedProperty],
+        original_model_str: &str,
+        archive_name: &str,
+    ) -> Result<(), ClassifierError> {
+        let result = build_tagged_classification_result(unit_colors, tagged_properties)?;
+        write_classification_result_with_options(
+            &result,
+            archive_name,
+            original_model_str,
+            &self.options,
+        )
+    }
+
+    /// Same as [Classifier::classify], but the archive is written into an arbitrary seekable
+    /// `writer`, which is returned once the archive is finished.
+    pub fn classify_to_writer<W: Write + Seek>(
+        &self,
+        assertion_formulae: &[String],
+        all_valid_colors: &GraphColors,
+        named_property_formulae: &[(String, String)],
+        property_results: &[GraphColors],
+        original_model_str: &str,
+        writer: W,
+    ) -> Result<W, ClassifierError> {
+        let result = build_classification_result_with_limit(
+            assertion_formulae,
+            all_valid_colors,
+            named_property_formulae,
+            property_results,
+            self.options.max_classes,
+        )?;
+        write_classification_result_to_writer(
+            &result,
+            writer,
+            original_model_str,
+            None,
+            &self.options,
+        )
+    }
+}
+
 /// Perform the classification of Boolean networks based on given properties.
 /// Takes a path to a file in annotated `AEON` format containing a partially defined BN model
 /// and 2 sets of HCTL formulae. Assertions are formulae that must be satisfied, and properties
@@ -44,18 +168,179 @@ fn get_universal_colors(
 /// of properties is satisfied (universally).
 ///
 /// Report and BDDs representing resulting classes are generated into `output_zip` archive.
-pub fn classify(model_path: &str, output_zip: &str) -> Result<(), String> {
-    // TODO: allow caching between model-checking assertions and properties somehow
-
-    // load the model and two sets of formulae (from model annotations)
+/// Optional parts of the output are configured by `options`.
+pub fn classify(model_path: &str, output_zip: &str, options: &OutputOptions) -> Result<(), String> {
     let Ok(aeon_str) = std::fs::read_to_string(model_path) else {
         return Err(format!("Input file `{model_path}` is not accessible."));
     };
-    let bn = BooleanNetwork::try_from(aeon_str.as_str())?;
-    let annotations = ModelAnnotation::from_model_string(aeon_str.as_str());
-    let assertions = read_model_assertions(&annotations);
-    let named_properties = read_model_properties(&annotations)?;
-    println!("Loaded model and properties out of `{model_path}`.");
+    classify_annotated_model(aeon_str.as_str(), output_zip, options)?;
+    Ok(())
+}
+
+/// Perform the classification of a BN model given in the `model_path` `AEON` file, with the
+/// HCTL formulae given in a separate `properties_path` file.
+///
+/// The formulae are given using the same `#! dynamic_assertion` and `#! dynamic_property`
+/// annotations as in an annotated model. The model and the formulae are merged into one
+/// annotated model, which is then classified as in [classify] (and the merged model is included
+/// in the `output_zip` archive).
+pub fn run_classification(
+    model_path: &str,
+    properties_path: &str,
+    output_zip: &str,
+) -> Result<(), String> {
+    let Ok(model_str) = std::fs::read_to_string(model_path) else {
+        return Err(format!("Input file `{model_path}` is not accessible."));
+    };
+    let Ok(properties_str) = std::fs::read_to_string(properties_path) else {
+        return Err(format!("Input file `{properties_path}` is not accessible."));
+    };
+    let aeon_str = format!("{properties_str}\n{model_str}");
+    classify_annotated_model(aeon_str.as_str(), output_zip, &OutputOptions::default())
+}
+
+/// Same as [build_classification_result], but the valid colors and the `property_results` are
+/// given as the string representations of their BDDs in the symbolic context of the `graph`
+/// (see [parse_colors]). This way, the model checking can happen in a separate process (or even
+/// another tool), which only passes the serialized results to the classification.
+///
+/// The result records the number of HCTL variables of the `graph` and the number of all its
+/// colors, so it can be written by [write_classification_result_with_graph].
+pub fn classify_serialized_results(
+    graph: &SymbolicAsyncGraph,
+    assertion_formulae: &[String],
+    valid_colors: &str,
+    named_property_formulae: &[(String, String)],
+    property_results: &[String],
+) -> Result<ClassificationResult, String> {
+    let valid_colors = parse_colors(valid_colors, graph)
+        .map_err(|e| format!("Invalid BDD of the valid colors: {e}"))?;
+    let mut property_colors = Vec::with_capacity(property_results.len());
+    for (i, bdd_str) in property_results.iter().enumerate() {
+        let colors = parse_colors(bdd_str, graph)
+            .map_err(|e| format!("Invalid BDD of property result {i}: {e}"))?;
+        property_colors.push(colors);
+    }
+    let mut result = build_classification_result(
+        assertion_formulae,
+        &valid_colors,
+        named_property_formulae,
+        &property_colors,
+    )
+    .map_err(|e| e.to_string())?;
+    result.num_hctl_vars = context_hctl_vars(graph.symbolic_context());
+    result.total_cardinality = Some(graph.mk_unit_colors().approx_cardinality());
+    Ok(result)
+}
+
+/// Merge the classification archives of several shards (e.g., computed for different parts of the
+/// parameter space) into one `output_zip` archive.
+///
+/// The colors of the categories with the same validity vector are merged, and the report is
+/// computed for the merged categories. All shards must be based on the same model, and use the
+/// same assertions and properties (in the same order), as well as the same number of HCTL
+/// variables.
+pub fn merge_archives(archive_paths: &[&str], output_zip: &str) -> Result<(), String> {
+    let Some((first_path, other_paths)) = archive_paths.split_first() else {
+        return Err("No archives to merge.".to_string());
+    };
+    let summary = read_archive_summary(first_path)?;
+    let mut options = OutputOptions {
+        source_fingerprint: summary.source_fingerprint.clone(),
+        ..OutputOptions::default()
+    };
+    for path in other_paths {
+        let other = read_archive_summary(path)?;
+        // The merged result only comes from a single source if all shards agree on it.
+        if other.source_fingerprint != options.source_fingerprint {
+            options.source_fingerprint = None;
+        }
+        if other.model != summary.model {
+            return Err(format!("Archive `{path}` is based on a different model."));
+        }
+        if other.assertion_formulae != summary.assertion_formulae {
+            return Err(format!("Archive `{path}` uses different assertions."));
+        }
+        if other.properties != summary.properties {
+            return Err(format!(
+                "Archive `{path}` uses different properties (or their order)."
+            ));
+        }
+        if other.num_hctl_vars != summary.num_hctl_vars {
+            return Err(format!(
+                "Archive `{path}` uses {} HCTL variables, but `{first_path}` uses {}.",
+                other.num_hctl_vars, summary.num_hctl_vars
+            ));
+        }
+    }
+
+    let bn = BooleanNetwork::try_from(summary.model.as_str())?;
+    let graph = SymbolicAsyncGraph::new(bn)?;
+    // The property results are restricted to the valid colors, so they can be reconstructed
+    // as unions of the categories in which each property holds.
+    let mut valid_colors = graph.mk_empty_colors();
+    let mut property_colors = vec![graph.mk_empty_colors(); summary.properties.len()];
+    for path in archive_paths {
+        for (validity, colors) in read_classification_archive(path, &graph)? {
+            if validity.len() != summary.properties.len() {
+                return Err(format!(
+                    "Category `{validity:?}` in `{path}` does not match the properties."
+                ));
+            }
+            valid_colors = valid_colors.union(&colors);
+            for (property, valid) in property_colors.iter_mut().zip(validity) {
+                if valid {
+                    *property = property.union(&colors);
+                }
+            }
+        }
+    }
+
+    let mut result = build_classification_result(
+        &summary.assertion_formulae,
+        &valid_colors,
+        &summary.properties,
+        &property_colors,
+    )
+    .map_err(|e| e.to_string())?;
+    result.num_hctl_vars = summary.num_hctl_vars;
+    result.formula_hctl_vars = summary.formula_hctl_vars;
+    result.total_cardinality = Some(graph.mk_unit_colors().approx_cardinality());
+    write_classification_result_with_graph(&result, output_zip, &summary.model, &graph, &options)
+        .map_err(|e| e.to_string())
+}
+
+/// Perform the classification (as described in [classify]) of an annotated model given
+/// by the `aeon_str`.
+fn classify_annotated_model(
+    aeon_str: &str,
+    output_zip: &str,
+    options: &OutputOptions,
+) -> Result<(), String> {
+    // Fail before the (possibly long) computation rather than after it.
+    if !options.overwrite && Path::new(output_zip).exists() {
+        return Err(ClassifierError::OutputExists(output_zip.to_string()).to_string());
+    }
+
+    // Unless given explicitly, the input is identified by the fingerprint of the annotated model.
+    let mut options = options.clone();
+    if options.source_fingerprint.is_none() {
+        options.source_fingerprint = Some(source_fingerprint(aeon_str));
+    }
+
+    // TODO: allow caching between model-checking assertions and properties somehow
+
+    // load the model and two sets of formulae (from model annotations)
+    let bn = BooleanNetwork::try_from(aeon_str)?;
+    let (assertions, mut named_properties) = read_annotated_formulae(aeon_str)?;
+    if let Some(order) = &options.property_order {
+        named_properties = property_order_indices(&named_properties, order)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|i| named_properties[i].clone())
+            .collect();
+    }
+    println!("Loaded model and properties.");
 
     println!("Parsing formulae and generating symbolic representation...");
     // Combine all assertions into one formula and add it to the list of properties.
@@ -75,11 +360,21 @@ pub fn classify(model_path: &str, output_zip: &str) -> Result<(), String> {
     // Parse all formulae and count the max. number of HCTL variables across formulae.
     let assertion_tree = parse_and_minimize_hctl_formula(&bn, &assertion)?;
     let mut num_hctl_vars = collect_unique_hctl_vars(assertion_tree.clone()).len();
+    // Also remember the number of variables of each individual formula (for the metadata).
+    let mut formula_hctl_vars = Vec::new();
+    let mut assertion_trees: Vec<HctlTreeNode> = Vec::new();
+    for (i, formula) in assertions.iter().enumerate() {
+        let tree = parse_and_minimize_hctl_formula(&bn, formula.as_str())?;
+        let tree_vars = collect_unique_hctl_vars(tree.clone()).len();
+        formula_hctl_vars.push((format!("assertion_{}", i + 1), tree_vars));
+        assertion_trees.push(tree);
+    }
     let mut property_trees: Vec<HctlTreeNode> = Vec::new();
-    for (_name, formula) in &named_properties {
+    for (name, formula) in &named_properties {
         let tree = parse_and_minimize_hctl_formula(&bn, formula.as_str())?;
         let tree_vars = collect_unique_hctl_vars(tree.clone()).len();
         num_hctl_vars = max(num_hctl_vars, tree_vars);
+        formula_hctl_vars.push((name.clone(), tree_vars));
         property_trees.push(tree);
     }
 
@@ -107,49 +402,105 @@ pub fn classify(model_path: &str, output_zip: &str) -> Result<(), String> {
         valid_colors.approx_cardinality(),
     );
 
-    if valid_colors.is_empty() {
-        println!("No instance satisfies given required properties. Aborting.");
-        return write_empty_report(&assertions, output_zip).map_err(|e| format!("{e:?}"));
-    }
+    let (valid_colors, property_colors, assertion_cardinalities) = if valid_colors.is_empty() {
+        println!("No instance satisfies given required properties. Skipping the properties.");
+        // Find out which of the assertions cannot be satisfied (together with the others).
+        let assertion_cardinalities: Vec<f64> =
+            check_assertions_individually(assertion_trees, &graph)?
+                .iter()
+                .map(|colors| colors.approx_cardinality())
+                .collect();
+        for (i, cardinality) in assertion_cardinalities.iter().enumerate() {
+            println!(
+                "Assertion {} is satisfied by {cardinality:.0} colors.",
+                i + 1
+            );
+        }
+        // No color can satisfy any property, so all the classes are empty (but the archive
+        // is still written as usual).
+        let valid_colors = sanitize_colors(&graph, &valid_colors);
+        let property_colors = vec![valid_colors.clone(); named_properties.len()];
+        (valid_colors, property_colors, assertion_cardinalities)
+    } else {
+        // restrict the colors on the symbolic graph
+        let graph = SymbolicAsyncGraph::with_custom_context(
+            bn,
+            graph.symbolic_context().clone(),
+            valid_colors.as_bdd().clone(),
+        )?;
 
-    // restrict the colors on the symbolic graph
-    let graph = SymbolicAsyncGraph::with_custom_context(
-        bn,
-        graph.symbolic_context().clone(),
-        valid_colors.as_bdd().clone(),
-    )?;
+        println!("Evaluating classification properties (this may take some time)...");
+        // Model check all properties on the restricted graph.
+        let property_result = model_check_multiple_trees_dirty(property_trees, &graph)?;
+        let property_colors: Vec<GraphColors> = property_result
+            .iter()
+            .map(|result| get_universal_colors(&graph, result))
+            .collect();
+        println!("Classification properties successfully evaluated.");
 
-    println!("Evaluating classification properties (this may take some time)...");
-    // Model check all properties on the restricted graph.
-    let property_result = model_check_multiple_trees_dirty(property_trees, &graph)?;
-    let property_colors: Vec<GraphColors> = property_result
-        .iter()
-        .map(|result| get_universal_colors(&graph, result))
-        .collect();
-    println!("Classification properties successfully evaluated.");
-
-    // This is an important step where we ensure that the "model checking context"
-    // does not "leak" outside of the BN classifier. In essence, this ensures that the
-    // BDD that we output is compatible with any `SymbolicAsyncGraph` based on the
-    // originally supplied model (i.e. if we want to read the BDD, we don't have to
-    // add any additional state variables to the symbolic context).
-    let valid_colors = sanitize_colors(&graph, &valid_colors);
-    let property_colors: Vec<GraphColors> = property_colors
-        .iter()
-        .map(|c| sanitize_colors(&graph, c))
-        .collect();
+        // This is an important step where we ensure that the "model checking context"
+        // does not "leak" outside of the BN classifier. In essence, this ensures that the
+        // BDD that we output is compatible with any `SymbolicAsyncGraph` based on the
+        // originally supplied model (i.e. if we want to read the BDD, we don't have to
+        // add any additional state variables to the symbolic context).
+        let valid_colors = sanitize_colors(&graph, &valid_colors);
+        let property_colors: Vec<GraphColors> = property_colors
+            .iter()
+            .map(|c| sanitize_colors(&graph, c))
+            .collect();
+        (valid_colors, property_colors, Vec::new())
+    };
 
-    // do the classification while printing the report and dumping resulting BDDs
-    println!("Generating classification mapping based on model-checking results...");
-    write_classifier_output(
-        &assertions,
-        &valid_colors,
-        &named_properties,
-        &property_colors,
+    let mut result = if options.stats_only {
+        // Only the cardinalities are computed, but the result is written as any other.
+        println!("Counting the classes based on model-checking results...");
+        build_cardinality_classification_result(
+            &assertions,
+            &valid_colors,
+            &named_properties,
+            &property_colors,
+        )
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| {
+            format!(
+                "Only the classes of at most {MAX_STATS_ONLY_PROPERTIES} properties can be counted."
+            )
+        })?
+    } else {
+        // do the classification while printing the report and dumping resulting BDDs
+        println!("Generating classification mapping based on model-checking results...");
+        if exceeds_max_classes(named_properties.len(), options.max_classes) {
+            println!(
+                "The {} properties have more than {} combinations, so only the non-empty classes \
+                are enumerated (see `--max-classes`).",
+                named_properties.len(),
+                options.max_classes,
+            );
+        }
+        build_classification_result_with_limit(
+            &assertions,
+            &valid_colors,
+            &named_properties,
+            &property_colors,
+            options.max_classes,
+        )
+        .map_err(|e| e.to_string())?
+    };
+    // The sanitized colors are encoded in the canonical context of the original model (whose
+    // unit colors are all colors, unlike the colors of the graph restricted to the valid ones).
+    let canonical_graph = SymbolicAsyncGraph::new(graph.as_network().clone())?;
+    result.num_hctl_vars = num_hctl_vars;
+    result.formula_hctl_vars = formula_hctl_vars;
+    result.total_cardinality = Some(canonical_graph.mk_unit_colors().approx_cardinality());
+    result.assertion_cardinalities = assertion_cardinalities;
+    write_classification_result_with_graph(
+        &result,
         output_zip,
-        aeon_str.as_str(),
+        aeon_str,
+        &canonical_graph,
+        &options,
     )
-    .map_err(|e| format!("{e:?}"))?;
+    .map_err(|e| e.to_string())?;
     println!("Results saved to `{output_zip}`.");
 
     Ok(())
@@ -157,13 +508,41 @@ pub fn classify(model_path: &str, output_zip: &str) -> Result<(), String> {
 
 #[cfg(test)]
 mod tests {
+    use crate::categories::tests::prepare_graph_and_sets;
+    use crate::categories::{bool_vec_to_int, build_classification_result, colors_equal};
     use crate::classification::{
-        build_combined_assertion, read_model_assertions, read_model_properties,
+        build_combined_assertion, classify, classify_serialized_results, merge_archives,
+        read_annotated_formulae, read_classification_archive, read_model_assertions,
+        read_model_properties, run_classification, Classifier,
+    };
+    use crate::errors::ClassifierError;
+    use crate::load_inputs::{
+        load_classification_archive, load_classification_archive_with_layout,
+        read_archive_properties, read_archive_report, read_archive_summary,
+        read_archive_summary_with_layout, read_filtered_classification_archive,
+        read_formula_variables, read_property_file, stream_classification_archive, verify_archive,
+        verify_classification_archive, verify_classification_archive_with_layout, ClassFilter,
+    };
+    use crate::write_output::{
+        build_classification_archive, source_fingerprint, split_archive_names,
+        write_classification_result_with_options, ArchiveLayout, BitOrder, ClassificationWriter,
+        OutputOptions,
+    };
+    use biodivine_hctl_model_checker::mc_utils::{
+        collect_unique_hctl_vars, get_extended_symbolic_graph,
     };
-    use biodivine_hctl_model_checker::mc_utils::collect_unique_hctl_vars;
     use biodivine_hctl_model_checker::preprocessing::parser::parse_and_minimize_hctl_formula;
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::symbolic_async_graph::SymbolicAsyncGraph;
     use biodivine_lib_param_bn::{BooleanNetwork, ModelAnnotation};
     use std::cmp::max;
+    use std::collections::HashMap;
+    use std::fs::File;
+    use std::io::{Cursor, Read, Write};
+    use std::sync::Arc;
+    use std::thread;
+    use zip::write::FileOptions;
+    use zip::{ZipArchive, ZipWriter};
 
     #[test]
     /// Test the formulae parsing and variable counting
@@ -210,6 +589,30 @@ mod tests {
         )
     }
 
+    #[test]
+    /// Test extracting the formulae directly from an annotated AEON model string.
+    fn test_read_annotated_formulae() {
+        let aeon_str = r"
+            #! dynamic_assertion: #`true`#
+            #! dynamic_property: p2: #`AG EF v_2`#
+            #! dynamic_property: p1: #`AX v_3`#
+            #! static_property: s1: #`f_v_2(x)`#
+            $v_2:!v_3
+            v_3 -| v_2
+            $v_3:v_3
+            v_3 -> v_3
+        ";
+        let (assertions, named_properties) = read_annotated_formulae(aeon_str).unwrap();
+        assert_eq!(assertions, vec!["true".to_string()]);
+        assert_eq!(
+            named_properties,
+            vec![
+                ("p1".to_string(), "AX v_3".to_string()),
+                ("p2".to_string(), "AG EF v_2".to_string()),
+            ]
+        );
+    }
+
     #[test]
     /// Test extracting the formulae from the AEON format annotations.
     fn test_extracting_formulae() {
@@ -258,4 +661,926 @@ mod tests {
             "Found multiple properties named `p1`."
         );
     }
+
+    #[test]
+    /// Test that the variables of a formula are collected with their original names.
+    fn test_read_formula_variables() {
+        let variables =
+            read_formula_variables("!{x}: 3{y}: (@{x}: ~a & b) & (@{y}: AX a)").unwrap();
+        assert_eq!(variables.network_variables, vec!["a", "b"]);
+        assert_eq!(variables.hctl_variables, vec!["x", "y"]);
+        let variables = read_formula_variables("true").unwrap();
+        assert!(variables.network_variables.is_empty());
+        assert!(variables.hctl_variables.is_empty());
+        assert!(read_formula_variables("a &").is_err());
+    }
+
+    #[test]
+    /// Test that the dumped categories can be loaded back from the archive (in both bit orders),
+    /// always with the MSB-first validity vectors.
+    fn test_archive_round_trip() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let property_results = vec![sets[0].clone(), sets[1].clone()];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-round-trip.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        for bit_order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            ClassificationWriter::new("a -?? b\nb -?? a\n")
+                .bit_order(bit_order)
+                .write(&result, archive_name)
+                .unwrap();
+
+            let loaded = read_classification_archive(archive_name, &graph).unwrap();
+            let (labeled, _) = load_classification_archive(archive_name.to_string()).unwrap();
+            std::fs::remove_file(&archive_path).unwrap();
+            assert_eq!(loaded.len(), result.categories.len());
+            for ((validity, colors), category) in loaded.iter().zip(result.categories.iter()) {
+                assert_eq!(validity, &category.validity);
+                assert_eq!(colors.as_bdd(), category.colors.as_bdd());
+                let label: String = validity
+                    .iter()
+                    .map(|it| if *it { '1' } else { '0' })
+                    .collect();
+                assert_eq!(labeled[&label].as_bdd(), category.colors.as_bdd());
+            }
+        }
+    }
+
+    #[test]
+    /// Test that overlapping categories are detected, and that the report can be read back.
+    fn test_verify_classification_archive() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-verify.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        let model = "a -?? b\nb -?? a\n";
+        ClassificationWriter::new(model)
+            .checksums(true)
+            .write(&result, archive_name)
+            .unwrap();
+        assert_eq!(verify_classification_archive(archive_name), Ok(()));
+        let report = read_archive_report(archive_name).unwrap();
+        assert!(report.contains("### Classes"));
+
+        let categories = HashMap::from([
+            ("0".to_string(), sets[0].clone()),
+            ("1".to_string(), sets[0].union(&sets[1])),
+        ]);
+        build_classification_archive(categories, archive_name, model).unwrap();
+        let error = verify_classification_archive(archive_name).unwrap_err();
+        std::fs::remove_file(&archive_path).unwrap();
+        assert!(error.contains("overlaps with other categories"));
+    }
+
+    #[test]
+    /// Test that the serialized results are classified the same as the color sets, and that
+    /// BDDs from a different symbolic context are rejected.
+    fn test_classify_serialized_results() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let expected =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..2])
+                .unwrap();
+        let valid_colors = graph.unit_colors().as_bdd().to_string();
+        let serialized: Vec<String> = sets[0..2].iter().map(|s| s.as_bdd().to_string()).collect();
+        let result =
+            classify_serialized_results(&graph, &[], &valid_colors, &properties, &serialized)
+                .unwrap();
+        assert_eq!(result.total_cardinality, Some(16.0));
+        assert_eq!(result.categories.len(), expected.categories.len());
+        for (x, y) in result.categories.iter().zip(expected.categories.iter()) {
+            assert_eq!(x.validity, y.validity);
+            assert!(colors_equal(&x.colors, &y.colors));
+        }
+
+        let extended_graph = get_extended_symbolic_graph(graph.as_network(), 1).unwrap();
+        let Err(error) = classify_serialized_results(
+            &extended_graph,
+            &[],
+            &valid_colors,
+            &properties,
+            &serialized,
+        ) else {
+            panic!("The BDDs do not match the extended symbolic context.");
+        };
+        assert!(error.starts_with("Invalid BDD of the valid colors"));
+        assert!(classify_serialized_results(&graph, &[], "", &properties, &serialized).is_err());
+    }
+
+    #[test]
+    /// Test that the categories are filtered by their cardinality (also given as a percentage of
+    /// the valid colors) and by their validity vectors.
+    fn test_read_filtered_classification_archive() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        // Categories `00`, `01`, `10` and `11` have 8, 0, 4 and 4 colors.
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-filtered.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        let model = "a -?? b\nb -?? a\n";
+        let read = |filter: ClassFilter| {
+            read_filtered_classification_archive(archive_name, &graph, &filter).map(|loaded| {
+                let validities: Vec<Vec<bool>> = loaded.into_iter().map(|(v, _)| v).collect();
+                validities
+            })
+        };
+        for bit_order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            ClassificationWriter::new(model)
+                .bit_order(bit_order)
+                .write(&result, archive_name)
+                .unwrap();
+            let large = read(ClassFilter {
+                min_cardinality: 5.0,
+                ..ClassFilter::default()
+            });
+            assert_eq!(large, Ok(vec![vec![false, false]]));
+            let non_empty = read(ClassFilter {
+                min_percentage: 25.0,
+                ..ClassFilter::default()
+            });
+            let expected = vec![vec![false, false], vec![true, false], vec![true, true]];
+            assert_eq!(non_empty, Ok(expected));
+            let selected = read(ClassFilter {
+                min_cardinality: 1.0,
+                validities: Some(vec![vec![true, true], vec![false, true]]),
+                ..ClassFilter::default()
+            });
+            assert_eq!(selected, Ok(vec![vec![true, true]]));
+        }
+
+        // Without the report, the classes cannot be filtered by the percentage.
+        let categories = HashMap::from([("0".to_string(), sets[0].clone())]);
+        build_classification_archive(categories, archive_name, model).unwrap();
+        let large = read(ClassFilter {
+            min_cardinality: 10.0,
+            ..ClassFilter::default()
+        });
+        assert_eq!(large, Ok(Vec::new()));
+        assert!(read(ClassFilter {
+            min_percentage: 10.0,
+            ..ClassFilter::default()
+        })
+        .is_err());
+
+        // A report that is not valid UTF-8 is an error as well (not a panic).
+        let mut writer = ZipWriter::new(File::create(&archive_path).unwrap());
+        writer
+            .start_file("model.aeon", FileOptions::default())
+            .unwrap();
+        writer.write_all(model.as_bytes()).unwrap();
+        writer
+            .start_file("bdd_dump_0.txt", FileOptions::default())
+            .unwrap();
+        writer
+            .write_all(sets[0].as_bdd().to_string().as_bytes())
+            .unwrap();
+        writer
+            .start_file("report.json", FileOptions::default())
+            .unwrap();
+        writer.write_all(&[0xff, 0xfe, 0xfd]).unwrap();
+        writer.finish().unwrap();
+        let error = read(ClassFilter {
+            min_percentage: 10.0,
+            ..ClassFilter::default()
+        });
+        assert!(matches!(error, Err(message) if message.contains("report.json")));
+        std::fs::remove_file(&archive_path).unwrap();
+    }
+
+    #[test]
+    /// Test that the property file of an archive reproduces the classification, and that its
+    /// version is checked.
+    fn test_property_file_round_trip() {
+        let dir = std::env::temp_dir();
+        let model_path = dir.join("bn-classifier-test-hctl-model.aeon");
+        let properties_path = dir.join("bn-classifier-test-hctl-properties.txt");
+        let archive_path = dir.join("bn-classifier-test-hctl.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        std::fs::write(&model_path, "a -?? b\nb -?? a\n").unwrap();
+        std::fs::write(
+            &properties_path,
+            "#! dynamic_assertion: #`true`#\n#! dynamic_property: p2: #`AG EF a`#\n\
+            #! dynamic_property: p1: #`EF b`#\n",
+        )
+        .unwrap();
+        let run = || {
+            run_classification(
+                model_path.to_str().unwrap(),
+                properties_path.to_str().unwrap(),
+                archive_name,
+            )
+            .unwrap();
+            read_archive_summary(archive_name).unwrap()
+        };
+        let first = run();
+
+        let (assertions, properties) = read_archive_properties(archive_name).unwrap();
+        assert_eq!(assertions, first.assertion_formulae);
+        assert_eq!(properties, first.properties);
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let mut property_file = String::new();
+        archive
+            .by_name("properties.hctl")
+            .unwrap()
+            .read_to_string(&mut property_file)
+            .unwrap();
+        assert!(property_file.starts_with("#! property_file_version: 1\n"));
+        std::fs::write(&properties_path, &property_file).unwrap();
+        let second = run();
+        assert_eq!(second.properties, first.properties);
+        assert_eq!(second.assertion_formulae, first.assertion_formulae);
+
+        // Unlike the annotations of a model, the property file keeps the order of properties.
+        let reordered = "#! property_file_version: 1\n#! dynamic_property: p2: #`a`#\n\
+            #! dynamic_property: p1: b\n";
+        let (_, properties) = read_property_file(reordered).unwrap();
+        assert_eq!(
+            properties,
+            vec![
+                ("p2".to_string(), "a".to_string()),
+                ("p1".to_string(), "b".to_string())
+            ]
+        );
+        assert!(read_property_file("#! dynamic_property: p1: b\n").is_err());
+
+        std::fs::remove_file(model_path).unwrap();
+        std::fs::remove_file(properties_path).unwrap();
+        std::fs::remove_file(archive_path).unwrap();
+    }
+
+    #[test]
+    /// Test that the streamed categories are filtered by their validity vectors.
+    fn test_stream_classification_archive() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..2])
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-stream.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        ClassificationWriter::new("a -?? b\nb -?? a\n")
+            .write(&result, archive_name)
+            .unwrap();
+
+        let mut seen = Vec::new();
+        let streamed: Vec<_> = stream_classification_archive(archive_name, &graph, |validity| {
+            seen.push(validity.to_vec());
+            validity[0]
+        })
+        .unwrap()
+        .collect::<Result<_, String>>()
+        .unwrap();
+        std::fs::remove_file(&archive_path).unwrap();
+        assert_eq!(seen.len(), 4);
+        assert_eq!(streamed.len(), 2);
+        for (validity, colors) in &streamed {
+            let category = &result.categories[bool_vec_to_int(validity) as usize];
+            assert!(validity[0]);
+            assert_eq!(colors.as_bdd(), category.colors.as_bdd());
+        }
+    }
+
+    #[test]
+    /// Test that the dumps cannot be loaded into a graph with a different number of HCTL vars.
+    fn test_archive_var_count_mismatch() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-var-count.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        ClassificationWriter::new("a -?? b\nb -?? a\n")
+            .write(&result, archive_name)
+            .unwrap();
+
+        let extended_graph = get_extended_symbolic_graph(graph.as_network(), 1).unwrap();
+        let error = read_classification_archive(archive_name, &extended_graph).unwrap_err();
+        std::fs::remove_file(&archive_path).unwrap();
+        let expected = ClassifierError::VarCountMismatch {
+            archive: 0,
+            graph: 1,
+        };
+        assert_eq!(error, expected.to_string());
+    }
+
+    #[test]
+    /// Test that the binary BDD dumps can be loaded back by both loaders.
+    fn test_binary_dumps_round_trip() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-binary.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        ClassificationWriter::new("a -?? b\nb -?? a\n")
+            .binary_bdds(true)
+            .write(&result, archive_name)
+            .unwrap();
+
+        let archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let mut dumps: Vec<&str> = archive
+            .file_names()
+            .filter(|it| it.starts_with("bdd_dump_"))
+            .collect();
+        dumps.sort();
+        assert_eq!(dumps, vec!["bdd_dump_0.bdd", "bdd_dump_1.bdd"]);
+
+        let loaded = read_classification_archive(archive_name, &graph).unwrap();
+        let (by_name, _) = load_classification_archive(archive_name.to_string()).unwrap();
+        std::fs::remove_file(&archive_path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        for ((validity, colors), category) in loaded.iter().zip(result.categories.iter()) {
+            assert_eq!(validity, &category.validity);
+            assert_eq!(colors.as_bdd(), category.colors.as_bdd());
+        }
+        assert_eq!(by_name["1"].as_bdd(), sets[0].as_bdd());
+    }
+
+    #[test]
+    /// Test that the dumps compressed with a dictionary can be loaded back by both loaders.
+    fn test_dictionary_dumps_round_trip() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties: Vec<(String, String)> = (0..4)
+            .map(|i| (format!("p{i}"), "true".to_string()))
+            .collect();
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets).unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-dictionary.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        ClassificationWriter::new("a -?? b\nb -?? a\n")
+            .bdd_dictionary(true)
+            .checksums(true)
+            .write(&result, archive_name)
+            .unwrap();
+
+        let archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        assert!(names.contains(&"bdd.dict"));
+        assert!(names.contains(&"bdd_dump_0000.txt.zst"));
+
+        let loaded = read_classification_archive(archive_name, &graph).unwrap();
+        let (by_name, _) = load_classification_archive(archive_name.to_string()).unwrap();
+        let verified = verify_archive(archive_name);
+        std::fs::remove_file(&archive_path).unwrap();
+        assert_eq!(verified, Ok(()));
+        assert_eq!(loaded.len(), 16);
+        for ((validity, colors), category) in loaded.iter().zip(result.categories.iter()) {
+            assert_eq!(validity, &category.validity);
+            assert_eq!(colors.as_bdd(), category.colors.as_bdd());
+        }
+        assert_eq!(
+            by_name["1111"].as_bdd(),
+            result.categories[15].colors.as_bdd()
+        );
+    }
+
+    #[test]
+    /// Test that the relative BDD dumps are complemented back by both loaders.
+    fn test_relative_dumps_round_trip() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        // Category `0` has 12 of the 16 colors, so it is dumped relative to them.
+        let property_results = vec![sets[0].intersect(&sets[1])];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-relative.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        ClassificationWriter::new("a -?? b\nb -?? a\n")
+            .relative_bdds(true)
+            .write(&result, archive_name)
+            .unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let mut index = String::new();
+        archive
+            .by_name("bdd_index.txt")
+            .unwrap()
+            .read_to_string(&mut index)
+            .unwrap();
+        // The complement of category `0` is exactly category `1`, so they share the dump.
+        assert_eq!(index, "0\tbdd_dump_0.txt\trelative\n1\tbdd_dump_0.txt\n");
+        assert!(archive.by_name("valid_colors.txt").is_ok());
+
+        let loaded = read_classification_archive(archive_name, &graph).unwrap();
+        let (by_name, _) = load_classification_archive(archive_name.to_string()).unwrap();
+        std::fs::remove_file(&archive_path).unwrap();
+        for ((validity, colors), category) in loaded.iter().zip(result.categories.iter()) {
+            assert_eq!(validity, &category.validity);
+            assert!(colors_equal(colors, &category.colors));
+        }
+        assert_eq!(by_name["0"].approx_cardinality(), 12.0);
+    }
+
+    #[test]
+    /// Test that equal categories share one BDD dump, but are all loaded back.
+    fn test_archive_deduplication() {
+        let (_graph, sets) = prepare_graph_and_sets();
+        // The same set, but built in a different way.
+        let same_set = sets[0].union(&sets[1]).intersect(&sets[0]);
+        let categories = HashMap::from([
+            ("a".to_string(), sets[0].clone()),
+            ("b".to_string(), sets[1].clone()),
+            ("c".to_string(), same_set),
+        ]);
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-dedup.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        build_classification_archive(categories, archive_name, "a -?? b\nb -?? a\n").unwrap();
+
+        let file = std::fs::File::open(&archive_path).unwrap();
+        let archive = zip::ZipArchive::new(file).unwrap();
+        let mut dumps: Vec<&str> = archive
+            .file_names()
+            .filter(|it| it.starts_with("bdd_dump_"))
+            .collect();
+        dumps.sort();
+        assert_eq!(dumps, vec!["bdd_dump_a.txt", "bdd_dump_b.txt"]);
+
+        let (loaded, _) = load_classification_archive(archive_name.to_string()).unwrap();
+        std::fs::remove_file(archive_path).unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded["c"].as_bdd(), loaded["a"].as_bdd());
+    }
+
+    #[test]
+    /// Test that binary (and compressed) dumps of split results are loaded back as the original
+    /// categories, also through the report archive.
+    fn test_load_split_binary_archive() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..2])
+                .unwrap();
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-load-split.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        let options = OutputOptions {
+            split_bdds: true,
+            binary_bdds: true,
+            bdd_dictionary: true,
+            relative_bdds: true,
+            ..Default::default()
+        };
+        write_classification_result_with_options(
+            &result,
+            archive_name,
+            "a -?? b\nb -?? a\n",
+            &options,
+        )
+        .unwrap();
+
+        let (report_name, bdds_name) = split_archive_names(archive_name);
+        for name in [&report_name, &bdds_name] {
+            let (loaded, _) = load_classification_archive(name.clone()).unwrap();
+            assert_eq!(loaded.len(), 4);
+            for category in &result.categories {
+                let label: String = category
+                    .validity
+                    .iter()
+                    .map(|it| if *it { '1' } else { '0' })
+                    .collect();
+                assert!(colors_equal(&loaded[&label], &category.colors));
+            }
+        }
+        std::fs::remove_file(report_name).unwrap();
+        std::fs::remove_file(bdds_name).unwrap();
+    }
+
+    #[test]
+    /// Test that the archives with a custom layout can be loaded with the same layout.
+    fn test_load_archive_with_layout() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let result = build_classification_result(
+            &["true".to_string()],
+            graph.unit_colors(),
+            &properties,
+            &sets[0..2],
+        )
+        .unwrap();
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-load-layout.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        let layout = ArchiveLayout {
+            prefix: "classification/".to_string(),
+            model: "network.aeon".to_string(),
+            bdd_index: "index.tsv".to_string(),
+            valid_colors: "valid.txt".to_string(),
+            bdd_dump: "class_{name}.txt".to_string(),
+            ..Default::default()
+        };
+        let options = OutputOptions {
+            split_bdds: true,
+            relative_bdds: true,
+            checksums: true,
+            layout: layout.clone(),
+            ..Default::default()
+        };
+        write_classification_result_with_options(
+            &result,
+            archive_name,
+            "a -?? b\nb -?? a\n",
+            &options,
+        )
+        .unwrap();
+
+        let (report_name, bdds_name) = split_archive_names(archive_name);
+        assert!(load_classification_archive(report_name.clone()).is_err());
+        let (loaded, _) = load_classification_archive_with_layout(&report_name, &layout).unwrap();
+        assert_eq!(loaded.len(), 4);
+        let summary = read_archive_summary_with_layout(&report_name, &layout).unwrap();
+        assert_eq!(summary.properties, properties);
+        verify_classification_archive_with_layout(&bdds_name, &layout).unwrap();
+        std::fs::remove_file(report_name).unwrap();
+        std::fs::remove_file(bdds_name).unwrap();
+    }
+
+    #[test]
+    /// Test the classification of a model with formulae given in a separate file.
+    fn test_run_classification() {
+        let dir = std::env::temp_dir();
+        let model_path = dir.join("bn-classifier-test-run-model.aeon");
+        let properties_path = dir.join("bn-classifier-test-run-properties.txt");
+        let archive_path = dir.join("bn-classifier-test-run.zip");
+        std::fs::write(&model_path, "a -?? b\nb -?? a\n").unwrap();
+        std::fs::write(
+            &properties_path,
+            "#! dynamic_assertion: #`true`#\n#! dynamic_property: p1: #`AG EF a`#\n",
+        )
+        .unwrap();
+
+        run_classification(
+            model_path.to_str().unwrap(),
+            properties_path.to_str().unwrap(),
+            archive_path.to_str().unwrap(),
+        )
+        .unwrap();
+
+        let (categories, aeon_str) =
+            load_classification_archive(archive_path.to_str().unwrap().to_string()).unwrap();
+        let annotations = ModelAnnotation::from_model_string(aeon_str.as_str());
+        assert_eq!(read_model_properties(&annotations).unwrap().len(), 1);
+        // The archive is matched with its (merged) input by the fingerprint.
+        let summary = read_archive_summary(archive_path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            summary.source_fingerprint,
+            Some(source_fingerprint(&aeon_str))
+        );
+        let total: f64 = categories
+            .values()
+            .map(|colors| colors.approx_cardinality())
+            .sum();
+        assert_eq!(total, 16.0);
+
+        std::fs::remove_file(model_path).unwrap();
+        std::fs::remove_file(properties_path).unwrap();
+        std::fs::remove_file(archive_path).unwrap();
+    }
+
+    #[test]
+    /// Test that the stats-only classification writes the class cardinalities without BDDs.
+    fn test_classify_stats_only() {
+        let dir = std::env::temp_dir();
+        let model_path = dir.join("bn-classifier-test-stats-model.aeon");
+        let archive_path = dir.join("bn-classifier-test-stats.zip");
+        std::fs::write(
+            &model_path,
+            "#! dynamic_assertion: #`true`#\n#! dynamic_property: p1: #`AG EF a`#\na -?? b\nb -?? a\n",
+        )
+        .unwrap();
+
+        let options = OutputOptions {
+            stats_only: true,
+            ..Default::default()
+        };
+        classify(
+            model_path.to_str().unwrap(),
+            archive_path.to_str().unwrap(),
+            &options,
+        )
+        .unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        assert!(!archive
+            .file_names()
+            .any(|name| name.starts_with("bdd_dump")));
+        assert!(archive.by_name("properties.hctl").is_ok());
+        assert!(archive.by_name("report.json").is_ok());
+        let mut report = String::new();
+        archive
+            .by_name("report.txt")
+            .unwrap()
+            .read_to_string(&mut report)
+            .unwrap();
+        std::fs::remove_file(model_path).unwrap();
+        std::fs::remove_file(archive_path).unwrap();
+        assert!(report.contains("covering 16 colors"));
+        assert!(report.contains("0 valid colors are not covered by any class"));
+        assert!(report.contains("# p1  |  AG EF a"));
+        assert_eq!(report.matches("colors in this category").count(), 2);
+        assert!(!report.contains("BDD of this category"));
+    }
+
+    #[test]
+    /// Test that the fraction of all parameterizations is relative to all colors of the model,
+    /// also when the assertions remove some of them.
+    fn test_classify_total_cardinality() {
+        let dir = std::env::temp_dir();
+        let model_path = dir.join("bn-classifier-test-total-model.aeon");
+        let archive_path = dir.join("bn-classifier-test-total.zip");
+        std::fs::write(
+            &model_path,
+            "#! dynamic_assertion: #`AG EF a`#\n#! dynamic_property: p1: #`AG EF b`#\n\
+            a -?? b\nb -?? a\n",
+        )
+        .unwrap();
+        classify(
+            model_path.to_str().unwrap(),
+            archive_path.to_str().unwrap(),
+            &OutputOptions::default(),
+        )
+        .unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let mut report = String::new();
+        archive
+            .by_name("report.txt")
+            .unwrap()
+            .read_to_string(&mut report)
+            .unwrap();
+        std::fs::remove_file(model_path).unwrap();
+        std::fs::remove_file(archive_path).unwrap();
+        // Half of the 16 colors satisfy the assertion.
+        assert!(report.contains("8 colors satisfy all assertions (50.0% of all parameterizations)"));
+        assert!(
+            report.contains("3 colors in this category (37.5%; 18.8% of all parameterizations)")
+        );
+    }
+
+    #[test]
+    /// Test that the archive of unsatisfiable assertions can be loaded and merged as any other
+    /// archive, and that it is written according to the options.
+    fn test_classify_unsatisfiable_assertions() {
+        let dir = std::env::temp_dir();
+        let model_path = dir.join("bn-classifier-test-unsatisfiable.aeon");
+        let archive_path = dir.join("bn-classifier-test-unsatisfiable.zip");
+        let merged_path = dir.join("bn-classifier-test-unsatisfiable-merged.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        std::fs::write(
+            &model_path,
+            "#! dynamic_assertion: #`AG a`#\n#! dynamic_property: p1: #`AG EF b`#\n\
+            a -?? b\nb -?? a\n",
+        )
+        .unwrap();
+        let options = OutputOptions {
+            checksums: true,
+            ..OutputOptions::default()
+        };
+        classify(model_path.to_str().unwrap(), archive_name, &options).unwrap();
+
+        let summary = read_archive_summary(archive_name).unwrap();
+        assert_eq!(summary.properties.len(), 1);
+        let (classes, _) = load_classification_archive(archive_name.to_string()).unwrap();
+        assert!(classes.is_empty());
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        assert!(archive.by_name("checksums.txt").is_ok());
+        let mut report = String::new();
+        archive
+            .by_name("report.txt")
+            .unwrap()
+            .read_to_string(&mut report)
+            .unwrap();
+        assert!(report.contains("Assertion 1 is satisfied by 0 colors"));
+        merge_archives(&[archive_name, archive_name], merged_path.to_str().unwrap()).unwrap();
+
+        std::fs::remove_file(model_path).unwrap();
+        std::fs::remove_file(archive_path).unwrap();
+        std::fs::remove_file(merged_path).unwrap();
+    }
+
+    #[test]
+    /// Test that a model without properties is classified into one class of all valid colors.
+    fn test_classify_without_properties() {
+        let dir = std::env::temp_dir();
+        let model_path = dir.join("bn-classifier-test-no-properties.aeon");
+        let archive_path = dir.join("bn-classifier-test-no-properties.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        std::fs::write(
+            &model_path,
+            "#! dynamic_assertion: #`AG EF a`#\na -?? b\nb -?? a\n",
+        )
+        .unwrap();
+        classify(
+            model_path.to_str().unwrap(),
+            archive_name,
+            &OutputOptions::default(),
+        )
+        .unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let mut dumps: Vec<&str> = archive
+            .file_names()
+            .filter(|it| it.starts_with("bdd_dump_"))
+            .collect();
+        dumps.sort();
+        assert_eq!(dumps, vec!["bdd_dump_all.txt"]);
+        let mut report = String::new();
+        archive
+            .by_name("report.txt")
+            .unwrap()
+            .read_to_string(&mut report)
+            .unwrap();
+        assert!(report.contains("No properties, all valid colors form a single class."));
+        assert!(report.contains("# all colors satisfying assertions\n"));
+
+        let bn = BooleanNetwork::try_from("a -?? b\nb -?? a\n").unwrap();
+        let graph = SymbolicAsyncGraph::new(bn).unwrap();
+        let loaded = read_classification_archive(archive_name, &graph).unwrap();
+        std::fs::remove_file(model_path).unwrap();
+        std::fs::remove_file(&archive_path).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded[0].0.is_empty());
+        assert!(!loaded[0].1.is_empty());
+    }
+
+    #[test]
+    /// Test that one classifier can be shared by several threads.
+    fn test_shared_classifier() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let classifier = Arc::new(Classifier::new(OutputOptions::default()));
+
+        let archives: Vec<Vec<u8>> = thread::scope(|scope| {
+            let handles: Vec<_> = sets
+                .chunks(1)
+                .map(|property_results| {
+                    let classifier = Arc::clone(&classifier);
+                    let properties = &properties;
+                    let graph = &graph;
+                    scope.spawn(move || {
+                        classifier
+                            .classify_to_writer(
+                                &[],
+                                graph.unit_colors(),
+                                properties,
+                                property_results,
+                                "a -?? b\nb -?? a\n",
+                                Cursor::new(Vec::new()),
+                            )
+                            .unwrap()
+                            .into_inner()
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|it| it.join().unwrap()).collect()
+        });
+
+        assert_eq!(archives.len(), sets.len());
+        for archive in archives {
+            let archive = ZipArchive::new(Cursor::new(archive)).unwrap();
+            assert!(archive.file_names().any(|name| name == "bdd_dump_1.txt"));
+        }
+    }
+
+    #[test]
+    /// Test that the checksums of the BDD dumps detect a modified dump.
+    fn test_verify_archive() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-checksums.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        ClassificationWriter::new("")
+            .write(&result, archive_name)
+            .unwrap();
+        assert!(verify_archive(archive_name).is_err());
+
+        ClassificationWriter::new("")
+            .checksums(true)
+            .write(&result, archive_name)
+            .unwrap();
+        assert_eq!(verify_archive(archive_name), Ok(()));
+
+        // Copy the archive, but replace the dump of class `1` with the dump of class `0`.
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let mut entries = Vec::new();
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).unwrap();
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents).unwrap();
+            entries.push((entry.name().to_string(), contents));
+        }
+        let dump_0 = entries.iter().find(|(name, _)| name == "bdd_dump_0.txt");
+        let dump_0 = dump_0.unwrap().1.clone();
+        let mut writer = ZipWriter::new(File::create(&archive_path).unwrap());
+        for (name, contents) in entries {
+            writer
+                .start_file(name.as_str(), FileOptions::default())
+                .unwrap();
+            let contents = if name == "bdd_dump_1.txt" {
+                &dump_0
+            } else {
+                &contents
+            };
+            write!(writer, "{contents}").unwrap();
+        }
+        writer.finish().unwrap();
+
+        assert_eq!(
+            verify_archive(archive_name),
+            Err("Checksum mismatch in `bdd_dump_1.txt`.".to_string())
+        );
+        std::fs::remove_file(archive_path).unwrap();
+    }
+
+    #[test]
+    /// Test that the archives of two shards of the color space are merged into the archive
+    /// of the whole space.
+    fn test_merge_archives() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let model = "a -?? b\nb -?? a\n";
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let shards = [sets[3].clone(), graph.unit_colors().minus(&sets[3])];
+        let temp_dir = std::env::temp_dir();
+        let mut paths = Vec::new();
+        for (i, shard) in shards.iter().enumerate() {
+            let property_results = vec![sets[0].intersect(shard), sets[1].intersect(shard)];
+            let result =
+                build_classification_result(&[], shard, &properties, &property_results).unwrap();
+            let path = temp_dir.join(format!("bn-classifier-test-shard-{i}.zip"));
+            ClassificationWriter::new(model)
+                .write(&result, path.to_str().unwrap())
+                .unwrap();
+            paths.push(path.to_str().unwrap().to_string());
+        }
+        let paths: Vec<&str> = paths.iter().map(|it| it.as_str()).collect();
+
+        let merged_path = temp_dir.join("bn-classifier-test-merged.zip");
+        let merged_name = merged_path.to_str().unwrap();
+        merge_archives(&paths, merged_name).unwrap();
+        let merged = read_classification_archive(merged_name, &graph).unwrap();
+        let expected =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..2])
+                .unwrap();
+        assert_eq!(merged.len(), expected.categories.len());
+        for ((validity, colors), category) in merged.iter().zip(&expected.categories) {
+            assert_eq!(validity, &category.validity);
+            assert!(colors_equal(colors, &category.colors));
+        }
+
+        // A shard with the properties in a different order cannot be merged.
+        let swapped: Vec<(String, String)> = properties.iter().rev().cloned().collect();
+        let result = build_classification_result(&[], &shards[0], &swapped, &sets[0..2]).unwrap();
+        ClassificationWriter::new(model)
+            .write(&result, paths[1])
+            .unwrap();
+        assert!(merge_archives(&paths, merged_name).is_err());
+
+        for path in paths {
+            std::fs::remove_file(path).unwrap();
+        }
+        std::fs::remove_file(merged_path).unwrap();
+    }
 }
@@ -0,0 +1,122 @@
+//! Errors that can occur while computing and generating the classification results.
+
+use std::fmt::{Display, Formatter};
+use zip::result::ZipError;
+
+/// Error produced while computing or writing the classification results.
+#[derive(Debug)]
+pub enum ClassifierError {
+    /// Failure of the underlying I/O (e.g., the disk is full).
+    Io(std::io::Error),
+    /// Failure while creating or finalizing the zip archive.
+    Zip(ZipError),
+    /// The model included in the output is not a valid Boolean network.
+    Model(String),
+    /// The number of property names does not match the number of property results.
+    PropertyCountMismatch { names: usize, results: usize },
+    /// The categories do not form an exact partition of the valid colors.
+    PartitionInvariantViolated(String),
+    /// The properties selected for the decomposition are not valid property indices.
+    InvalidPropertySelection(String),
+    /// The output file already exists (and overwriting it is not allowed).
+    OutputExists(String),
+    /// Several classes have the same (custom) label.
+    DuplicateClassLabel(String),
+    /// The custom name of a class is empty, or it is not a plain file name (i.e., it contains
+    /// `/`, `\\` or `..`), so it cannot name the BDD dump of the class.
+    InvalidClassLabel(String),
+    /// The label of a universe is not a plain directory name (see
+    /// [ClassifierError::InvalidClassLabel], `.` is not allowed either), or it is used by
+    /// several universes.
+    InvalidUniverseLabel(String),
+    /// The writer does not support the given output option (e.g., `split_bdds`).
+    UnsupportedOption(String),
+    /// The BDD dumps of an archive use a different number of HCTL variables than the symbolic
+    /// graph into which they are loaded.
+    VarCountMismatch { archive: usize, graph: usize },
+    /// The number of HCTL variables declared by a result does not match the number of variables
+    /// of its BDDs in the symbolic context of the model.
+    InconsistentHctlVars { declared: usize, bdd_vars: usize },
+    /// The zip archive at `path` could not be finalized (the incomplete file is removed).
+    Finalize { path: String, error: ZipError },
+}
+
+impl Display for ClassifierError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClassifierError::Io(e) => write!(f, "I/O error: {e}"),
+            ClassifierError::Zip(e) => write!(f, "Zip archive error: {e}"),
+            ClassifierError::Model(e) => write!(f, "Invalid model: {e}"),
+            ClassifierError::PropertyCountMismatch { names, results } => write!(
+                f,
+                "Got {names} property names, but {results} property results."
+            ),
+            ClassifierError::PartitionInvariantViolated(e) => {
+                write!(f, "Categories are not a partition of the valid colors: {e}")
+            }
+            ClassifierError::InvalidPropertySelection(e) => {
+                write!(f, "Invalid selection of properties: {e}")
+            }
+            ClassifierError::OutputExists(path) => {
+                write!(f, "Output `{path}` already exists.")
+            }
+            ClassifierError::DuplicateClassLabel(label) => {
+                write!(f, "Multiple classes are labelled `{label}`.")
+            }
+            ClassifierError::InvalidClassLabel(label) => {
+                write!(f, "Invalid class name `{label}`.")
+            }
+            ClassifierError::InvalidUniverseLabel(label) => {
+                write!(f, "Invalid (or duplicate) universe label `{label}`.")
+            }
+            ClassifierError::UnsupportedOption(option) => {
+                write!(f, "The output option `{option}` is not supported here.")
+            }
+            ClassifierError::VarCountMismatch { archive, graph } => write!(
+                f,
+                "The archive is encoded with {archive} HCTL variables, but the graph has {graph}."
+            ),
+            ClassifierError::InconsistentHctlVars { declared, bdd_vars } => write!(
+                f,
+                "The result declares {declared} HCTL variables, which is inconsistent with the \
+                {bdd_vars} variables of its BDDs in the symbolic context of the model."
+            ),
+            ClassifierError::Finalize { path, error } => {
+                write!(f, "Failed to finalize the archive `{path}`: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClassifierError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ClassifierError::Io(e) => Some(e),
+            ClassifierError::Zip(e) => Some(e),
+            ClassifierError::Model(_) => None,
+            ClassifierError::PropertyCountMismatch { .. } => None,
+            ClassifierError::PartitionInvariantViolated(_) => None,
+            ClassifierError::InvalidPropertySelection(_) => None,
+            ClassifierError::OutputExists(_) => None,
+            ClassifierError::DuplicateClassLabel(_) => None,
+            ClassifierError::InvalidClassLabel(_) => None,
+            ClassifierError::InvalidUniverseLabel(_) => None,
+            ClassifierError::UnsupportedOption(_) => None,
+            ClassifierError::VarCountMismatch { .. } => None,
+            ClassifierError::InconsistentHctlVars { .. } => None,
+            ClassifierError::Finalize { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for ClassifierError {
+    fn from(value: std::io::Error) -> Self {
+        ClassifierError::Io(value)
+    }
+}
+
+impl From<ZipError> for ClassifierError {
+    fn from(value: ZipError) -> Self {
+        ClassifierError::Zip(value)
+    }
+}
@@ -0,0 +1,11 @@
+//! Library of the symbolic classifier of BN models based on dynamic properties.
+//!
+//! Contains the decomposition of colors into categories, the model-checking wrappers, and the
+//! writers and loaders of the classification archives, so that other tools (e.g., the HCTL
+//! explorer) can read the archives the same way as the `bn-classifier` binary writes them.
+
+pub mod categories;
+pub mod classification;
+pub mod errors;
+pub mod load_inputs;
+pub mod write_output;
@@ -1,12 +1,24 @@
 //! Loading of various input components of the model, mainly of various properties/assertions.
 
+use crate::errors::ClassifierError;
+use crate::write_output::{ArchiveLayout, PROPERTY_FILE_VERSION};
 use biodivine_lib_param_bn::{BooleanNetwork, ModelAnnotation};
 
+use biodivine_hctl_model_checker::mc_utils::collect_unique_hctl_vars;
+use biodivine_hctl_model_checker::preprocessing::node::{HctlTreeNode, NodeType};
+use biodivine_hctl_model_checker::preprocessing::operator_enums::Atomic;
+use biodivine_hctl_model_checker::preprocessing::parser::parse_hctl_formula;
 use biodivine_lib_bdd::Bdd;
-use biodivine_lib_param_bn::symbolic_async_graph::{GraphColors, SymbolicAsyncGraph};
-use std::collections::HashMap;
+use biodivine_lib_param_bn::biodivine_std::traits::Set;
+use biodivine_lib_param_bn::symbolic_async_graph::{
+    GraphColors, SymbolicAsyncGraph, SymbolicContext,
+};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeSet, HashMap};
+use std::ffi::OsStr;
 use std::fs::File;
-use std::io::Read;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
 use zip::ZipArchive;
 
 /// Read the list of assertions from an `.aeon` model annotation object.
@@ -52,6 +64,89 @@ pub fn read_model_properties(
     Ok(properties)
 }
 
+/// Assertion formulae and named property formulae of an annotated model.
+pub type AnnotatedFormulae = (Vec<String>, Vec<(String, String)>);
+
+/// Read the HCTL formulae from the annotations of an annotated `.aeon` model string.
+///
+/// Returns the `#!dynamic_assertion` formulae (see [read_model_assertions]) and the named
+/// `#!dynamic_property` formulae (see [read_model_properties]). Other annotations (such as
+/// the `#!static_property` constraints, which are not HCTL formulae) are ignored.
+pub fn read_annotated_formulae(aeon_str: &str) -> Result<AnnotatedFormulae, String> {
+    let annotations = ModelAnnotation::from_model_string(aeon_str);
+    let assertions = read_model_assertions(&annotations);
+    let named_properties = read_model_properties(&annotations)?;
+    Ok((assertions, named_properties))
+}
+
+/// Read the formulae of a property file written into the classification archives (see
+/// [crate::write_output::PROPERTY_FILE_VERSION] for the format).
+///
+/// Unlike [read_annotated_formulae], the properties keep the order of the file (i.e., the order
+/// of the bits of the classes). Lines that are not annotations are ignored, but the version
+/// of the format must be the supported one.
+pub fn read_property_file(hctl_str: &str) -> Result<AnnotatedFormulae, String> {
+    let mut lines = hctl_str.lines().map(|line| line.trim());
+    let version = lines
+        .next()
+        .and_then(|line| line.strip_prefix("#!"))
+        .and_then(|line| line.trim().strip_prefix("property_file_version:"))
+        .map(|version| version.trim());
+    if version != Some(PROPERTY_FILE_VERSION.to_string().as_str()) {
+        return Err(format!(
+            "Unsupported property file version (expected {PROPERTY_FILE_VERSION})."
+        ));
+    }
+    // The formulae are (optionally) escaped as #`formula`#.
+    let unescape = |formula: &str| {
+        let formula = formula.trim();
+        let escaped = formula
+            .strip_prefix("#`")
+            .and_then(|it| it.strip_suffix("`#"));
+        escaped.unwrap_or(formula).to_string()
+    };
+
+    let mut assertions = Vec::new();
+    let mut named_properties = Vec::new();
+    for line in lines {
+        let Some(annotation) = line.strip_prefix("#!").map(|it| it.trim()) else {
+            continue;
+        };
+        if let Some(formula) = annotation.strip_prefix("dynamic_assertion:") {
+            assertions.push(unescape(formula));
+        } else if let Some(property) = annotation.strip_prefix("dynamic_property:") {
+            let Some((name, formula)) = property.split_once(':') else {
+                return Err(format!("Malformed property `{line}`."));
+            };
+            named_properties.push((name.trim().to_string(), unescape(formula)));
+        }
+    }
+    Ok((assertions, named_properties))
+}
+
+/// Read the formulae of the property file of the `archive_path` zip produced by the classifier
+/// (see [read_property_file]).
+pub fn read_archive_properties(archive_path: &str) -> Result<AnnotatedFormulae, String> {
+    read_archive_properties_with_layout(archive_path, &ArchiveLayout::default())
+}
+
+/// Same as [read_archive_properties], but the entries of the archive are named by the `layout`
+/// (see [crate::write_output::OutputOptions::layout]).
+pub fn read_archive_properties_with_layout(
+    archive_path: &str,
+    layout: &ArchiveLayout,
+) -> Result<AnnotatedFormulae, String> {
+    let archive_file = File::open(archive_path).map_err(|e| format!("{e:?}"))?;
+    let mut archive = ZipArchive::new(archive_file).map_err(|e| format!("{e:?}"))?;
+    let properties = layout.entry(&layout.properties);
+    if archive.by_name(&properties).is_err() {
+        return Err(format!(
+            "Archive `{archive_path}` does not contain `{properties}`."
+        ));
+    }
+    read_property_file(&read_zip_file(&mut archive, &properties)?)
+}
+
 /// Combine all HCTL assertions in the given list into a single conjunction of assertions.
 pub fn build_combined_assertion(assertions: &[String]) -> String {
     if assertions.is_empty() {
@@ -64,12 +159,179 @@ pub fn build_combined_assertion(assertions: &[String]) -> String {
     }
 }
 
-/// Read the contents of a file from a zip archive into a string.
-fn read_zip_file(reader: &mut ZipArchive<File>, file_name: &str) -> String {
+/// Read the contents of a file from a zip archive into a string. Fails if there is no such
+/// file, or if it cannot be read (e.g., it is truncated or it is not valid UTF-8).
+fn read_zip_file(reader: &mut ZipArchive<File>, file_name: &str) -> Result<String, String> {
     let mut contents = String::new();
-    let mut file = reader.by_name(file_name).unwrap();
-    file.read_to_string(&mut contents).unwrap();
-    contents
+    let mut file = reader
+        .by_name(file_name)
+        .map_err(|e| format!("Cannot open `{file_name}`: {e:?}"))?;
+    file.read_to_string(&mut contents)
+        .map_err(|e| format!("Cannot read `{file_name}`: {e:?}"))?;
+    Ok(contents)
+}
+
+/// Variables that appear in an HCTL formula.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FormulaVariables {
+    /// Network variables used as atomic propositions (sorted by name).
+    pub network_variables: Vec<String>,
+    /// HCTL state variables introduced by the quantifiers and binders (sorted by name).
+    pub hctl_variables: Vec<String>,
+}
+
+/// Collect the [FormulaVariables] of the HCTL `formula`. The variables keep the names given in
+/// the formula (i.e., the HCTL variables are not minimized as for the model checking).
+pub fn read_formula_variables(formula: &str) -> Result<FormulaVariables, String> {
+    let tree = parse_hctl_formula(formula)?;
+    let mut network_variables = BTreeSet::new();
+    collect_propositions(&tree, &mut network_variables);
+    let hctl_variables: BTreeSet<String> = collect_unique_hctl_vars(tree).into_iter().collect();
+    Ok(FormulaVariables {
+        network_variables: network_variables.into_iter().collect(),
+        hctl_variables: hctl_variables.into_iter().collect(),
+    })
+}
+
+/// Add the names of all atomic propositions of the formula `tree` to `propositions`.
+fn collect_propositions(tree: &HctlTreeNode, propositions: &mut BTreeSet<String>) {
+    match &tree.node_type {
+        NodeType::TerminalNode(Atomic::Prop(name)) => {
+            propositions.insert(name.clone());
+        }
+        NodeType::TerminalNode(_) => {}
+        NodeType::UnaryNode(_, child) | NodeType::HybridNode(_, _, child) => {
+            collect_propositions(child, propositions);
+        }
+        NodeType::BinaryNode(_, left, right) => {
+            collect_propositions(left, propositions);
+            collect_propositions(right, propositions);
+        }
+    }
+}
+
+/// Read the mapping of category names to the files with their BDD dumps, together with the flag
+/// whether the dump is relative to the valid colors (see [read_category_bdd]).
+///
+/// The mapping is given by the `bdd_index.txt` entry (several categories with the same colors can
+/// share one dump). For archives without the index, it is derived from the names of the
+/// `bdd_dump_<name>.txt` (or binary `bdd_dump_<name>.bdd`) files. All entries are named by the
+/// `layout`.
+fn read_bdd_index(
+    archive: &mut ZipArchive<File>,
+    layout: &ArchiveLayout,
+) -> Result<Vec<(String, String, bool)>, String> {
+    let bdd_index = layout.entry(&layout.bdd_index);
+    if archive.by_name(&bdd_index).is_err() {
+        let index = archive
+            .file_names()
+            .filter_map(|file| {
+                // Only read BDD dumps (and skip their optional `.dot` versions).
+                let name = layout.dumped_category(file)?;
+                Some((name.to_string(), file.to_string(), false))
+            })
+            .collect();
+        return Ok(index);
+    }
+
+    let index_str = read_zip_file(archive, &bdd_index)?;
+    index_str
+        .lines()
+        .map(|line| {
+            let columns: Vec<&str> = line.split('\t').collect();
+            match columns[..] {
+                [name, file] => Ok((name.to_string(), file.to_string(), false)),
+                [name, file, "relative"] => Ok((name.to_string(), file.to_string(), true)),
+                _ => Err(format!("Malformed BDD index entry `{line}`.")),
+            }
+        })
+        .collect()
+}
+
+/// Read the BDD dump from the `file` entry of the archive. Dumps with the `.bdd` extension are in
+/// the binary format of [Bdd::write_as_bytes], all other dumps are in the text format.
+///
+/// Dumps with an extra `.zst` extension are compressed by zstd with the dictionary stored in the
+/// `bdd.dict` entry of the `layout` (see [crate::write_output::OutputOptions::bdd_dictionary]).
+fn read_bdd_dump(
+    archive: &mut ZipArchive<File>,
+    layout: &ArchiveLayout,
+    file: &str,
+) -> Result<Bdd, String> {
+    let Some(format_name) = file.strip_suffix(".zst") else {
+        let mut bdd_file = archive.by_name(file).map_err(|e| format!("{e:?}"))?;
+        return read_bdd(&mut bdd_file, file);
+    };
+    let mut dictionary = Vec::new();
+    archive
+        .by_name(&layout.entry(&layout.bdd_dictionary))
+        .and_then(|mut entry| Ok(entry.read_to_end(&mut dictionary)?))
+        .map_err(|e| format!("Cannot read the dictionary of `{file}`: {e:?}"))?;
+    let bdd_file = archive.by_name(file).map_err(|e| format!("{e:?}"))?;
+    let mut decoder =
+        zstd::stream::read::Decoder::with_dictionary(BufReader::new(bdd_file), &dictionary)
+            .map_err(|e| format!("{e:?}"))?;
+    read_bdd(&mut decoder, format_name)
+}
+
+/// Read a BDD from the `reader` in the format given by the extension of the `file` name (see
+/// [read_bdd_dump]).
+fn read_bdd<R: Read>(reader: &mut R, file: &str) -> Result<Bdd, String> {
+    if file.ends_with(".bdd") {
+        Bdd::read_as_bytes(reader).map_err(|e| format!("{e:?}"))
+    } else {
+        Bdd::read_as_string(reader)
+    }
+}
+
+/// Parse a color set of the `graph` from the string representation of its BDD (as given by
+/// [Bdd::to_string], e.g., by a model checker running in another process).
+///
+/// The BDD must be encoded in the symbolic context of the `graph` (including the same number
+/// of HCTL variables), otherwise an error is returned.
+pub fn parse_colors(bdd_str: &str, graph: &SymbolicAsyncGraph) -> Result<GraphColors, String> {
+    let bdd = Bdd::read_as_string(&mut bdd_str.as_bytes())?;
+    if bdd.size() == 0 {
+        return Err("BDD string is empty.".to_string());
+    }
+    let context = graph.symbolic_context();
+    if bdd.num_vars() != context.bdd_variable_set().num_vars() {
+        return Err(format!(
+            "BDD has {} variables, but the symbolic context has {}.",
+            bdd.num_vars(),
+            context.bdd_variable_set().num_vars(),
+        ));
+    }
+    Ok(GraphColors::new(bdd, context))
+}
+
+/// Read the BDD of a category from its `file` dump. A `relative` dump contains the valid colors
+/// outside of the category, so it is complemented w.r.t. the valid colors (given by the
+/// `valid_colors.txt` entry of the `layout`, which is read into `valid_colors` on first use).
+fn read_category_bdd(
+    archive: &mut ZipArchive<File>,
+    layout: &ArchiveLayout,
+    file: &str,
+    relative: bool,
+    valid_colors: &mut Option<Bdd>,
+) -> Result<Bdd, String> {
+    let bdd = read_bdd_dump(archive, layout, file)?;
+    if !relative {
+        return Ok(bdd);
+    }
+    let valid_colors = match valid_colors {
+        Some(valid_colors) => valid_colors,
+        None => {
+            let valid_colors_file = layout.entry(&layout.valid_colors);
+            if archive.by_name(&valid_colors_file).is_err() {
+                return Err(format!(
+                    "Archive with relative BDD dumps has no `{valid_colors_file}`."
+                ));
+            }
+            valid_colors.insert(read_bdd_dump(archive, layout, &valid_colors_file)?)
+        }
+    };
+    Ok(valid_colors.and_not(&bdd))
 }
 
 /// Load the archive containing results produced by the classifier.
@@ -77,46 +339,579 @@ fn read_zip_file(reader: &mut ZipArchive<File>, file_name: &str) -> String {
 /// those produced by the `build_classification_archive` function).
 ///
 /// Return mapping `category name -> color set` and whole model string in aeon format.
-/// Category names are simply taken from BDD-file names in the archive.
+/// Category names are taken from the BDD index (or BDD-file names) in the archive. The names
+/// that are class bits are always returned with the most significant bit first, i.e., the
+/// labels of archives written with `bit_order=lsb_first` are reversed back.
+///
+/// For the report archive of split results (see
+/// [crate::write_output::OutputOptions::split_bdds]), the BDD dumps are loaded from its
+/// companion archive (which must be placed next to it).
 pub fn load_classification_archive(
     archive_path: String,
+) -> Result<(HashMap<String, GraphColors>, String), String> {
+    load_classification_archive_with_layout(&archive_path, &ArchiveLayout::default())
+}
+
+/// Same as [load_classification_archive], but the entries of the archive are named by the
+/// `layout` (see [crate::write_output::OutputOptions::layout]).
+pub fn load_classification_archive_with_layout(
+    archive_path: &str,
+    layout: &ArchiveLayout,
 ) -> Result<(HashMap<String, GraphColors>, String), String> {
     // Open the zip archive with classification results.
     let archive_file = File::open(archive_path).map_err(|e| format!("{e:?}"))?;
     let mut archive = ZipArchive::new(archive_file).map_err(|e| format!("{e:?}"))?;
 
     // Load the BN model (from the archive) and generate the extended STG.
-    let aeon_str = read_zip_file(&mut archive, "model.aeon");
+    let aeon_str = read_zip_file(&mut archive, &layout.entry(&layout.model))?;
     let bn = BooleanNetwork::try_from(aeon_str.as_str())?;
     let graph = SymbolicAsyncGraph::new(bn)?;
+    // The metadata are only stored in the report archive (not in its companion).
+    let lsb_first = is_lsb_first(&mut archive, layout);
+
+    if let Some(bdds_path) = read_companion_bdds_path(&mut archive, layout, archive_path)? {
+        let archive_file = File::open(&bdds_path).map_err(|e| format!("{e:?}"))?;
+        archive = ZipArchive::new(archive_file).map_err(|e| format!("{e:?}"))?;
+    }
 
     // collect the classification outcomes (colored sets) from the individual BDD dumps
     let mut categories = HashMap::new();
 
     // Load all class BDDs from files in the archive.
-    let files = archive
-        .file_names()
-        .map(|it| it.to_string())
-        .collect::<Vec<_>>();
-
-    for file in files {
-        if !file.starts_with("bdd_dump_") {
-            // Only read BDD dumps.
+    let mut valid_colors = None;
+    for (category_id, file, relative) in read_bdd_index(&mut archive, layout)? {
+        let bdd = read_category_bdd(
+            &mut archive,
+            layout,
+            file.as_str(),
+            relative,
+            &mut valid_colors,
+        )?;
+        let color_set = GraphColors::new(bdd, graph.symbolic_context());
+        let category_id = if lsb_first && string_to_bool_vec(&category_id).is_some() {
+            category_id.chars().rev().collect()
+        } else {
+            category_id
+        };
+
+        // The insert should create a new item, otherwise the archive is malformed.
+        assert!(categories.insert(category_id, color_set).is_none());
+    }
+
+    Ok((categories, aeon_str))
+}
+
+/// If the archive at `archive_path` is the report archive of split results (i.e., it has
+/// a `companion.txt` entry, but no BDD index), return the path of its companion archive with
+/// the BDD dumps (in the same directory). The entries are named by the `layout`.
+fn read_companion_bdds_path(
+    archive: &mut ZipArchive<File>,
+    layout: &ArchiveLayout,
+    archive_path: &str,
+) -> Result<Option<PathBuf>, String> {
+    let companion = layout.entry(&layout.companion);
+    if archive.by_name(&layout.entry(&layout.bdd_index)).is_ok()
+        || archive.by_name(&companion).is_err()
+    {
+        return Ok(None);
+    }
+    let companion = read_zip_file(archive, &companion)?;
+    let Some(bdds_name) = companion
+        .lines()
+        .find_map(|line| line.strip_prefix("bdds="))
+    else {
+        return Err("Malformed `companion.txt` without the BDD archive.".to_string());
+    };
+    if Path::new(archive_path).file_name() == Some(OsStr::new(bdds_name)) {
+        return Ok(None);
+    }
+    Ok(Some(Path::new(archive_path).with_file_name(bdds_name)))
+}
+
+/// Parse a validity vector from its binary string (e.g., the `010` part of `bdd_dump_010.txt`).
+fn string_to_bool_vec(bits: &str) -> Option<Vec<bool>> {
+    bits.chars()
+        .map(|c| match c {
+            '0' => Some(false),
+            '1' => Some(true),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Information about a classification archive (besides its categories), as given by its
+/// `model.aeon`, `metadata.txt` and `report.json` entries.
+pub struct ArchiveSummary {
+    /// The model included in the archive.
+    pub model: String,
+    /// The assertion formulae of the classification.
+    pub assertion_formulae: Vec<String>,
+    /// Names and formulae of the classification properties, in the order of the bits of the
+    /// validity vectors.
+    pub properties: Vec<(String, String)>,
+    /// Maximal number of HCTL variables across all formulae.
+    pub num_hctl_vars: usize,
+    /// Number of HCTL variables of each formula (given as `(name, count)` pairs).
+    pub formula_hctl_vars: Vec<(String, usize)>,
+    /// Fingerprint of the input of the classification, if it is recorded.
+    pub source_fingerprint: Option<String>,
+}
+
+/// Read the [ArchiveSummary] of the `archive_path` zip produced by the classifier.
+pub fn read_archive_summary(archive_path: &str) -> Result<ArchiveSummary, String> {
+    read_archive_summary_with_layout(archive_path, &ArchiveLayout::default())
+}
+
+/// Same as [read_archive_summary], but the entries of the archive are named by the `layout`
+/// (see [crate::write_output::OutputOptions::layout]).
+pub fn read_archive_summary_with_layout(
+    archive_path: &str,
+    layout: &ArchiveLayout,
+) -> Result<ArchiveSummary, String> {
+    let archive_file = File::open(archive_path).map_err(|e| format!("{e:?}"))?;
+    let mut archive = ZipArchive::new(archive_file).map_err(|e| format!("{e:?}"))?;
+    let model = read_zip_file(&mut archive, &layout.entry(&layout.model))?;
+
+    let metadata = read_zip_file(&mut archive, &layout.entry(&layout.metadata))?;
+    let mut lines = metadata.lines();
+    let num_hctl_vars = lines
+        .next()
+        .and_then(|line| line.parse::<usize>().ok())
+        .ok_or_else(|| format!("Malformed metadata in `{archive_path}`."))?;
+    let mut formula_hctl_vars = Vec::new();
+    let mut source_fingerprint = None;
+    for line in lines {
+        if let Some(fingerprint) = line.strip_prefix("source=") {
+            source_fingerprint = Some(fingerprint.to_string());
             continue;
         }
+        let Some((name, count)) = line
+            .strip_prefix("hctl_vars.")
+            .and_then(|it| it.rsplit_once('='))
+        else {
+            continue;
+        };
+        let count = count
+            .parse::<usize>()
+            .map_err(|_| format!("Malformed metadata entry `{line}`."))?;
+        formula_hctl_vars.push((name.to_string(), count));
+    }
 
-        let bdd_string = read_zip_file(&mut archive, file.as_str());
-        let bdd = Bdd::from_string(bdd_string.as_str());
-        let color_set = GraphColors::new(bdd, graph.symbolic_context());
+    let report_str = read_zip_file(&mut archive, &layout.entry(&layout.report_json))?;
+    let report: serde_json::Value =
+        serde_json::from_str(&report_str).map_err(|e| format!("{e:?}"))?;
+    let malformed = || format!("Malformed `{}` in `{archive_path}`.", layout.report_json);
+    let assertion_formulae = report["assertion_formulae"]
+        .as_array()
+        .ok_or_else(malformed)?
+        .iter()
+        .map(|it| it.as_str().map(|it| it.to_string()).ok_or_else(malformed))
+        .collect::<Result<Vec<String>, String>>()?;
+    let properties = report["properties"]
+        .as_array()
+        .ok_or_else(malformed)?
+        .iter()
+        .map(|it| match (it["name"].as_str(), it["formula"].as_str()) {
+            (Some(name), Some(formula)) => Ok((name.to_string(), formula.to_string())),
+            _ => Err(malformed()),
+        })
+        .collect::<Result<Vec<(String, String)>, String>>()?;
+
+    Ok(ArchiveSummary {
+        model,
+        assertion_formulae,
+        properties,
+        num_hctl_vars,
+        formula_hctl_vars,
+        source_fingerprint,
+    })
+}
 
-        let category_id = file.strip_prefix("bdd_dump_").unwrap();
-        let category_id = category_id.strip_suffix(".txt").unwrap();
+/// Check whether the class labels in the archive are written with the least significant bit
+/// first, as recorded by `bit_order=lsb_first` in its `metadata.txt` (named by the `layout`).
+fn is_lsb_first(archive: &mut ZipArchive<File>, layout: &ArchiveLayout) -> bool {
+    let metadata = layout.entry(&layout.metadata);
+    if archive.by_name(&metadata).is_err() {
+        return false;
+    }
+    let metadata = read_zip_file(archive, &metadata).unwrap_or_default();
+    metadata.lines().any(|line| line == "bit_order=lsb_first")
+}
 
-        // The insert should create a new item, otherwise the archive is malformed.
-        assert!(categories
-            .insert(category_id.to_string(), color_set)
-            .is_none());
+/// Read the number of HCTL variables recorded in the first line of the `metadata.txt` of the
+/// archive (`None` if there is no such metadata), named by the `layout`.
+fn read_stored_hctl_vars(archive: &mut ZipArchive<File>, layout: &ArchiveLayout) -> Option<usize> {
+    let metadata = layout.entry(&layout.metadata);
+    if archive.by_name(&metadata).is_err() {
+        return None;
     }
+    let metadata = read_zip_file(archive, &metadata).ok()?;
+    metadata.lines().next()?.trim().parse().ok()
+}
 
-    Ok((categories, aeon_str))
+/// Number of HCTL variables for which the symbolic `context` has extra state variables
+/// (i.e., the number of extra variables per each network variable).
+pub(crate) fn context_hctl_vars(context: &SymbolicContext) -> usize {
+    if context.num_state_variables() == 0 {
+        0
+    } else {
+        context.num_extra_state_variables() / context.num_state_variables()
+    }
+}
+
+/// Read back the categories dumped by the classifier into the `archive_path` zip.
+///
+/// The colors are reconstructed using the symbolic context of the provided `graph`, which
+/// must be compatible with the context in which the BDDs were dumped (i.e., it must be based
+/// on the same model as the `model.aeon` in the archive).
+///
+/// The dumps are either sanitized (i.e., without any HCTL variables, as written by the
+/// classifier), or they use the number of HCTL variables recorded in the `metadata.txt`. If the
+/// `graph` has a different number of HCTL variables than the dumps, the error is given by
+/// [ClassifierError::VarCountMismatch].
+///
+/// Return the validity vector (parsed from the BDD-file name, w.r.t. the bit order given in the
+/// metadata) and the color set of each category, sorted by the validity vectors.
+pub fn read_classification_archive(
+    archive_path: &str,
+    graph: &SymbolicAsyncGraph,
+) -> Result<Vec<(Vec<bool>, GraphColors)>, String> {
+    read_filtered_classification_archive(archive_path, graph, &ClassFilter::default())
+}
+
+/// Selection of the categories that are read back from an archive (see
+/// [read_filtered_classification_archive]). By default, all categories are selected.
+#[derive(Clone, Debug, Default)]
+pub struct ClassFilter {
+    /// Only select the categories with at least this many colors.
+    pub min_cardinality: f64,
+    /// Only select the categories with at least this percentage (`0` to `100`) of the valid
+    /// colors.
+    pub min_percentage: f64,
+    /// If given, only select the categories with one of these validity vectors.
+    pub validities: Option<Vec<Vec<bool>>>,
+}
+
+/// Same as [read_classification_archive], but only the categories selected by the `filter` are
+/// returned.
+///
+/// The cardinalities of the categories are taken from the `report.json` of the archive, so the
+/// BDD dumps of the other categories are not even parsed. Without the report, the categories
+/// are only filtered by their cardinality once they are loaded, and they cannot be filtered
+/// by the percentage of the valid colors.
+pub fn read_filtered_classification_archive(
+    archive_path: &str,
+    graph: &SymbolicAsyncGraph,
+    filter: &ClassFilter,
+) -> Result<Vec<(Vec<bool>, GraphColors)>, String> {
+    read_filtered_classification_archive_with_layout(
+        archive_path,
+        graph,
+        filter,
+        &ArchiveLayout::default(),
+    )
+}
+
+/// Same as [read_filtered_classification_archive], but the entries of the archive are named by
+/// the `layout` (see [crate::write_output::OutputOptions::layout]).
+pub fn read_filtered_classification_archive_with_layout(
+    archive_path: &str,
+    graph: &SymbolicAsyncGraph,
+    filter: &ClassFilter,
+    layout: &ArchiveLayout,
+) -> Result<Vec<(Vec<bool>, GraphColors)>, String> {
+    let cardinalities = read_class_cardinalities(archive_path, layout)?;
+    let min_cardinality = match &cardinalities {
+        Some((valid_cardinality, _)) => filter
+            .min_cardinality
+            .max(valid_cardinality * filter.min_percentage / 100.0),
+        None if filter.min_percentage > 0.0 => {
+            return Err(format!(
+                "Archive `{archive_path}` has no `{}` with the valid colors.",
+                layout.report_json
+            ));
+        }
+        None => filter.min_cardinality,
+    };
+
+    let selected = |validity: &[bool]| {
+        let selected_validity = match &filter.validities {
+            Some(validities) => validities.iter().any(|it| it == validity),
+            None => true,
+        };
+        // The classes which are not listed in the report are empty.
+        let large_enough = match &cardinalities {
+            Some((_, classes)) => classes.get(validity).copied().unwrap_or(0.0) >= min_cardinality,
+            None => true,
+        };
+        selected_validity && large_enough
+    };
+    let mut categories = Vec::new();
+    for category in
+        stream_classification_archive_with_layout(archive_path, graph, selected, layout)?
+    {
+        let (validity, colors) = category?;
+        if colors.approx_cardinality() >= min_cardinality {
+            categories.push((validity, colors));
+        }
+    }
+    categories.sort_by(|(x, _), (y, _)| x.cmp(y));
+    Ok(categories)
+}
+
+/// Number of valid colors, and the number of colors of each (non-empty) category by its
+/// validity vector.
+type ClassCardinalities = (f64, HashMap<Vec<bool>, f64>);
+
+/// Read the [ClassCardinalities] from the `report.json` (named by the `layout`) of the
+/// `archive_path` zip (`None` if the archive has no such report).
+fn read_class_cardinalities(
+    archive_path: &str,
+    layout: &ArchiveLayout,
+) -> Result<Option<ClassCardinalities>, String> {
+    let archive_file = File::open(archive_path).map_err(|e| format!("{e:?}"))?;
+    let mut archive = ZipArchive::new(archive_file).map_err(|e| format!("{e:?}"))?;
+    let report_json = layout.entry(&layout.report_json);
+    if archive.by_name(&report_json).is_err() {
+        return Ok(None);
+    }
+    let lsb_first = is_lsb_first(&mut archive, layout);
+    let report_str = read_zip_file(&mut archive, &report_json)?;
+    let report: serde_json::Value =
+        serde_json::from_str(&report_str).map_err(|e| format!("{e:?}"))?;
+    let malformed = || format!("Malformed `{}` in `{archive_path}`.", layout.report_json);
+    let valid_cardinality = report["valid_cardinality"].as_f64().ok_or_else(malformed)?;
+    let mut classes = HashMap::new();
+    for class in report["classes"].as_array().ok_or_else(malformed)? {
+        let bits = class["class_bits"].as_str().ok_or_else(malformed)?;
+        let mut validity = string_to_bool_vec(bits).ok_or_else(malformed)?;
+        if lsb_first {
+            validity.reverse();
+        }
+        let cardinality = class["cardinality"].as_f64().ok_or_else(malformed)?;
+        classes.insert(validity, cardinality);
+    }
+    Ok(Some((valid_cardinality, classes)))
+}
+
+/// Lazy iterator over the categories of a classification archive, see
+/// [stream_classification_archive].
+pub struct ArchiveClassIter<'a, F> {
+    archive: ZipArchive<File>,
+    graph: &'a SymbolicAsyncGraph,
+    layout: ArchiveLayout,
+    index: std::vec::IntoIter<(String, String, bool)>,
+    filter: F,
+    lsb_first: bool,
+    stored_hctl_vars: Option<usize>,
+    valid_colors: Option<Bdd>,
+}
+
+impl<F: FnMut(&[bool]) -> bool> ArchiveClassIter<'_, F> {
+    /// Read the category from the `file` dump, and check that it fits the symbolic context of
+    /// the graph (see [read_classification_archive]).
+    fn read_category(&mut self, file: &str, relative: bool) -> Result<GraphColors, String> {
+        let context = self.graph.symbolic_context();
+        let graph_hctl_vars = context_hctl_vars(context);
+        let canonical_num_vars = context.num_state_variables() + context.num_parameter_variables();
+
+        let bdd = read_category_bdd(
+            &mut self.archive,
+            &self.layout,
+            file,
+            relative,
+            &mut self.valid_colors,
+        )?;
+        let num_vars = usize::from(bdd.num_vars());
+        let dump_hctl_vars = if num_vars == canonical_num_vars {
+            Some(0)
+        } else {
+            self.stored_hctl_vars.filter(|vars| {
+                num_vars == canonical_num_vars + vars * context.num_state_variables()
+            })
+        };
+        if let Some(dump_hctl_vars) = dump_hctl_vars.filter(|vars| *vars != graph_hctl_vars) {
+            return Err(ClassifierError::VarCountMismatch {
+                archive: dump_hctl_vars,
+                graph: graph_hctl_vars,
+            }
+            .to_string());
+        }
+        if bdd.num_vars() != context.bdd_variable_set().num_vars() {
+            return Err(format!(
+                "BDD in `{file}` has {} variables, but the symbolic context has {}.",
+                bdd.num_vars(),
+                context.bdd_variable_set().num_vars(),
+            ));
+        }
+        Ok(GraphColors::new(bdd, context))
+    }
+}
+
+impl<F: FnMut(&[bool]) -> bool> Iterator for ArchiveClassIter<'_, F> {
+    type Item = Result<(Vec<bool>, GraphColors), String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (bits, file, relative) in self.index.by_ref() {
+            let Some(mut validity) = string_to_bool_vec(&bits) else {
+                return Some(Err(format!("Malformed category name `{bits}`.")));
+            };
+            if self.lsb_first {
+                validity.reverse();
+            }
+            // The skipped categories are never decompressed.
+            if !(self.filter)(&validity) {
+                continue;
+            }
+            return Some(
+                self.read_category(file.as_str(), relative)
+                    .map(|colors| (validity, colors)),
+            );
+        }
+        None
+    }
+}
+
+/// Lazily read back the categories dumped by the classifier into the `archive_path` zip, in the
+/// order of the BDD index of the archive.
+///
+/// Same as [read_classification_archive], but only one BDD dump is decompressed and parsed
+/// at a time, once the previous category has been consumed, so the memory is bounded by a single
+/// category (plus the valid colors for the relative dumps). Only the categories whose validity
+/// vector satisfies the `filter` are read, the dumps of the other ones are skipped.
+pub fn stream_classification_archive<'a, F: FnMut(&[bool]) -> bool>(
+    archive_path: &str,
+    graph: &'a SymbolicAsyncGraph,
+    filter: F,
+) -> Result<ArchiveClassIter<'a, F>, String> {
+    stream_classification_archive_with_layout(
+        archive_path,
+        graph,
+        filter,
+        &ArchiveLayout::default(),
+    )
+}
+
+/// Same as [stream_classification_archive], but the entries of the archive are named by the
+/// `layout` (see [crate::write_output::OutputOptions::layout]).
+pub fn stream_classification_archive_with_layout<'a, F: FnMut(&[bool]) -> bool>(
+    archive_path: &str,
+    graph: &'a SymbolicAsyncGraph,
+    filter: F,
+    layout: &ArchiveLayout,
+) -> Result<ArchiveClassIter<'a, F>, String> {
+    let archive_file = File::open(archive_path).map_err(|e| format!("{e:?}"))?;
+    let mut archive = ZipArchive::new(archive_file).map_err(|e| format!("{e:?}"))?;
+    let lsb_first = is_lsb_first(&mut archive, layout);
+    let stored_hctl_vars = read_stored_hctl_vars(&mut archive, layout);
+    let index = read_bdd_index(&mut archive, layout)?;
+    Ok(ArchiveClassIter {
+        archive,
+        graph,
+        layout: layout.clone(),
+        index: index.into_iter(),
+        filter,
+        lsb_first,
+        stored_hctl_vars,
+        valid_colors: None,
+    })
+}
+
+/// Check the integrity of the BDD dumps in the `archive_path` zip, i.e., recompute their SHA-256
+/// hashes and compare them with those recorded in `checksums.txt` (see
+/// [crate::write_output::OutputOptions::checksums]).
+///
+/// An error is returned if the archive has no checksums, or if some dump is missing or differs.
+pub fn verify_archive(archive_path: &str) -> Result<(), String> {
+    verify_archive_with_layout(archive_path, &ArchiveLayout::default())
+}
+
+/// Same as [verify_archive], but the entries of the archive are named by the `layout` (see
+/// [crate::write_output::OutputOptions::layout]).
+pub fn verify_archive_with_layout(
+    archive_path: &str,
+    layout: &ArchiveLayout,
+) -> Result<(), String> {
+    let archive_file = File::open(archive_path).map_err(|e| format!("{e:?}"))?;
+    let mut archive = ZipArchive::new(archive_file).map_err(|e| format!("{e:?}"))?;
+    let checksums = layout.entry(&layout.checksums);
+    if archive.by_name(&checksums).is_err() {
+        return Err(format!("Archive does not contain `{checksums}`."));
+    }
+
+    let checksums = read_zip_file(&mut archive, &checksums)?;
+    for line in checksums.lines() {
+        let Some((digest, file)) = line.split_once("  ") else {
+            return Err(format!("Malformed checksum entry `{line}`."));
+        };
+        let mut entry = archive.by_name(file).map_err(|e| format!("{e:?}"))?;
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut entry, &mut hasher).map_err(|e| format!("{e:?}"))?;
+        if format!("{:x}", hasher.finalize()) != digest {
+            return Err(format!("Checksum mismatch in `{file}`."));
+        }
+    }
+    Ok(())
+}
+
+/// Check the consistency of the `archive_path` zip produced by the classifier, i.e., that the
+/// categories (loaded as in [read_classification_archive]) are pairwise disjoint and, if the
+/// archive contains `checksums.txt`, that its BDD dumps are intact (see [verify_archive]).
+pub fn verify_classification_archive(archive_path: &str) -> Result<(), String> {
+    verify_classification_archive_with_layout(archive_path, &ArchiveLayout::default())
+}
+
+/// Same as [verify_classification_archive], but the entries of the archive are named by the
+/// `layout` (see [crate::write_output::OutputOptions::layout]).
+pub fn verify_classification_archive_with_layout(
+    archive_path: &str,
+    layout: &ArchiveLayout,
+) -> Result<(), String> {
+    let archive_file = File::open(archive_path).map_err(|e| format!("{e:?}"))?;
+    let mut archive = ZipArchive::new(archive_file).map_err(|e| format!("{e:?}"))?;
+    let has_checksums = archive.by_name(&layout.entry(&layout.checksums)).is_ok();
+    let aeon_str = read_zip_file(&mut archive, &layout.entry(&layout.model))?;
+    let bn = BooleanNetwork::try_from(aeon_str.as_str())?;
+    let graph = SymbolicAsyncGraph::new(bn)?;
+
+    let mut covered = graph.mk_empty_colors();
+    let filter = ClassFilter::default();
+    for (validity, colors) in
+        read_filtered_classification_archive_with_layout(archive_path, &graph, &filter, layout)?
+    {
+        // Disjointness with the union of the previous categories implies pairwise disjointness.
+        if !colors.intersect(&covered).is_empty() {
+            return Err(ClassifierError::PartitionInvariantViolated(format!(
+                "category `{validity:?}` overlaps with other categories"
+            ))
+            .to_string());
+        }
+        covered = covered.union(&colors);
+    }
+    if has_checksums {
+        verify_archive_with_layout(archive_path, layout)?;
+    }
+    Ok(())
+}
+
+/// Read the text report (`report.txt`) of the `archive_path` zip produced by the classifier.
+pub fn read_archive_report(archive_path: &str) -> Result<String, String> {
+    read_archive_report_with_layout(archive_path, &ArchiveLayout::default())
+}
+
+/// Same as [read_archive_report], but the entries of the archive are named by the `layout`
+/// (see [crate::write_output::OutputOptions::layout]).
+pub fn read_archive_report_with_layout(
+    archive_path: &str,
+    layout: &ArchiveLayout,
+) -> Result<String, String> {
+    let archive_file = File::open(archive_path).map_err(|e| format!("{e:?}"))?;
+    let mut archive = ZipArchive::new(archive_file).map_err(|e| format!("{e:?}"))?;
+    let report = layout.entry(&layout.report);
+    if archive.by_name(&report).is_err() {
+        return Err(format!(
+            "Archive `{archive_path}` does not contain `{report}`."
+        ));
+    }
+    read_zip_file(&mut archive, &report)
 }
@@ -9,19 +9,61 @@
 //! decomposed into categories based on the classification properties they satisfy.
 //!
 
-pub mod classification;
-pub mod load_inputs;
-pub mod write_output;
-
-use crate::classification::classify;
-use clap::Parser;
+use bn_classifier::categories::DEFAULT_MAX_CLASSES;
+use bn_classifier::classification::{classify, merge_archives};
+use bn_classifier::load_inputs::{read_archive_report, verify_classification_archive};
+use bn_classifier::write_output::{BitOrder, CardinalityFormat, CompressionOptions, OutputOptions};
+use clap::{Args, CommandFactory, Parser, Subcommand};
 use std::path::Path;
 use std::time::SystemTime;
+use zip::CompressionMethod;
 
-/// Structure to collect CLI arguments
+/// Structure to collect CLI arguments. Without a subcommand, the arguments of `classify` are
+/// expected (i.e., `bn-classifier model.aeon` is the same as `bn-classifier classify model.aeon`).
 #[derive(Parser)]
-#[clap(about = "Symbolic classifier for BN models based on dynamic properties.")]
+#[clap(
+    about = "Symbolic classifier for BN models based on dynamic properties.",
+    args_conflicts_with_subcommands = true
+)]
 struct Arguments {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    #[clap(flatten)]
+    classify: Option<ClassifyArguments>,
+}
+
+/// Subcommands of the CLI.
+#[derive(Subcommand)]
+enum Command {
+    /// Classify an annotated model and write the results into a zip archive.
+    Classify(ClassifyArguments),
+    /// Print the report of an existing classification archive.
+    Inspect {
+        /// Path to the classification archive.
+        archive_path: String,
+    },
+    /// Merge the classification archives of several shards of the parameter space.
+    Merge {
+        /// Paths to the archives of the individual shards.
+        #[clap(required = true)]
+        archive_paths: Vec<String>,
+
+        /// Path to a zip archive to which the merged results will be written.
+        #[clap(short, long, default_value = "classification_result.zip")]
+        output_zip: String,
+    },
+    /// Check that the classes of an archive are disjoint, and that its BDD dumps match the
+    /// recorded checksums (if there are any).
+    Verify {
+        /// Path to the classification archive.
+        archive_path: String,
+    },
+}
+
+/// CLI arguments of the classification.
+#[derive(Args)]
+struct ClassifyArguments {
     /// Path to a file in annotated `aeon` format containing a PSBN model and 2 sets
     /// of HCTL formulae.
     input_path: String,
@@ -29,13 +71,180 @@ struct Arguments {
     /// Path to a zip archive to which a report and BDD results will be dumped.
     #[clap(short, long, default_value = "classification_result.zip")]
     output_zip: String,
+
+    /// Do not dump the BDDs of the categories (only the reports are written).
+    #[clap(long)]
+    no_bdds: bool,
+
+    /// Also dump the BDD of each category in the Graphviz `.dot` format.
+    #[clap(long)]
+    dot_dumps: bool,
+
+    /// List only non-empty classes in the report, sorted by their size (largest first).
+    #[clap(long)]
+    sort_by_size: bool,
+
+    /// Check that the classes form an exact partition of the valid colors before writing them.
+    #[clap(long)]
+    verify: bool,
+
+    /// Aggregate the classes with less colors than this into one `other` entry in the report.
+    #[clap(long, default_value_t = 0.0)]
+    min_class_cardinality: f64,
+
+    /// Write the large color counts in the report in the scientific notation (e.g., `3.40e38`).
+    /// This is a shorthand for `--cardinality-format scientific`.
+    #[clap(long, conflicts_with = "cardinality_format")]
+    scientific: bool,
+
+    /// Format of the color counts in the report (`fixed`, `scientific`, `exact` or `grouped`).
+    #[clap(long, value_parser = parse_cardinality_format)]
+    cardinality_format: Option<CardinalityFormat>,
+
+    /// List the combinations of properties that no color satisfies in the report.
+    #[clap(long)]
+    absent_classes: bool,
+
+    /// Also write the lattice of the classes in the GraphML format (`lattice.graphml`).
+    #[clap(long)]
+    lattice: bool,
+
+    /// Also write the pairwise overlaps of the properties (`overlaps.csv`).
+    #[clap(long)]
+    overlaps: bool,
+
+    /// Record the duration of the phases of the computation in `timing.txt`.
+    #[clap(long)]
+    timing: bool,
+
+    /// Comma-separated names of all properties in the order of their bits in the class labels.
+    #[clap(long, value_delimiter = ',')]
+    property_order: Option<Vec<String>>,
+
+    /// Only count the colors of each class, without computing (and dumping) the class BDDs.
+    #[clap(long)]
+    stats_only: bool,
+
+    /// Enumerate all property combinations (including the empty classes) only if there are
+    /// at most this many of them, otherwise only the non-empty classes.
+    #[clap(long, default_value_t = DEFAULT_MAX_CLASSES)]
+    max_classes: usize,
+
+    /// Describe a representative instance of each class in the report.
+    #[clap(long)]
+    representatives: bool,
+
+    /// List the BDD variables that appear in the BDD of each class in the report.
+    #[clap(long)]
+    bdd_support: bool,
+
+    /// Also write a copy of the text report next to the archive (`<output>.report.txt`).
+    #[clap(long)]
+    report_sidecar: bool,
+
+    /// Write the BDD dumps into `<output>.bdds.zip` and the reports into `<output>.report.zip`.
+    #[clap(long)]
+    split_bdds: bool,
+
+    /// Fail instead of replacing the output archive if it already exists.
+    #[clap(long)]
+    no_overwrite: bool,
+
+    /// Record the SHA-256 hash of each BDD dump in `checksums.txt`.
+    #[clap(long)]
+    checksums: bool,
+
+    /// Write the class labels from the least significant bit (i.e., the last property first).
+    #[clap(long)]
+    lsb_first: bool,
+
+    /// Dump the classes covering most of the valid colors as their complement.
+    #[clap(long)]
+    relative_bdds: bool,
+
+    /// Write the BDD dumps in the compact binary format (`bdd_dump_<bits>.bdd`).
+    #[clap(long)]
+    binary_bdds: bool,
+
+    /// Compress the BDD dumps by zstd with a dictionary trained on the dumps (`bdd.dict`).
+    #[clap(long)]
+    bdd_dictionary: bool,
+
+    /// Also write the report in the XML format (`report.xml`).
+    #[clap(long)]
+    xml_report: bool,
+
+    /// Also write the report in the Markdown format (`report.md`).
+    #[clap(long)]
+    markdown_report: bool,
+
+    /// Compression method for the BDD dumps (`stored`, `deflate`, `bzip2` or `zstd`).
+    #[clap(long, default_value = "deflate", value_parser = parse_compression_method)]
+    bdd_compression: CompressionMethod,
+
+    /// Compression level for the BDD dumps (the range depends on the compression method).
+    #[clap(long)]
+    bdd_compression_level: Option<i32>,
 }
 
-/// Wrapper function to invoke the classifier and feed it with CLI arguments.
+/// Parse the name of a zip compression method given as a CLI argument.
+fn parse_compression_method(name: &str) -> Result<CompressionMethod, String> {
+    match name {
+        "stored" => Ok(CompressionMethod::Stored),
+        "deflate" => Ok(CompressionMethod::Deflated),
+        "bzip2" => Ok(CompressionMethod::Bzip2),
+        "zstd" => Ok(CompressionMethod::Zstd),
+        _ => Err(format!("Unknown compression method `{name}`.")),
+    }
+}
+
+/// Parse the name of a cardinality format given as a CLI argument.
+fn parse_cardinality_format(name: &str) -> Result<CardinalityFormat, String> {
+    match name {
+        "fixed" => Ok(CardinalityFormat::Fixed),
+        "scientific" => Ok(CardinalityFormat::Scientific),
+        "exact" => Ok(CardinalityFormat::Exact),
+        "grouped" => Ok(CardinalityFormat::Grouped),
+        _ => Err(format!("Unknown cardinality format `{name}`.")),
+    }
+}
+
+/// Wrapper function to invoke the subcommand given by the CLI arguments.
 fn main() {
+    let args = Arguments::parse();
+    let result = match args.command {
+        Some(Command::Classify(args)) => run_classify(args),
+        Some(Command::Inspect { archive_path }) => {
+            read_archive_report(&archive_path).map(|report| print!("{report}"))
+        }
+        Some(Command::Merge {
+            archive_paths,
+            output_zip,
+        }) => {
+            let archive_paths: Vec<&str> = archive_paths.iter().map(|it| it.as_str()).collect();
+            merge_archives(&archive_paths, &output_zip)
+                .map(|()| println!("Merged results saved to `{output_zip}`."))
+        }
+        Some(Command::Verify { archive_path }) => verify_classification_archive(&archive_path)
+            .map(|()| println!("Archive `{archive_path}` is consistent.")),
+        None => match args.classify {
+            Some(args) => run_classify(args),
+            None => {
+                Arguments::command().print_help().unwrap();
+                Ok(())
+            }
+        },
+    };
+    if let Err(error) = result {
+        eprintln!("Error: {error}");
+        std::process::exit(1);
+    }
+}
+
+/// Wrapper function to invoke the classifier and feed it with CLI arguments.
+fn run_classify(args: ClassifyArguments) -> Result<(), String> {
     let start = SystemTime::now();
 
-    let args = Arguments::parse();
     println!("Loading input files...");
 
     let input_path = args.input_path;
@@ -43,21 +252,54 @@ fn main() {
 
     // check if given input path is valid
     if !Path::new(input_path.as_str()).is_file() {
-        println!("{input_path} is not valid file");
-        return;
+        return Err(format!("{input_path} is not valid file"));
     }
 
-    let classification_res = classify(input_path.as_str(), output_name.as_str());
-
-    if classification_res.is_err() {
-        println!(
-            "Error during computation: {}",
-            classification_res.err().unwrap()
-        )
-    }
+    let options = OutputOptions {
+        include_bdds: !args.no_bdds,
+        dot_dumps: args.dot_dumps,
+        sort_classes_by_size: args.sort_by_size,
+        markdown_report: args.markdown_report,
+        xml_report: args.xml_report,
+        binary_bdds: args.binary_bdds,
+        bdd_dictionary: args.bdd_dictionary,
+        relative_bdds: args.relative_bdds,
+        min_class_cardinality: args.min_class_cardinality,
+        verify: args.verify,
+        representatives: args.representatives,
+        bdd_support: args.bdd_support,
+        cardinality_format: match args.cardinality_format {
+            Some(format) => format,
+            None if args.scientific => CardinalityFormat::Scientific,
+            None => CardinalityFormat::Fixed,
+        },
+        absent_classes: args.absent_classes,
+        overlaps: args.overlaps,
+        lattice: args.lattice,
+        report_sidecar: args.report_sidecar,
+        split_bdds: args.split_bdds,
+        stats_only: args.stats_only,
+        max_classes: args.max_classes,
+        property_order: args.property_order,
+        timing: args.timing,
+        checksums: args.checksums,
+        overwrite: !args.no_overwrite,
+        bit_order: if args.lsb_first {
+            BitOrder::LsbFirst
+        } else {
+            BitOrder::MsbFirst
+        },
+        bdd_compression: CompressionOptions {
+            method: args.bdd_compression,
+            level: args.bdd_compression_level,
+        },
+        ..Default::default()
+    };
+    classify(input_path.as_str(), output_name.as_str(), &options)?;
 
     println!(
         "Total computation time: {}ms",
         start.elapsed().unwrap().as_millis()
     );
+    Ok(())
 }
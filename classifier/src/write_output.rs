@@ -1,271 +1,5381 @@
 //! Finish the classification process and generate the results (report and BDD representation).
 
+use crate::categories::{
+    build_classification_result, colors_equal, num_combinations, num_combinations_through,
+    property_implications, property_overlaps, property_relations, verify_partition, Category,
+    ClassificationResult, ClassifiedSet, PropertyRelation, PropertyResult,
+    TernaryClassificationResult, Verdict, DEFAULT_MAX_CLASSES, MAX_ABSENT_CLASS_PROPERTIES,
+};
+use crate::errors::ClassifierError;
+use crate::load_inputs::read_formula_variables;
+
+use base64ct::{Base64, Encoding};
+use biodivine_lib_bdd::Bdd;
 use biodivine_lib_param_bn::biodivine_std::traits::Set;
-use biodivine_lib_param_bn::symbolic_async_graph::GraphColors;
-use std::collections::HashMap;
+use biodivine_lib_param_bn::symbolic_async_graph::{
+    GraphColors, SymbolicAsyncGraph, SymbolicContext,
+};
+use biodivine_lib_param_bn::BooleanNetwork;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use num_bigint::{BigInt, Sign};
+use num_traits::Zero;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 
-use std::fs::File;
-use std::io::{ErrorKind, Write};
-use std::path::Path;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, ErrorKind, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 
 use zip::write::{FileOptions, ZipWriter};
-
-/// Transform integer into a corresponding binary number of the given length.
-///
-/// If the integer "bit width" is larger than the given length, it is truncated. If it is smaller,
-/// the result is padded with zeroes to ensure `result.len() == bits_num`.
-///
-/// The result is given in MSB first (most significant bit first) format (as opposed to LSB, which
-/// is a bit more common in other applications). This means that when the vector is printed (with
-/// first element being the left-most printed item), it can be read left-to-right as the binary
-/// representation of the input `number`.
-fn int_to_bool_vec(number: i32, bits_num: usize) -> Vec<bool> {
-    let mut bits = vec![false; bits_num]; // Pre-allocate the values in one operation.
-    for i in 0..bits_num {
-        let msb_index = bits_num - i - 1; // Invert index to ensure MSB bit order.
-        bits[msb_index] = ((number >> i) & 1) == 1;
-    }
-    bits
-}
+use zip::{CompressionMethod, ZipArchive};
 
 /// Convert a vector of bools to the corresponding binary string.
-fn bool_vec_to_string(bool_data: &[bool]) -> String {
+pub(crate) fn bool_vec_to_string(bool_data: &[bool]) -> String {
     bool_data
         .iter()
         .map(|x| if *x { '1' } else { '0' })
         .collect()
 }
 
-/// Prepare the initial part of the report regarding results for assertion formulae and
-/// results for individual property formulae.
-fn prepare_report_intro(
-    assertion_formulae: &[String],
-    all_valid_colors: &GraphColors,
-    named_property_formulae: &[(String, String)],
-    property_results: &[GraphColors],
-) -> Result<Vec<u8>, std::io::Error> {
-    // We will first write the report into an intermediate buffer,
-    // because we want to write it into the zip archive at the end
-    // once all results are computed.
-    let mut report = Vec::new();
-
-    // Write the list of assertions.
-    writeln!(report, "### Assertion formulae")?;
-    writeln!(report)?;
-    for assertion in assertion_formulae {
-        writeln!(report, "# {assertion}")?;
+/// Order of the bits in the class labels (e.g., `bdd_dump_<bits>.txt` or `# <bits>` in the
+/// report). Each bit gives the validity of one property.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BitOrder {
+    /// The bits of the class number are written from the most significant one, i.e., the
+    /// left-most bit belongs to the first property.
+    #[default]
+    MsbFirst,
+    /// The bits of the class number are written from the least significant one, i.e., the
+    /// left-most bit belongs to the last property.
+    LsbFirst,
+}
+
+impl BitOrder {
+    /// Name of the bit order used in the metadata and in the JSON report.
+    fn name(self) -> &'static str {
+        match self {
+            BitOrder::MsbFirst => "msb_first",
+            BitOrder::LsbFirst => "lsb_first",
+        }
     }
-    writeln!(
-        report,
-        "{:.0} colors satisfy all assertions",
-        all_valid_colors.approx_cardinality()
-    )?;
-    writeln!(report)?;
 
-    // Write results for each property.
-    writeln!(report, "### Property formulae individually")?;
-    writeln!(report)?;
-    for i in 0..named_property_formulae.len() {
-        let (name, property) = &named_property_formulae[i];
-        writeln!(report, "# {name}  |  {property}")?;
-        let cardinality = property_results[i].approx_cardinality();
-        writeln!(report, "{cardinality:.0} colors satisfy this property")?;
-        writeln!(report)?;
+    /// Label of the class with the given `validity` of properties.
+    fn label(self, validity: &[bool]) -> String {
+        match self {
+            BitOrder::MsbFirst => bool_vec_to_string(validity),
+            BitOrder::LsbFirst => validity
+                .iter()
+                .rev()
+                .map(|x| if *x { '1' } else { '0' })
+                .collect(),
+        }
     }
+}
 
-    // Output info regarding the classification.
-    writeln!(report, "### Classes")?;
-    writeln!(report)?;
+/// Function giving the custom name of the class with the given validity vector (if any).
+pub type ClassNameFn = dyn Fn(&[bool]) -> Option<String>;
 
-    Ok(report)
+/// Transformation of the colors of a category before they are dumped (see
+/// [ClassificationWriter::transform_dumps]).
+pub type DumpTransformFn<'a> = dyn Fn(&GraphColors) -> GraphColors + 'a;
+
+/// Optional callbacks of the writers, see [ClassificationWriter::on_progress],
+/// [ClassificationWriter::with_class_names] and [ClassificationWriter::transform_dumps].
+#[derive(Clone, Copy, Default)]
+struct WriterHooks<'a> {
+    progress: Option<&'a dyn Fn(usize, usize)>,
+    class_names: Option<&'a ClassNameFn>,
+    transform: Option<&'a DumpTransformFn<'a>>,
 }
 
-/// Write a short summary regarding each category of the color decomposition, and dump a BDD
-/// encoding the colors, all into the `archive_name` zip.
-///
-///  - `assertion_formulae`: list of assertion formulae
-///  - `all_valid_colors`: represents a "unit color set", i.e. all colors satisfying the
-///     assertion formulae.
-///  - `named_property_formulae`: lists the property names with their HCTL formula strings.
-///  - `property_results`: lists the symbolic color set results for each property.
-///  - `archive_name`: name of the `.zip` archive with results.
-///  - `original_model_str`: original model in the aeon format
+/// Labels of the classes in the output: the bit strings in the given `bit_order`, unless
+/// the `names` function gives a custom name of the class (see
+/// [ClassificationWriter::with_class_names]).
+#[derive(Clone, Copy)]
+struct ClassLabels<'a> {
+    bit_order: BitOrder,
+    names: Option<&'a ClassNameFn>,
+}
+
+impl ClassLabels<'_> {
+    /// Label of the class with the given `validity` of properties.
+    fn label(&self, validity: &[bool]) -> String {
+        self.names
+            .and_then(|names| names(validity))
+            .unwrap_or_else(|| self.bit_order.label(validity))
+    }
+
+    /// Label of the class in the human-readable reports. Same as [ClassLabels::label], except
+    /// that the only class of a classification without properties (whose bit string is empty)
+    /// is called [ALL_COLORS_LABEL].
+    fn title(&self, validity: &[bool]) -> String {
+        let label = self.label(validity);
+        if label.is_empty() {
+            ALL_COLORS_LABEL.to_string()
+        } else {
+            label
+        }
+    }
+}
+
+/// True if the custom `name` of a class can be used in the name of its BDD dump, i.e., it is
+/// not empty and it does not contain `/`, `\` or `..` (so the dump stays in its directory).
+fn is_valid_class_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['/', '\\']) && !name.contains("..")
+}
+
+/// Human-readable name of the only class of a classification without any properties.
+const ALL_COLORS_LABEL: &str = "all colors satisfying assertions";
+
+impl From<BitOrder> for ClassLabels<'_> {
+    fn from(bit_order: BitOrder) -> Self {
+        ClassLabels {
+            bit_order,
+            names: None,
+        }
+    }
+}
+
+/// Express `part` as a percentage of `total`. If `total` is zero, the result is zero as well.
+fn percentage(part: f64, total: f64) -> f64 {
+    if total == 0.0 {
+        0.0
+    } else {
+        100.0 * part / total
+    }
+}
+
+/// If there are some non-existing directories in the `archive_path`, create them. Nothing is
+/// created for a bare file name (i.e., an archive in the current directory).
+fn create_parent_dirs(archive_path: &Path) -> Result<(), std::io::Error> {
+    match archive_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => std::fs::create_dir_all(parent),
+        _ => Ok(()),
+    }
+}
+
+/// Create the output file at `archive_path` (including its parent directories). An existing file
+/// is truncated, unless `overwrite` is false, in which case [ClassifierError::OutputExists]
+/// is returned instead. The I/O failures are retried according to the `retry` policy.
+fn create_output_file(
+    archive_path: &Path,
+    overwrite: bool,
+    retry: RetryPolicy,
+) -> Result<File, ClassifierError> {
+    let transient = |error: &ClassifierError| matches!(error, ClassifierError::Io(_));
+    retry.run(transient, || {
+        try_create_output_file(archive_path, overwrite)
+    })
+}
+
+/// One attempt of [create_output_file].
+fn try_create_output_file(archive_path: &Path, overwrite: bool) -> Result<File, ClassifierError> {
+    create_parent_dirs(archive_path)?;
+    if overwrite {
+        return Ok(File::create(archive_path)?);
+    }
+    // Checking and creating the file in one step ensures that no file is ever truncated.
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(archive_path);
+    match file {
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => Err(ClassifierError::OutputExists(
+            archive_path.display().to_string(),
+        )),
+        file => Ok(file?),
+    }
+}
+
+/// Copy the text report of the finished `archive_name` zip (given by the `layout`) into the
+/// `<archive_name>.report.txt` file next to it (see [OutputOptions::report_sidecar]).
+fn write_report_sidecar(
+    archive_name: &str,
+    layout: &ArchiveLayout,
+    overwrite: bool,
+    retry: RetryPolicy,
+) -> Result<(), ClassifierError> {
+    let mut archive = ZipArchive::new(File::open(archive_name)?)?;
+    let mut report = archive.by_name(&layout.entry(&layout.report))?;
+    let sidecar_path = PathBuf::from(format!("{archive_name}.report.txt"));
+    let mut sidecar = create_output_file(&sidecar_path, overwrite, retry)?;
+    std::io::copy(&mut report, &mut sidecar)?;
+    Ok(())
+}
+
+/// Write a zip archive into a new `archive_name` file (see [create_output_file]), where the
+/// entries are written by `write_entries`. If anything fails, the half-written file is removed,
+/// so that it cannot be mistaken for a valid result. Failure of the final step (writing
+/// the central directory of the archive) is reported as [ClassifierError::Finalize].
 ///
-/// Each result category is given by a set of colors that satisfy exactly the same properties.
-pub fn write_classifier_output(
-    assertion_formulae: &[String],
-    all_valid_colors: &GraphColors,
-    named_property_formulae: &[(String, String)],
-    property_results: &[GraphColors],
+/// Only the creation of the file is retried by the `retry` policy. The final step is not
+/// repeatable (the writer is finished even if it fails), so its failure is terminal.
+fn write_archive_file(
     archive_name: &str,
-    original_model_str: &str,
-) -> Result<(), std::io::Error> {
+    overwrite: bool,
+    retry: RetryPolicy,
+    write_entries: impl FnOnce(&mut ZipWriter<File>) -> Result<(), ClassifierError>,
+) -> Result<(), ClassifierError> {
     let archive_path = Path::new(archive_name);
-    // If there are some non existing dirs in path, create them.
-    let prefix = archive_path
-        .parent()
-        .ok_or(std::io::Error::new(ErrorKind::Other, "Invalid path."))?;
-    std::fs::create_dir_all(prefix)?;
-
-    // Create a zip writer for the desired archive.
-    let archive = File::create(archive_path)?;
-    let mut zip_writer = ZipWriter::new(archive);
-
-    // We will first write the report into an intermediate buffer,
-    // because we want to write it into the zip archive at the end
-    // once all results are computed.
-    let mut report = prepare_report_intro(
-        assertion_formulae,
-        all_valid_colors,
-        named_property_formulae,
-        property_results,
-    )?;
+    let mut zip_writer = ZipWriter::new(create_output_file(archive_path, overwrite, retry)?);
+    let written = write_entries(&mut zip_writer).and_then(|()| {
+        zip_writer
+            .finish()
+            .map(|_| ())
+            .map_err(|error| ClassifierError::Finalize {
+                path: archive_name.to_string(),
+                error,
+            })
+    });
+    // The file must be closed before it can be removed (on some platforms).
+    drop(zip_writer);
+    if written.is_err() {
+        // The original error is more important than a failure of the clean-up.
+        let _ = std::fs::remove_file(archive_path);
+    }
+    written
+}
+
+/// Names of the entries in the result archive.
+///
+/// All names are prefixed with `prefix` (e.g., `classification/` to nest all entries in
+/// a directory). In `bdd_dump`, `binary_bdd_dump` and `dot_dump`, the `{name}` placeholder is
+/// substituted by the category name (i.e., the bit string of the category for the classifier
+/// output). The archives with a custom layout are read back by the loaders given the same
+/// layout (e.g., [crate::load_inputs::load_classification_archive_with_layout]).
+#[derive(Clone, Debug)]
+pub struct ArchiveLayout {
+    pub prefix: String,
+    pub metadata: String,
+    pub report: String,
+    pub report_json: String,
+    pub report_md: String,
+    pub report_xml: String,
+    pub classes_csv: String,
+    pub overlaps_csv: String,
+    pub lattice: String,
+    pub classes_legend: String,
+    pub model: String,
+    pub properties: String,
+    pub bdd_index: String,
+    pub checksums: String,
+    pub timing: String,
+    pub companion: String,
+    pub bdd_dictionary: String,
+    pub valid_colors: String,
+    pub bdd_dump: String,
+    pub binary_bdd_dump: String,
+    pub dot_dump: String,
+}
+
+impl Default for ArchiveLayout {
+    fn default() -> Self {
+        ArchiveLayout {
+            prefix: String::new(),
+            metadata: "metadata.txt".to_string(),
+            report: "report.txt".to_string(),
+            report_json: "report.json".to_string(),
+            report_md: "report.md".to_string(),
+            report_xml: "report.xml".to_string(),
+            classes_csv: "classes.csv".to_string(),
+            overlaps_csv: "overlaps.csv".to_string(),
+            lattice: "lattice.graphml".to_string(),
+            classes_legend: "classes_legend.txt".to_string(),
+            model: "model.aeon".to_string(),
+            properties: "properties.hctl".to_string(),
+            bdd_index: "bdd_index.txt".to_string(),
+            checksums: "checksums.txt".to_string(),
+            timing: "timing.txt".to_string(),
+            companion: "companion.txt".to_string(),
+            bdd_dictionary: "bdd.dict".to_string(),
+            valid_colors: "valid_colors.txt".to_string(),
+            bdd_dump: "bdd_dump_{name}.txt".to_string(),
+            binary_bdd_dump: "bdd_dump_{name}.bdd".to_string(),
+            dot_dump: "bdd_dump_{name}.dot".to_string(),
+        }
+    }
+}
 
-    // If this is broken, the number of properties is too high
-    // to enumerate the combinations explicitly.
-    assert!(property_results.len() < 31);
-    let number_of_combinations = 1 << property_results.len();
+impl ArchiveLayout {
+    /// Full name of the archive entry `file_name`.
+    pub(crate) fn entry(&self, file_name: &str) -> String {
+        format!("{}{file_name}", self.prefix)
+    }
 
-    for i in 0..number_of_combinations {
-        let validity = int_to_bool_vec(i, property_results.len());
+    /// Full name of the archive entry with the BDD dump of the category called `category_name`
+    /// (in the `binary` or the text format).
+    fn bdd_dump_entry(&self, category_name: &str, binary: bool) -> String {
+        let template = if binary {
+            &self.binary_bdd_dump
+        } else {
+            &self.bdd_dump
+        };
+        self.entry(&template.replace("{name}", dump_name(category_name)))
+    }
 
-        // Build the color set of this category based on the validity vector for this index.
-        let mut category_colors = all_valid_colors.clone();
-        for (set, is_valid) in property_results.iter().zip(validity.iter()) {
-            if *is_valid {
-                category_colors = category_colors.intersect(set);
-            } else {
-                category_colors = category_colors.minus(set);
-            }
+    /// Full name of the archive entry with the `.dot` dump of the category called `category_name`.
+    fn dot_dump_entry(&self, category_name: &str) -> String {
+        self.entry(&self.dot_dump.replace("{name}", dump_name(category_name)))
+    }
+
+    /// Check if the entry called `name` is a part of the BDD dumps (i.e., a dump of some
+    /// category, the valid colors, the BDD index or the checksums).
+    fn is_bdd_entry(&self, name: &str) -> bool {
+        let is_dump = [&self.bdd_dump, &self.binary_bdd_dump, &self.dot_dump]
+            .into_iter()
+            .filter_map(|template| template.split_once("{name}"))
+            .any(|(start, end)| {
+                let start = self.entry(start);
+                name.len() > start.len() + end.len()
+                    && name.starts_with(&start)
+                    && name.ends_with(end)
+            });
+        is_dump
+            || [
+                &self.valid_colors,
+                &self.bdd_index,
+                &self.checksums,
+                &self.bdd_dictionary,
+            ]
+            .into_iter()
+            .any(|file_name| name == self.entry(file_name))
+    }
+
+    /// Name of the category whose (text or binary) BDD dump is the entry called `name`, if it is
+    /// such a dump.
+    pub(crate) fn dumped_category<'n>(&self, name: &'n str) -> Option<&'n str> {
+        [&self.bdd_dump, &self.binary_bdd_dump]
+            .into_iter()
+            .filter_map(|template| template.split_once("{name}"))
+            .find_map(|(start, end)| name.strip_prefix(&self.entry(start))?.strip_suffix(end))
+    }
+
+    /// Check if the entry called `name` is needed in both archives of the split output (see
+    /// [OutputOptions::split_bdds]), so that each of them can be opened on its own (this also
+    /// includes the cross-reference of the two archives).
+    fn is_shared_entry(&self, name: &str) -> bool {
+        [
+            &self.metadata,
+            &self.model,
+            &self.properties,
+            &self.companion,
+        ]
+        .into_iter()
+        .any(|file_name| name == self.entry(file_name))
+    }
+}
+
+/// Name used in the dump files of the category called `category_name`. Without any properties,
+/// the only category has an empty name, so its files are called `all` instead (e.g.,
+/// `bdd_dump_all.txt` rather than `bdd_dump_.txt`).
+fn dump_name(category_name: &str) -> &str {
+    if category_name.is_empty() {
+        "all"
+    } else {
+        category_name
+    }
+}
+
+/// Destination of the entries of the result archive: either a zip archive, or a directory
+/// (see [DirectoryWriter]).
+trait EntryWriter: Write {
+    /// Start a new entry called `name`, the following writes then go into this entry.
+    fn start_entry(&mut self, name: &str, options: FileOptions) -> Result<(), ClassifierError>;
+}
+
+impl<W: Write + Seek> EntryWriter for ZipWriter<W> {
+    fn start_entry(&mut self, name: &str, options: FileOptions) -> Result<(), ClassifierError> {
+        Ok(self.start_file(name, options)?)
+    }
+}
+
+/// Writes the entries of the result archive as files in the `root` directory (the compression
+/// options of the entries are ignored).
+struct DirectoryWriter {
+    root: PathBuf,
+    file: Option<BufWriter<File>>,
+}
+
+impl DirectoryWriter {
+    /// Flush the current entry, if there is one.
+    fn finish(&mut self) -> Result<(), std::io::Error> {
+        match self.file.take() {
+            Some(mut file) => file.flush(),
+            None => Ok(()),
         }
+    }
+}
 
-        writeln!(report, "# {}", bool_vec_to_string(&validity))?;
-        writeln!(
-            report,
-            "{:.0} colors in this category",
-            category_colors.approx_cardinality()
-        )?;
-        writeln!(report)?;
+impl Write for DirectoryWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self.file.as_mut() {
+            Some(file) => file.write(buf),
+            None => Err(std::io::Error::new(ErrorKind::Other, "No entry started.")),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl EntryWriter for DirectoryWriter {
+    fn start_entry(&mut self, name: &str, _options: FileOptions) -> Result<(), ClassifierError> {
+        self.finish()?;
+        let path = self.root.join(name);
+        create_parent_dirs(&path)?;
+        self.file = Some(BufWriter::new(File::create(path)?));
+        Ok(())
+    }
+}
 
-        if !category_colors.is_empty() {
-            // If the BDD is not empty, the results go directly into the zip archive.
-            let bdd_file_name = format!("bdd_dump_{}.txt", bool_vec_to_string(&validity));
-            zip_writer
-                .start_file(&bdd_file_name, FileOptions::default())
-                .map_err(std::io::Error::from)?;
+/// Writes the entries of the result into two zip archives (see [OutputOptions::split_bdds]):
+/// the BDD dumps go into `bdds`, the metadata and the model into both archives, and everything
+/// else into `report`.
+struct SplitWriter<'a> {
+    layout: &'a ArchiveLayout,
+    report: &'a mut ZipWriter<File>,
+    bdds: &'a mut ZipWriter<File>,
+    /// Destinations of the current entry, as `(report, bdds)` flags.
+    target: (bool, bool),
+}
 
-            category_colors.as_bdd().write_as_string(&mut zip_writer)?;
+impl Write for SplitWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // Both archives must receive the same data, so partial writes are not possible.
+        if self.target.0 {
+            self.report.write_all(buf)?;
         }
+        if self.target.1 {
+            self.bdds.write_all(buf)?;
+        }
+        Ok(buf.len())
     }
 
-    // Finally, we can write the report.
-    zip_writer
-        .start_file("report.txt", FileOptions::default())
-        .map_err(std::io::Error::from)?;
-    zip_writer.write_all(&report)?;
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.report.flush()?;
+        self.bdds.flush()
+    }
+}
 
-    // Include the original model in the result bundle (we need to load it later).
-    zip_writer
-        .start_file("model.aeon", FileOptions::default())
-        .map_err(std::io::Error::from)?;
-    write!(zip_writer, "{original_model_str}")?;
+impl EntryWriter for SplitWriter<'_> {
+    fn start_entry(&mut self, name: &str, options: FileOptions) -> Result<(), ClassifierError> {
+        let shared = self.layout.is_shared_entry(name);
+        let is_bdd = self.layout.is_bdd_entry(name);
+        self.target = (shared || !is_bdd, shared || is_bdd);
+        if self.target.0 {
+            self.report.start_file(name, options)?;
+        }
+        if self.target.1 {
+            self.bdds.start_file(name, options)?;
+        }
+        Ok(())
+    }
+}
 
-    zip_writer.finish().map_err(std::io::Error::from)?;
-    Ok(())
+/// Writer which computes the SHA-256 hash of everything written into the `inner` writer.
+struct HashingWriter<'a, W: Write> {
+    inner: &'a mut W,
+    hasher: Sha256,
 }
 
-/// Create classification archive for an arbitrary "map" of `string -> color set`.
-pub fn build_classification_archive(
-    categories: HashMap<String, GraphColors>,
-    archive_name: &str,
-    original_model_str: &str,
-) -> Result<(), std::io::Error> {
-    let archive_path = Path::new(archive_name);
-    // If there are some non existing dirs in path, create them.
-    let prefix = archive_path
-        .parent()
-        .ok_or(std::io::Error::new(ErrorKind::Other, "Invalid path."))?;
-    std::fs::create_dir_all(prefix)?;
+impl<W: Write> Write for HashingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
 
-    // Create a zip writer for the desired archive.
-    let archive = File::create(archive_path)?;
-    let mut zip_writer = ZipWriter::new(archive);
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
 
-    for (category_name, category_colors) in categories.iter() {
-        if !category_colors.is_empty() {
-            // If the BDD is not empty, the results go directly into the zip archive.
-            let bdd_file_name = format!("bdd_dump_{}.txt", category_name);
-            zip_writer
-                .start_file(&bdd_file_name, FileOptions::default())
-                .map_err(std::io::Error::from)?;
+/// Entry writer which (if `enabled`) measures the total time spent in writing into the `inner`
+/// writer, i.e., in the compression (and I/O) of the written data.
+struct TimingWriter<'a, E: EntryWriter> {
+    inner: &'a mut E,
+    enabled: bool,
+    elapsed: Duration,
+}
 
-            category_colors.as_bdd().write_as_string(&mut zip_writer)?;
+impl<'a, E: EntryWriter> TimingWriter<'a, E> {
+    fn new(inner: &'a mut E, enabled: bool) -> Self {
+        TimingWriter {
+            inner,
+            enabled,
+            elapsed: Duration::ZERO,
         }
     }
 
-    // Include the original model in the result bundle (we need to load it later).
-    zip_writer
-        .start_file("model.aeon", FileOptions::default())
-        .map_err(std::io::Error::from)?;
-    write!(zip_writer, "{original_model_str}")?;
+    /// Run `action` on the inner writer, adding its duration to the total (if enabled).
+    fn timed<T>(&mut self, action: impl FnOnce(&mut E) -> T) -> T {
+        if !self.enabled {
+            return action(self.inner);
+        }
+        let start = Instant::now();
+        let result = action(self.inner);
+        self.elapsed += start.elapsed();
+        result
+    }
+}
+
+impl<E: EntryWriter> Write for TimingWriter<'_, E> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.timed(|inner| inner.write(buf))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.timed(|inner| inner.flush())
+    }
+}
+
+impl<E: EntryWriter> EntryWriter for TimingWriter<'_, E> {
+    fn start_entry(&mut self, name: &str, options: FileOptions) -> Result<(), ClassifierError> {
+        // Starting an entry also finishes the compression of the previous one.
+        self.timed(|inner| inner.start_entry(name, options))
+    }
+}
+
+/// Write the BDD dumps of the given named color sets into the archive. A set that is equal
+/// to some previously written set is not written again, instead it refers to the dump of that set.
+/// Also writes `.dot` versions of the dumps if the `dot_context` is given. The names, format and
+/// compression of the dumps are given by the `options`, with the modification time `timestamp`.
+///
+/// If `progress` is given, it is called as `progress(sets_done, sets_total)` after each set.
+/// If `checksums` are given, the SHA-256 hash of each BDD dump is added to them as
+/// a `(file, hex digest)` pair.
+///
+/// With the `parallel` feature, the dumps are serialized (and compressed by the dictionary)
+/// by multiple threads in advance (see [prepare_bdd_dumps]), and only the finished buffers are
+/// written into the archive sequentially.
+///
+/// Return the index which maps each category name to the file with its BDD dump.
+fn write_bdd_dumps<E: EntryWriter>(
+    entries: &mut E,
+    named_sets: &[(String, &GraphColors)],
+    options: &OutputOptions,
+    timestamp: SystemTime,
+    dot_context: Option<&SymbolicContext>,
+    progress: Option<&dyn Fn(usize, usize)>,
+    mut checksums: Option<&mut Vec<(String, String)>>,
+) -> Result<Vec<(String, String)>, ClassifierError> {
+    let layout = &options.layout;
+    let file_options = options.bdd_compression.file_options(timestamp);
+    let dictionary = if options.bdd_dictionary {
+        train_bdd_dictionary(named_sets, options.binary_bdds)?
+    } else {
+        None
+    };
+    let mut compressor = match &dictionary {
+        Some(dictionary) => {
+            entries.start_entry(&layout.entry(&layout.bdd_dictionary), file_options)?;
+            entries.write_all(&dictionary.dictionary)?;
+            let level = options.bdd_compression.level.unwrap_or(0);
+            Some(zstd::bulk::Compressor::with_dictionary(
+                level,
+                &dictionary.dictionary,
+            )?)
+        }
+        None => None,
+    };
+    let duplicate_of = find_duplicate_sets(named_sets);
+    let mut prepared = if cfg!(feature = "parallel") {
+        prepare_bdd_dumps(
+            named_sets,
+            &duplicate_of,
+            dictionary.as_ref(),
+            options.bdd_compression.level.unwrap_or(0),
+            options.binary_bdds,
+            dot_context,
+        )?
+    } else {
+        Vec::new()
+    };
+    let mut index: Vec<(String, String)> = Vec::with_capacity(named_sets.len());
+    for (i, (name, colors)) in named_sets.iter().enumerate() {
+        if let Some(original) = duplicate_of[i] {
+            let file = index[original].1.clone();
+            index.push((name.clone(), file));
+        } else {
+            let mut bdd_file_name = layout.bdd_dump_entry(name, options.binary_bdds);
+            if dictionary.is_some() {
+                // The dumps are already compressed, so the zip compression is not used.
+                bdd_file_name.push_str(".zst");
+            }
+            match (prepared.get_mut(i).and_then(Option::take), &mut compressor) {
+                (Some(dump), _) => {
+                    let dump_options = if dictionary.is_some() {
+                        file_options.compression_method(CompressionMethod::Stored)
+                    } else {
+                        file_options
+                    };
+                    write_bytes_entry(
+                        entries,
+                        &bdd_file_name,
+                        dump_options,
+                        &dump.data,
+                        checksums.as_deref_mut(),
+                    )?;
+                    if let Some(dot) = dump.dot {
+                        entries.start_entry(&layout.dot_dump_entry(name), file_options)?;
+                        entries.write_all(dot.as_bytes())?;
+                    }
+                }
+                (None, Some(compressor)) => {
+                    let dictionary = dictionary.as_ref().unwrap();
+                    write_bytes_entry(
+                        entries,
+                        &bdd_file_name,
+                        file_options.compression_method(CompressionMethod::Stored),
+                        &compressor.compress(&dictionary.dumps[i])?,
+                        checksums.as_deref_mut(),
+                    )?;
+                }
+                (None, None) => write_bdd_entry(
+                    entries,
+                    &bdd_file_name,
+                    file_options,
+                    colors.as_bdd(),
+                    options.binary_bdds,
+                    checksums.as_deref_mut(),
+                )?,
+            }
+
+            // The prepared dumps already include their `.dot` versions.
+            if let (true, Some(context)) = (prepared.is_empty(), dot_context) {
+                entries.start_entry(&layout.dot_dump_entry(name), file_options)?;
+                write!(entries, "{}", colors.to_dot_string(context))?;
+            }
+
+            index.push((name.clone(), bdd_file_name));
+        }
+        if let Some(progress) = progress {
+            progress(index.len(), named_sets.len());
+        }
+    }
+    Ok(index)
+}
+
+/// For each of the `named_sets`, find the index of the first previous set that is equal to it
+/// (or `None` if there is no such set, i.e., the set has its own BDD dump).
+///
+/// Equal sets have the same cardinality and BDD size, so only the sets with the same such
+/// signature are compared (i.e., the categories of a partition are only compared when they
+/// have the same size, instead of all pairs of categories).
+fn find_duplicate_sets(named_sets: &[(String, &GraphColors)]) -> Vec<Option<usize>> {
+    let mut originals: HashMap<(u64, usize), Vec<usize>> = HashMap::new();
+    let mut duplicate_of = Vec::with_capacity(named_sets.len());
+    for (i, (_, colors)) in named_sets.iter().enumerate() {
+        let signature = (
+            colors.approx_cardinality().to_bits(),
+            colors.as_bdd().size(),
+        );
+        let candidates = originals.entry(signature).or_default();
+        let original = candidates
+            .iter()
+            .copied()
+            .find(|j| colors_equal(named_sets[*j].1, *colors));
+        if original.is_none() {
+            candidates.push(i);
+        }
+        duplicate_of.push(original);
+    }
+    duplicate_of
+}
+
+/// BDD dump of a set (and its `.dot` version) prepared in advance by [prepare_bdd_dumps].
+struct PreparedDump {
+    /// The serialized (and possibly compressed) BDD.
+    data: Vec<u8>,
+    /// The `.dot` version of the BDD, if requested.
+    dot: Option<String>,
+}
+
+/// Serialize the BDDs of the `named_sets` that are not duplicates of previous sets (see
+/// [find_duplicate_sets]) into memory, using multiple threads if the `parallel` feature is
+/// enabled. If the `dictionary` is given, its dumps are compressed with the given zstd `level`
+/// instead. If the `dot_context` is given, the `.dot` versions of the BDDs are prepared as well.
+///
+/// The result contains the prepared dump of each unique set, and `None` for the duplicates.
+fn prepare_bdd_dumps(
+    named_sets: &[(String, &GraphColors)],
+    duplicate_of: &[Option<usize>],
+    dictionary: Option<&BddDictionary>,
+    level: i32,
+    binary: bool,
+    dot_context: Option<&SymbolicContext>,
+) -> Result<Vec<Option<PreparedDump>>, ClassifierError> {
+    let prepare = |i: usize| -> Result<Option<PreparedDump>, ClassifierError> {
+        if duplicate_of[i].is_some() {
+            return Ok(None);
+        }
+        let colors = named_sets[i].1;
+        let data = match dictionary {
+            Some(dictionary) => {
+                zstd::bulk::Compressor::with_dictionary(level, &dictionary.dictionary)?
+                    .compress(&dictionary.dumps[i])?
+            }
+            None => serialize_bdd(colors.as_bdd(), binary)?,
+        };
+        let dot = dot_context.map(|context| colors.to_dot_string(context));
+        Ok(Some(PreparedDump { data, dot }))
+    };
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        (0..named_sets.len()).into_par_iter().map(prepare).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        (0..named_sets.len()).map(prepare).collect()
+    }
+}
+
+/// Maximal size of the zstd dictionary of the BDD dumps (see [OutputOptions::bdd_dictionary]).
+const BDD_DICTIONARY_SIZE: usize = 16 * 1024;
+
+/// Zstd dictionary of the BDD dumps (see [OutputOptions::bdd_dictionary]).
+struct BddDictionary {
+    /// The trained dictionary.
+    dictionary: Vec<u8>,
+    /// The serialized (and not yet compressed) BDD dump of each set.
+    dumps: Vec<Vec<u8>>,
+}
+
+/// Serialize the BDDs of the `named_sets` (in the `binary` or the text format) and train a zstd
+/// dictionary on them. Returns `None` if the dictionary cannot be trained (e.g., for too few
+/// or too small dumps).
+fn train_bdd_dictionary(
+    named_sets: &[(String, &GraphColors)],
+    binary: bool,
+) -> Result<Option<BddDictionary>, ClassifierError> {
+    let serialize = |(_, colors): &(String, &GraphColors)| serialize_bdd(colors.as_bdd(), binary);
+    #[cfg(feature = "parallel")]
+    let dumps: Vec<Vec<u8>> = {
+        use rayon::prelude::*;
+        named_sets
+            .par_iter()
+            .map(serialize)
+            .collect::<Result<_, _>>()?
+    };
+    #[cfg(not(feature = "parallel"))]
+    let dumps: Vec<Vec<u8>> = named_sets.iter().map(serialize).collect::<Result<_, _>>()?;
+    Ok(zstd::dict::from_samples(&dumps, BDD_DICTIONARY_SIZE)
+        .ok()
+        .map(|dictionary| BddDictionary { dictionary, dumps }))
+}
 
-    zip_writer.finish().map_err(std::io::Error::from)?;
+/// Write the `data` into a new entry `file_name` with the `file_options`. If `checksums` are
+/// given, the SHA-256 hash of the `data` is added to them.
+fn write_bytes_entry<E: EntryWriter>(
+    entries: &mut E,
+    file_name: &str,
+    file_options: FileOptions,
+    data: &[u8],
+    checksums: Option<&mut Vec<(String, String)>>,
+) -> Result<(), ClassifierError> {
+    entries.start_entry(file_name, file_options)?;
+    entries.write_all(data)?;
+    if let Some(checksums) = checksums {
+        checksums.push((file_name.to_string(), format!("{:x}", Sha256::digest(data))));
+    }
     Ok(())
 }
 
-/// Write a short summary regarding the classification computation where the assertions were
-/// not satisfied.
-pub fn write_empty_report(
-    assertion_formulae: &[String],
-    archive_name: &str,
-) -> Result<(), std::io::Error> {
-    let archive_path = Path::new(archive_name);
-    let archive = File::create(archive_path)?;
-    let mut zip_writer = ZipWriter::new(archive);
+/// Write the `bdd` (see [write_bdd]) into a new entry `file_name` with the `file_options`.
+/// If `checksums` are given, the SHA-256 hash of the dump is added to them.
+fn write_bdd_entry<E: EntryWriter>(
+    entries: &mut E,
+    file_name: &str,
+    file_options: FileOptions,
+    bdd: &Bdd,
+    binary: bool,
+    checksums: Option<&mut Vec<(String, String)>>,
+) -> Result<(), ClassifierError> {
+    entries.start_entry(file_name, file_options)?;
+    if let Some(checksums) = checksums {
+        let mut writer = HashingWriter {
+            inner: entries,
+            hasher: Sha256::new(),
+        };
+        write_bdd(&mut writer, bdd, binary)?;
+        let digest = format!("{:x}", writer.hasher.finalize());
+        checksums.push((file_name.to_string(), digest));
+    } else {
+        write_bdd(entries, bdd, binary)?;
+    }
+    Ok(())
+}
 
-    // Here, we can write the empty report directly because there is nothing else to compute.
-    zip_writer
-        .start_file("report.txt", FileOptions::default())
-        .map_err(std::io::Error::from)?;
+/// Write the `bdd` in the `binary` format of [Bdd::write_as_bytes], or in the text format of
+/// [Bdd::write_as_string].
+fn write_bdd<W: Write>(writer: &mut W, bdd: &Bdd, binary: bool) -> Result<(), std::io::Error> {
+    if binary {
+        bdd.write_as_bytes(writer)
+    } else {
+        bdd.write_as_string(writer)
+    }
+}
 
-    writeln!(zip_writer, "### Assertion formulae")?;
-    writeln!(zip_writer)?;
-    for assertion in assertion_formulae {
-        writeln!(zip_writer, "# {assertion}")?;
+/// Serialize the `bdd` into a new buffer (see [write_bdd]).
+fn serialize_bdd(bdd: &Bdd, binary: bool) -> Result<Vec<u8>, std::io::Error> {
+    let mut data = Vec::new();
+    write_bdd(&mut data, bdd, binary)?;
+    Ok(data)
+}
+
+/// Write the `bdd_index.txt` entry which maps each category name to the file with its BDD
+/// dump, one `name<TAB>file` pair per line. The categories in `relative` are dumped relative
+/// to the valid colors (see [OutputOptions::relative_bdds]), which is marked by an extra
+/// `<TAB>relative` column.
+fn write_bdd_index<E: EntryWriter>(
+    entries: &mut E,
+    layout: &ArchiveLayout,
+    index: &[(String, String)],
+    relative: &HashSet<String>,
+    file_options: FileOptions,
+) -> Result<(), ClassifierError> {
+    entries.start_entry(&layout.entry(&layout.bdd_index), file_options)?;
+    for (name, file) in index {
+        if relative.contains(name) {
+            writeln!(entries, "{name}\t{file}\trelative")?;
+        } else {
+            writeln!(entries, "{name}\t{file}")?;
+        }
     }
-    writeln!(zip_writer, "0 colors satisfy combination of all assertions")?;
-    writeln!(zip_writer)?;
+    Ok(())
+}
 
-    zip_writer.finish().map_err(std::io::Error::from)?;
+/// Write the `checksums.txt` entry with the SHA-256 hashes of the BDD dumps, in the format
+/// of `sha256sum` (i.e., one `digest<SPACE><SPACE>file` pair per line).
+fn write_checksums<E: EntryWriter>(
+    entries: &mut E,
+    layout: &ArchiveLayout,
+    checksums: &[(String, String)],
+    file_options: FileOptions,
+) -> Result<(), ClassifierError> {
+    entries.start_entry(&layout.entry(&layout.checksums), file_options)?;
+    for (file, digest) in checksums {
+        writeln!(entries, "{digest}  {file}")?;
+    }
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::write_output::{bool_vec_to_string, int_to_bool_vec};
+/// Cardinalities from which the `scientific` notation is used by [format_cardinality].
+const SCIENTIFIC_THRESHOLD: f64 = 1e6;
 
-    #[test]
-    fn test_int_to_bool_vec() {
-        let expected_vec = vec![false, false, false];
-        assert_eq!(int_to_bool_vec(0, 3), expected_vec);
+/// Format the `approx` cardinality, followed by the `exact` one if the two differ (i.e., once
+/// the approximation loses precision). If `scientific` is set, large cardinalities are
+/// approximated in the scientific notation (e.g., `3.40e38`), which is always followed by the
+/// exact count.
+fn format_cardinality(approx: f64, exact: &BigInt, scientific: bool) -> String {
+    let approx_str = if scientific && approx >= SCIENTIFIC_THRESHOLD {
+        format!("{approx:.2e}")
+    } else {
+        format!("{approx:.0}")
+    };
+    if approx_str == exact.to_string() {
+        approx_str
+    } else {
+        format!("{approx_str} (exactly {exact})")
+    }
+}
+
+/// Format of the cardinalities in the text reports.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum CardinalityFormat {
+    /// The approximate count as an integer (e.g., `1152921504606846976`), followed by the exact
+    /// count once the approximation loses precision.
+    #[default]
+    Fixed,
+    /// Same as [CardinalityFormat::Fixed], but the large counts (from a million colors) are
+    /// approximated in the scientific notation (e.g., `3.40e38`), followed by the exact count.
+    Scientific,
+    /// Only the exact count (e.g., `340282366920938463463374607431768211456`).
+    Exact,
+    /// The exact count with the digits grouped by thousands (e.g., `1,048,576`).
+    Grouped,
+    /// Custom rendering of the approximate and the exact count.
+    Custom(fn(f64, &BigInt) -> String),
+}
+
+impl CardinalityFormat {
+    /// Format the cardinality given by its `approx`imate and `exact` count.
+    pub fn format(&self, approx: f64, exact: &BigInt) -> String {
+        match self {
+            CardinalityFormat::Fixed => format_cardinality(approx, exact, false),
+            CardinalityFormat::Scientific => format_cardinality(approx, exact, true),
+            CardinalityFormat::Exact => exact.to_string(),
+            CardinalityFormat::Grouped => {
+                let digits = exact.magnitude().to_string();
+                let mut grouped = String::new();
+                for (i, digit) in digits.chars().enumerate() {
+                    if i > 0 && (digits.len() - i) % 3 == 0 {
+                        grouped.push(',');
+                    }
+                    grouped.push(digit);
+                }
+                if exact.sign() == Sign::Minus {
+                    grouped.insert(0, '-');
+                }
+                grouped
+            }
+            CardinalityFormat::Custom(format) => format(approx, exact),
+        }
+    }
+}
 
-        let expected_vec = vec![false, true];
-        assert_eq!(int_to_bool_vec(1, 2), expected_vec);
+/// Prepare the machine-readable (JSON) version of the report. Unlike the text report, it only
+/// lists the non-empty categories together with the names of their BDD dumps (as given
+/// by the `bdd_index`), and it lists the variables of each property formula (see
+/// [read_formula_variables]).
+fn prepare_report_json(
+    result: &ClassificationResult,
+    bdd_index: &[(String, String)],
+    labels: ClassLabels,
+) -> serde_json::Value {
+    let bdd_files: HashMap<&str, &str> = bdd_index
+        .iter()
+        .map(|(name, file)| (name.as_str(), file.as_str()))
+        .collect();
+    let property_json = |property: &PropertyResult| {
+        // Formulae that cannot be parsed (which cannot happen for model-checked properties)
+        // simply have no variables listed.
+        let variables = read_formula_variables(&property.formula).ok();
+        json!({
+            "name": property.name,
+            "formula": property.formula,
+            "network_variables": variables.as_ref().map(|it| &it.network_variables),
+            "hctl_variables": variables.as_ref().map(|it| &it.hctl_variables),
+            "cardinality": property.cardinality,
+            "exact_cardinality": property.exact_cardinality.to_string(),
+            "discriminating": property.discriminating,
+        })
+    };
+    let properties: Vec<serde_json::Value> = result.properties.iter().map(property_json).collect();
+    let projected_properties: Vec<serde_json::Value> = result
+        .projected_properties
+        .iter()
+        .map(property_json)
+        .collect();
+    let relations: Vec<serde_json::Value> = property_relations(result)
+        .into_iter()
+        .map(|relation| {
+            let (kind, i, j) = match relation {
+                PropertyRelation::NeverBothTrue(i, j) => ("never_both_true", i, j),
+                PropertyRelation::NeverBothFalse(i, j) => ("never_both_false", i, j),
+            };
+            let names = [&result.properties[i].name, &result.properties[j].name];
+            json!({ "kind": kind, "properties": names })
+        })
+        .collect();
+    let classes: Vec<serde_json::Value> = result
+        .categories
+        .iter()
+        .filter(|category| !category.is_empty())
+        .map(|category| {
+            let label = labels.label(&category.validity);
+            json!({
+                "bdd_file": bdd_files.get(label.as_str()),
+                "class_bits": labels.bit_order.label(&category.validity),
+                "label": label,
+                "cardinality": category.cardinality,
+                "bdd_size": category.colors.as_bdd().size(),
+                "exact_cardinality": category.exact_cardinality.to_string(),
+            })
+        })
+        .collect();
+    json!({
+        "assertion_formulae": result.assertion_formulae,
+        "valid_cardinality": result.valid_cardinality,
+        "valid_exact_cardinality": result.valid_exact_cardinality.to_string(),
+        "num_classes": result.num_classes(),
+        "classified_cardinality": result.classified_cardinality(),
+        "classified_exact_cardinality": result.classified_exact_cardinality().to_string(),
+        "unclassified_cardinality": result.unclassified_cardinality().0,
+        "bit_order": labels.bit_order.name(),
+        "properties": properties,
+        "projected_properties": projected_properties,
+        "relations": relations,
+        "classes": classes,
+    })
+}
 
-        let expected_vec = vec![false, false, false, true];
-        assert_eq!(int_to_bool_vec(1, 4), expected_vec);
+/// How the BDDs of the classes are included in the report of [write_report_json_gz].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JsonBdds {
+    /// The BDDs are not included at all.
+    #[default]
+    Omitted,
+    /// Every class contains a `bdd` field with its BDD in the binary format of
+    /// [Bdd::write_as_bytes], encoded in (standard, padded) base64.
+    Base64,
+    /// The BDDs are written in the binary format of [Bdd::write_as_bytes] into separate files
+    /// (named by [ArchiveLayout::binary_bdd_dump]) in the `<name>.bdds` directory next to the
+    /// report `<name>.json.gz`, and every class contains a `bdd_file` field with the path of its
+    /// file relative to the directory of the report.
+    External,
+}
 
-        let expected_vec = vec![false, false, true, false];
-        assert_eq!(int_to_bool_vec(2, 4), expected_vec);
+/// Write the JSON version of the report (see [prepare_report_json]) as a single gzip-compressed
+/// file `path` (e.g., `result.json.gz`) instead of a zip archive, with the BDDs of the classes
+/// included according to `bdds`. The file (and the external BDD files) are created according
+/// to the [OutputOptions::overwrite] and [OutputOptions::retry], the class labels are given in
+/// the [OutputOptions::bit_order], and the other options are not used. If anything fails, the
+/// half-written report is removed.
+pub fn write_report_json_gz(
+    result: &ClassificationResult,
+    path: &str,
+    bdds: JsonBdds,
+    options: &OutputOptions,
+) -> Result<(), ClassifierError> {
+    let report_path = Path::new(path);
+    // The report is created first, so that nothing is written if it cannot be (re)placed.
+    let file = create_output_file(report_path, options.overwrite, options.retry)?;
+    let written = write_json_gz_entries(result, report_path, file, bdds, options);
+    if written.is_err() {
+        // The original error is more important than a failure of the clean-up.
+        let _ = std::fs::remove_file(report_path);
+    }
+    written
+}
 
-        let expected_vec = vec![true, true, true, true];
-        assert_eq!(int_to_bool_vec(15, 4), expected_vec);
+/// Write the (external BDDs and the) report of [write_report_json_gz] into the `file` created
+/// for the report at `report_path`.
+fn write_json_gz_entries(
+    result: &ClassificationResult,
+    report_path: &Path,
+    file: File,
+    bdds: JsonBdds,
+    options: &OutputOptions,
+) -> Result<(), ClassifierError> {
+    let labels = ClassLabels::from(options.bit_order);
+    let non_empty: Vec<&Category> = result.non_empty_categories().collect();
+    let mut bdd_index = Vec::new();
+    if bdds == JsonBdds::External {
+        let file_name = report_path
+            .file_name()
+            .map_or("report".into(), |name| name.to_string_lossy());
+        let stem = file_name
+            .strip_suffix(".json.gz")
+            .or_else(|| file_name.strip_suffix(".gz"))
+            .unwrap_or(&file_name);
+        let directory = format!("{stem}.bdds");
+        for category in &non_empty {
+            let label = labels.label(&category.validity);
+            let dump = options
+                .layout
+                .binary_bdd_dump
+                .replace("{name}", dump_name(&label));
+            let dump_path = report_path.with_file_name(&directory).join(&dump);
+            let mut dump_file = create_output_file(&dump_path, options.overwrite, options.retry)?;
+            write_bdd(&mut dump_file, category.colors.as_bdd(), true)?;
+            bdd_index.push((label, format!("{directory}/{dump}")));
+        }
     }
 
-    #[test]
-    fn test_bool_vec_to_string() {
-        assert_eq!(bool_vec_to_string(&[]), "".to_string());
-        assert_eq!(bool_vec_to_string(&[true, false]), "10".to_string());
-        assert_eq!(bool_vec_to_string(&[true, true, false]), "110".to_string());
+    let mut report = prepare_report_json(result, &bdd_index, labels);
+    if let Some(classes) = report["classes"].as_array_mut() {
+        for (class, category) in classes.iter_mut().zip(non_empty) {
+            match bdds {
+                JsonBdds::Omitted => {}
+                JsonBdds::Base64 => {
+                    let data = serialize_bdd(category.colors.as_bdd(), true)?;
+                    class["bdd"] = json!(Base64::encode_string(&data));
+                }
+                JsonBdds::External => continue,
+            }
+            // There are no BDD files to refer to.
+            if let Some(class) = class.as_object_mut() {
+                class.remove("bdd_file");
+            }
+        }
+    }
+
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    serde_json::to_writer(&mut encoder, &report).map_err(std::io::Error::from)?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// Quote a CSV field if it contains a separator, a quote or a line break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Write the table of non-empty categories in the CSV format. There is one boolean column
+/// for each property (named after the property), followed by the `cardinality` and
+/// `bdd_file` columns (the BDD dumps are given by the `bdd_index`, w.r.t. the class `labels`).
+fn write_classes_csv<W: Write>(
+    writer: &mut W,
+    result: &ClassificationResult,
+    bdd_index: &[(String, String)],
+    labels: ClassLabels,
+) -> Result<(), std::io::Error> {
+    let bdd_files: HashMap<&str, &str> = bdd_index
+        .iter()
+        .map(|(name, file)| (name.as_str(), file.as_str()))
+        .collect();
+
+    let mut header: Vec<String> = result
+        .properties
+        .iter()
+        .map(|property| csv_field(&property.name))
+        .collect();
+    header.push("cardinality".to_string());
+    header.push("bdd_file".to_string());
+    writeln!(writer, "{}", header.join(","))?;
+
+    for category in &result.categories {
+        if category.is_empty() {
+            continue;
+        }
+        let mut row: Vec<String> = category.validity.iter().map(|it| it.to_string()).collect();
+        row.push(format!("{:.0}", category.cardinality));
+        let label = labels.label(&category.validity);
+        row.push(csv_field(bdd_files.get(label.as_str()).unwrap_or(&"")));
+        writeln!(writer, "{}", row.join(","))?;
+    }
+    Ok(())
+}
+
+/// Write the matrix of the pairwise property `overlaps` (see [property_overlaps]) in the CSV
+/// format. The first column and the header contain the names of the properties.
+fn write_overlaps_csv<W: Write>(
+    writer: &mut W,
+    result: &ClassificationResult,
+    overlaps: &[Vec<BigInt>],
+) -> Result<(), std::io::Error> {
+    let names: Vec<String> = result
+        .properties
+        .iter()
+        .map(|property| csv_field(&property.name))
+        .collect();
+    writeln!(writer, "property,{}", names.join(","))?;
+    for (name, row) in names.iter().zip(overlaps) {
+        let row: Vec<String> = row.iter().map(|it| it.to_string()).collect();
+        writeln!(writer, "{name},{}", row.join(","))?;
+    }
+    Ok(())
+}
+
+/// Human-readable description of a category with the given `validity` of `properties`, such as
+/// `p1 ∧ ¬p2 ∧ p3`.
+fn class_description<S>(properties: &[PropertyResult<S>], validity: &[bool]) -> String {
+    if properties.is_empty() {
+        return "true".to_string();
+    }
+    let literals: Vec<String> = properties
+        .iter()
+        .zip(validity)
+        .map(|(property, valid)| {
+            if *valid {
+                property.name.clone()
+            } else {
+                format!("¬{}", property.name)
+            }
+        })
+        .collect();
+    literals.join(" ∧ ")
+}
+
+/// Write the legend explaining the class names: the property corresponding to each bit
+/// position of the name (w.r.t. the bit order of the `labels`), and the human-readable
+/// description of each non-empty class.
+fn write_classes_legend<W: Write>(
+    writer: &mut W,
+    result: &ClassificationResult,
+    labels: ClassLabels,
+) -> Result<(), std::io::Error> {
+    writeln!(writer, "### Bit positions (from the left)")?;
+    writeln!(writer)?;
+    let mut positions: Vec<&PropertyResult> = result.properties.iter().collect();
+    if labels.bit_order == BitOrder::LsbFirst {
+        positions.reverse();
+    }
+    if positions.is_empty() {
+        writeln!(
+            writer,
+            "No properties, the only class covers {ALL_COLORS_LABEL}."
+        )?;
+    }
+    for (i, property) in positions.into_iter().enumerate() {
+        writeln!(writer, "{}: {}", i + 1, property.name)?;
+    }
+    writeln!(writer)?;
+
+    writeln!(writer, "### Classes")?;
+    writeln!(writer)?;
+    for category in &result.categories {
+        if category.is_empty() {
+            continue;
+        }
+        writeln!(
+            writer,
+            "{}: {}",
+            labels.title(&category.validity),
+            class_description(&result.properties, &category.validity)
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the metadata regarding the classification run. The first line contains the number of
+/// HCTL variables, the remaining lines are `key=value` pairs with the crate version, the
+/// `timestamp` of writing (in UTC), the optional `source_fingerprint` of the input (as `source`,
+/// see [source_fingerprint]), the number of HCTL variables of each formula given in
+/// `formula_hctl_vars` (as `hctl_vars.<name>=<count>`), the numbers of BDD nodes given in
+/// `bdd_nodes` (as `bdd_nodes.<name>=<count>`), and the `bit_order` of the class labels.
+fn write_metadata<W: Write>(
+    writer: &mut W,
+    num_hctl_vars: usize,
+    formula_hctl_vars: &[(String, usize)],
+    bdd_nodes: &[(&str, usize)],
+    bit_order: BitOrder,
+    timestamp: SystemTime,
+    source_fingerprint: Option<&str>,
+) -> Result<(), std::io::Error> {
+    let timestamp = OffsetDateTime::from(timestamp)
+        .replace_nanosecond(0)
+        .map_err(|e| std::io::Error::new(ErrorKind::Other, e))?
+        .format(&Rfc3339)
+        .map_err(|e| std::io::Error::new(ErrorKind::Other, e))?;
+    writeln!(writer, "{num_hctl_vars}")?;
+    writeln!(writer, "version={}", env!("CARGO_PKG_VERSION"))?;
+    writeln!(writer, "timestamp={timestamp}")?;
+    if let Some(fingerprint) = source_fingerprint {
+        writeln!(writer, "source={fingerprint}")?;
+    }
+    for (name, count) in formula_hctl_vars {
+        writeln!(writer, "hctl_vars.{name}={count}")?;
+    }
+    for (name, count) in bdd_nodes {
+        writeln!(writer, "bdd_nodes.{name}={count}")?;
+    }
+    writeln!(writer, "bit_order={}", bit_order.name())?;
+    Ok(())
+}
+
+/// Version of the format of the property file, see [write_property_file].
+pub const PROPERTY_FILE_VERSION: u32 = 1;
+
+/// Write the assertion and the property formulae of the `result` as a property file, which can
+/// be read back by [crate::load_inputs::read_property_file].
+///
+/// The first line gives the version of the format (`#! property_file_version: 1`, see
+/// [PROPERTY_FILE_VERSION]), followed by the formulae as the annotations of an annotated
+/// model (``#! dynamic_assertion: #`...`#`` and ``#! dynamic_property: name: #`...`#``), one
+/// per line and in the order of the properties in the result. As such, the file can also be given
+/// to [crate::classification::run_classification] together with the model.
+fn write_property_file<W: Write>(
+    writer: &mut W,
+    result: &ClassificationResult,
+) -> Result<(), std::io::Error> {
+    writeln!(writer, "#! property_file_version: {PROPERTY_FILE_VERSION}")?;
+    for assertion in &result.assertion_formulae {
+        writeln!(writer, "#! dynamic_assertion: #`{assertion}`#")?;
+    }
+    for property in &result.properties {
+        writeln!(
+            writer,
+            "#! dynamic_property: {}: #`{}`#",
+            property.name, property.formula
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the header of the report section with the list of assertion formulae.
+fn write_assertion_section<W: Write>(
+    writer: &mut W,
+    assertion_formulae: &[String],
+) -> Result<(), std::io::Error> {
+    writeln!(writer, "### Assertion formulae")?;
+    writeln!(writer)?;
+    for assertion in assertion_formulae {
+        writeln!(writer, "# {assertion}")?;
+    }
+    Ok(())
+}
+
+/// Human-readable description of the `relation` of two properties of the `result`.
+fn relation_description<S>(result: &ClassificationResult<S>, relation: PropertyRelation) -> String {
+    let name = |i: usize| result.properties[i].name.as_str();
+    match relation {
+        PropertyRelation::NeverBothTrue(i, j) => {
+            format!("{} and {} never hold together", name(i), name(j))
+        }
+        PropertyRelation::NeverBothFalse(i, j) => {
+            format!("{} or {} always holds", name(i), name(j))
+        }
+    }
+}
+
+/// Write the initial part of the report: the headline number of (non-empty) classes (and of the
+/// valid colors outside of all classes), and the results for assertion formulae and for
+/// individual property formulae. The cardinalities are shown in the given `format`. For a result
+/// without the colors of the classes (see [ClassificationResult::cardinalities_only]), this is
+/// noted in the headline.
+fn write_report_intro<W: Write, S: ClassifiedSet>(
+    report: &mut W,
+    result: &ClassificationResult<S>,
+    format: CardinalityFormat,
+) -> Result<(), std::io::Error> {
+    writeln!(
+        report,
+        "{} non-empty classes covering {} {}",
+        result.num_classes(),
+        format.format(
+            result.classified_cardinality(),
+            &result.classified_exact_cardinality()
+        ),
+        S::ELEMENTS
+    )?;
+    let (unclassified, unclassified_exact) = result.unclassified_cardinality();
+    writeln!(
+        report,
+        "{}{} valid {} are not covered by any class",
+        if unclassified_exact.is_zero() {
+            ""
+        } else {
+            "WARNING: "
+        },
+        format.format(unclassified, &unclassified_exact),
+        S::ELEMENTS
+    )?;
+    if result.cardinalities_only {
+        writeln!(
+            report,
+            "Only the cardinalities of the classes were computed (without their BDDs)"
+        )?;
+    }
+    writeln!(report)?;
+    write_assertion_section(report, &result.assertion_formulae)?;
+    write!(
+        report,
+        "{} {} satisfy all assertions",
+        format.format(result.valid_cardinality, &result.valid_exact_cardinality),
+        S::ELEMENTS
+    )?;
+    match result.total_cardinality {
+        Some(total) => writeln!(
+            report,
+            " ({:.1}% of all parameterizations)",
+            percentage(result.valid_cardinality, total)
+        )?,
+        None => writeln!(report)?,
+    }
+    writeln!(report)?;
+
+    // Write results for each property.
+    writeln!(report, "### Property formulae individually")?;
+    writeln!(report)?;
+    if result.properties.is_empty() {
+        writeln!(
+            report,
+            "No properties, all valid {} form a single class.",
+            S::ELEMENTS
+        )?;
+        writeln!(report)?;
+    }
+    for property in &result.properties {
+        writeln!(report, "# {}  |  {}", property.name, property.formula)?;
+        let cardinality = format.format(property.cardinality, &property.exact_cardinality);
+        let percent = percentage(property.cardinality, result.valid_cardinality);
+        writeln!(
+            report,
+            "{cardinality} {} satisfy this property ({percent:.1}%)",
+            S::ELEMENTS
+        )?;
+        if !property.discriminating {
+            let holds_for = if property.colors.intersect(&result.valid_colors).is_empty() {
+                "no"
+            } else {
+                "all"
+            };
+            writeln!(
+                report,
+                "WARNING: This property is non-discriminating (it holds for {holds_for} {})",
+                S::ELEMENTS
+            )?;
+        }
+        writeln!(report)?;
+    }
+
+    if !result.projected_properties.is_empty() {
+        writeln!(report, "### Property formulae projected out")?;
+        writeln!(report)?;
+        for property in &result.projected_properties {
+            writeln!(report, "# {}  |  {}", property.name, property.formula)?;
+            let cardinality = format.format(property.cardinality, &property.exact_cardinality);
+            let percent = percentage(property.cardinality, result.valid_cardinality);
+            writeln!(
+                report,
+                "{cardinality} {} satisfy this property ({percent:.1}%)",
+                S::ELEMENTS
+            )?;
+            writeln!(report)?;
+        }
+    }
+
+    let relations = property_relations(result);
+    if !relations.is_empty() {
+        writeln!(report, "### Property relations")?;
+        writeln!(report)?;
+        for relation in relations {
+            writeln!(report, "{}", relation_description(result, relation))?;
+        }
+        writeln!(report)?;
+    }
+
+    let implications = property_implications(result);
+    if !implications.is_empty() {
+        writeln!(report, "### Property implications")?;
+        writeln!(report)?;
+        for (i, j) in implications {
+            let (premise, conclusion) = (&result.properties[i].name, &result.properties[j].name);
+            writeln!(report, "{premise} => {conclusion}")?;
+        }
+        writeln!(report)?;
+    }
+
+    // Output info regarding the classification.
+    writeln!(report, "### Classes")?;
+    writeln!(report)?;
+
+    Ok(())
+}
+
+/// Categories of the `result` in the order in which they are listed in the report.
+///
+/// By default, all categories of the `result` are listed in their original order. If
+/// [OutputOptions::sort_classes_by_size] is set, only the non-empty categories are listed,
+/// starting with the largest one.
+fn report_categories<'a, S: ClassifiedSet>(
+    result: &'a ClassificationResult<S>,
+    options: &OutputOptions,
+) -> Vec<&'a Category<S>> {
+    let mut categories: Vec<&Category<S>> = result.categories.iter().collect();
+    if options.sort_classes_by_size {
+        categories.retain(|category| !category.is_empty());
+        // The sort is stable, so classes of the same size keep their original order.
+        categories.sort_by(|x, y| y.cardinality.total_cmp(&x.cardinality));
+    }
+    categories
+}
+
+/// Describe one representative color of the (non-empty) `colors` by the update functions of
+/// its witness network. Only the variables with (partially) unknown update functions in the
+/// model of the `graph` are listed.
+fn representative_description(graph: &SymbolicAsyncGraph, colors: &GraphColors) -> Vec<String> {
+    let network = graph.as_network();
+    let witness = graph.pick_witness(colors);
+    network
+        .variables()
+        .filter(|var| match network.get_update_function(*var) {
+            Some(function) => !function.collect_parameters().is_empty(),
+            None => true,
+        })
+        .filter_map(|var| {
+            let function = witness.get_update_function(var).as_ref()?;
+            Some(format!(
+                "{} = {}",
+                network.get_variable_name(var),
+                function.to_string(&witness)
+            ))
+        })
+        .collect()
+}
+
+/// Write the part of the report regarding the individual categories (see [report_categories]
+/// for their order), including the size (number of nodes) of the BDD of each non-empty category.
+///
+/// If the `graph` of the model is given, a representative instance of each non-empty category
+/// is described as well (see [representative_description]), as long as
+/// [OutputOptions::representatives] is set. With [OutputOptions::bdd_support], the variables
+/// of the category BDD are listed as well (named by the `graph`, if given).
+///
+/// Categories smaller than [OutputOptions::min_class_cardinality] are not listed individually,
+/// they are aggregated into one `other` entry at the end (which does not count the empty
+/// categories). The categories are named by their `labels`.
+fn write_report_classes<W: Write, S: ClassifiedSet>(
+    report: &mut W,
+    result: &ClassificationResult<S>,
+    options: &OutputOptions,
+    labels: ClassLabels,
+    graph: Option<&SymbolicAsyncGraph>,
+) -> Result<(), std::io::Error> {
+    // The empty categories below the threshold are left out (and not counted as `other`).
+    let (categories, other): (Vec<&Category<S>>, Vec<&Category<S>>) =
+        report_categories(result, options)
+            .into_iter()
+            .filter(|category| {
+                category.cardinality > 0.0 || category.cardinality >= options.min_class_cardinality
+            })
+            .partition(|category| category.cardinality >= options.min_class_cardinality);
+
+    for category in categories {
+        writeln!(report, "# {}", labels.title(&category.validity))?;
+        writeln!(
+            report,
+            "{} {} in this category ({:.1}%{})",
+            options
+                .cardinality_format
+                .format(category.cardinality, &category.exact_cardinality),
+            S::ELEMENTS,
+            percentage(category.cardinality, result.valid_cardinality),
+            total_percentage_note(result, category.cardinality),
+        )?;
+        if !category.colors.is_empty() {
+            let size = category.colors.as_bdd().size();
+            writeln!(report, "BDD of this category has {size} nodes")?;
+        }
+        if options.bdd_support && !category.colors.is_empty() {
+            let variables = graph.map(|graph| graph.symbolic_context().bdd_variable_set());
+            let support: Vec<String> = category
+                .support()
+                .into_iter()
+                .map(|var| match variables {
+                    Some(variables) => variables.name_of(var),
+                    None => format!("x_{var}"),
+                })
+                .collect();
+            writeln!(
+                report,
+                "BDD of this category depends on: {}",
+                support.join(", ")
+            )?;
+        }
+        let representative_graph = graph.filter(|_| options.representatives);
+        if let Some(graph) = representative_graph.filter(|_| !category.colors.is_empty()) {
+            let description = representative_description(graph, &category.colors.colors());
+            if !description.is_empty() {
+                writeln!(report, "Representative instance:")?;
+                for line in description {
+                    writeln!(report, "  {line}")?;
+                }
+            }
+        }
+        writeln!(report)?;
+    }
+
+    if !other.is_empty() {
+        let cardinality: f64 = other.iter().map(|category| category.cardinality).sum();
+        let exact_cardinality: BigInt = other
+            .iter()
+            .map(|category| &category.exact_cardinality)
+            .sum();
+        writeln!(report, "# other")?;
+        writeln!(
+            report,
+            "{} {} in {} other categories ({:.1}%{})",
+            options
+                .cardinality_format
+                .format(cardinality, &exact_cardinality),
+            S::ELEMENTS,
+            other.len(),
+            percentage(cardinality, result.valid_cardinality),
+            total_percentage_note(result, cardinality),
+        )?;
+        writeln!(report)?;
+    }
+    Ok(())
+}
+
+/// The `cardinality` as a fraction of all colors of the symbolic graph (see
+/// [ClassificationResult::total_cardinality]), formatted as a suffix of the percentage in the
+/// text report (empty if the total is unknown).
+fn total_percentage_note<S>(result: &ClassificationResult<S>, cardinality: f64) -> String {
+    match result.total_cardinality {
+        Some(total) => format!(
+            "; {:.1}% of all parameterizations",
+            percentage(cardinality, total)
+        ),
+        None => String::new(),
+    }
+}
+
+/// Write the report section with the absent classes (see [ClassificationResult::absent_classes]),
+/// i.e., the combinations of properties that no valid color satisfies. For too many properties,
+/// the section only notes that the enumeration was skipped.
+fn write_absent_classes<W: Write, S: ClassifiedSet>(
+    report: &mut W,
+    result: &ClassificationResult<S>,
+    labels: ClassLabels,
+) -> Result<(), std::io::Error> {
+    writeln!(report, "### Absent classes")?;
+    writeln!(report)?;
+    match result.absent_classes() {
+        None => writeln!(
+            report,
+            "Absent classes are not enumerated for more than {MAX_ABSENT_CLASS_PROPERTIES} properties."
+        )?,
+        Some(absent) if absent.is_empty() => {
+            writeln!(report, "All combinations of properties occur.")?
+        }
+        Some(absent) => {
+            for validity in absent {
+                writeln!(
+                    report,
+                    "# {}  |  {}",
+                    labels.title(&validity),
+                    class_description(&result.properties, &validity)
+                )?;
+            }
+        }
+    }
+    writeln!(report)?;
+    Ok(())
+}
+
+/// Escape the `|` characters (HCTL disjunction) so that `text` can be used in a Markdown table.
+fn escape_markdown_table_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+/// Escape the special characters of XML, so that `text` can be used in element content as well
+/// as in (quoted) attribute values.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Write the lattice of the non-empty categories in the GraphML format. Each category is a node
+/// (identified by its label, with its bits and cardinality as attributes), and two categories
+/// are connected by an edge if their validity vectors differ in exactly one property (the name
+/// of the property is the attribute of the edge).
+fn write_lattice_graphml<W: Write>(
+    writer: &mut W,
+    result: &ClassificationResult,
+    labels: ClassLabels,
+) -> Result<(), std::io::Error> {
+    let validities: Vec<&Vec<bool>> = result
+        .non_empty_categories()
+        .map(|category| &category.validity)
+        .collect();
+    let present: HashSet<&Vec<bool>> = validities.iter().copied().collect();
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#
+    )?;
+    writeln!(
+        writer,
+        r#"  <key id="bits" for="node" attr.name="bits" attr.type="string"/>"#
+    )?;
+    writeln!(
+        writer,
+        r#"  <key id="cardinality" for="node" attr.name="cardinality" attr.type="double"/>"#
+    )?;
+    writeln!(
+        writer,
+        r#"  <key id="property" for="edge" attr.name="property" attr.type="string"/>"#
+    )?;
+    writeln!(writer, r#"  <graph id="lattice" edgedefault="undirected">"#)?;
+    for category in result.non_empty_categories() {
+        writeln!(
+            writer,
+            r#"    <node id="{}">"#,
+            escape_xml(&labels.label(&category.validity))
+        )?;
+        writeln!(
+            writer,
+            r#"      <data key="bits">{}</data>"#,
+            bool_vec_to_string(&category.validity)
+        )?;
+        writeln!(
+            writer,
+            r#"      <data key="cardinality">{}</data>"#,
+            category.cardinality
+        )?;
+        writeln!(writer, "    </node>")?;
+    }
+    // Each edge is written once, from the category where the property does not hold.
+    for validity in &validities {
+        for (i, property) in result.properties.iter().enumerate() {
+            if validity[i] {
+                continue;
+            }
+            let mut neighbour = (*validity).clone();
+            neighbour[i] = true;
+            if present.contains(&neighbour) {
+                writeln!(
+                    writer,
+                    r#"    <edge source="{}" target="{}">"#,
+                    escape_xml(&labels.label(validity)),
+                    escape_xml(&labels.label(&neighbour))
+                )?;
+                writeln!(
+                    writer,
+                    r#"      <data key="property">{}</data>"#,
+                    escape_xml(&property.name)
+                )?;
+                writeln!(writer, "    </edge>")?;
+            }
+        }
+    }
+    writeln!(writer, "  </graph>")?;
+    writeln!(writer, "</graphml>")?;
+    Ok(())
+}
+
+/// Write the XML version of the report. It mirrors the structure of the JSON report (see
+/// [prepare_report_json]): the `classification` root element contains the `assertions`,
+/// `properties`, `projected_properties` and `classes` (only the non-empty ones) elements,
+/// with all cardinalities and class labels given as attributes.
+fn write_report_xml<W: Write>(
+    writer: &mut W,
+    result: &ClassificationResult,
+    bdd_index: &[(String, String)],
+    labels: ClassLabels,
+) -> Result<(), std::io::Error> {
+    let bdd_files: HashMap<&str, &str> = bdd_index
+        .iter()
+        .map(|(name, file)| (name.as_str(), file.as_str()))
+        .collect();
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        writer,
+        r#"<classification valid_cardinality="{}" valid_exact_cardinality="{}" num_classes="{}" bit_order="{}">"#,
+        result.valid_cardinality,
+        result.valid_exact_cardinality,
+        result.num_classes(),
+        labels.bit_order.name(),
+    )?;
+
+    writeln!(writer, "  <assertions>")?;
+    for assertion in &result.assertion_formulae {
+        writeln!(
+            writer,
+            "    <assertion>{}</assertion>",
+            escape_xml(assertion)
+        )?;
+    }
+    writeln!(writer, "  </assertions>")?;
+
+    let sections = [
+        ("properties", &result.properties),
+        ("projected_properties", &result.projected_properties),
+    ];
+    for (element, properties) in sections {
+        writeln!(writer, "  <{element}>")?;
+        for property in properties {
+            writeln!(
+                writer,
+                r#"    <property name="{}" formula="{}" cardinality="{}" exact_cardinality="{}" discriminating="{}"/>"#,
+                escape_xml(&property.name),
+                escape_xml(&property.formula),
+                property.cardinality,
+                property.exact_cardinality,
+                property.discriminating,
+            )?;
+        }
+        writeln!(writer, "  </{element}>")?;
+    }
+
+    writeln!(writer, "  <classes>")?;
+    for category in &result.categories {
+        if category.is_empty() {
+            continue;
+        }
+        let class_bits = labels.bit_order.label(&category.validity);
+        let label = labels.label(&category.validity);
+        let bdd_file = match bdd_files.get(label.as_str()) {
+            Some(file) => format!(r#" bdd_file="{}""#, escape_xml(file)),
+            None => String::new(),
+        };
+        writeln!(
+            writer,
+            r#"    <class class_bits="{class_bits}" label="{}" cardinality="{}" exact_cardinality="{}" bdd_size="{}"{bdd_file}/>"#,
+            escape_xml(&label),
+            category.cardinality,
+            category.exact_cardinality,
+            category.colors.as_bdd().size(),
+        )?;
+    }
+    writeln!(writer, "  </classes>")?;
+    writeln!(writer, "</classification>")?;
+    Ok(())
+}
+
+/// The `text` with its first letter in upper case (e.g., for the table headers).
+fn capitalized(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Write the Markdown table with the cardinalities of the given `properties` (in the given
+/// `format`).
+fn write_markdown_property_table<W: Write, S: ClassifiedSet>(
+    report: &mut W,
+    properties: &[PropertyResult<S>],
+    valid_cardinality: f64,
+    format: CardinalityFormat,
+) -> Result<(), std::io::Error> {
+    writeln!(
+        report,
+        "| Property | Formula | {} | Percentage |",
+        capitalized(S::ELEMENTS)
+    )?;
+    writeln!(report, "| --- | --- | ---: | ---: |")?;
+    for property in properties {
+        writeln!(
+            report,
+            "| {} | `{}` | {} | {:.1}% |",
+            escape_markdown_table_cell(&property.name),
+            escape_markdown_table_cell(&property.formula),
+            format.format(property.cardinality, &property.exact_cardinality),
+            percentage(property.cardinality, valid_cardinality),
+        )?;
+    }
+    writeln!(report)?;
+    Ok(())
+}
+
+/// Write the Markdown version of the report: the assertions, the tables with the cardinalities
+/// of the properties (including the projected ones), the property relations, and the table
+/// with the cardinalities of the classes. The headline, the implications, the absent classes
+/// and the warnings about non-discriminating properties are only in the plain-text report.
+///
+/// The classes are listed in the same order as in the plain-text report (and named by the
+/// same `labels`), and the cardinalities are given in [OutputOptions::cardinality_format].
+fn write_report_markdown<W: Write, S: ClassifiedSet>(
+    report: &mut W,
+    result: &ClassificationResult<S>,
+    options: &OutputOptions,
+    labels: ClassLabels,
+) -> Result<(), std::io::Error> {
+    let format = options.cardinality_format;
+    writeln!(report, "## Assertion formulae")?;
+    writeln!(report)?;
+    for assertion in &result.assertion_formulae {
+        writeln!(report, "- `{assertion}`")?;
+    }
+    if !result.assertion_formulae.is_empty() {
+        writeln!(report)?;
+    }
+    writeln!(
+        report,
+        "{} {} satisfy all assertions.",
+        format.format(result.valid_cardinality, &result.valid_exact_cardinality),
+        S::ELEMENTS
+    )?;
+    writeln!(report)?;
+
+    writeln!(report, "## Property formulae individually")?;
+    writeln!(report)?;
+    let valid_cardinality = result.valid_cardinality;
+    write_markdown_property_table(report, &result.properties, valid_cardinality, format)?;
+    if !result.projected_properties.is_empty() {
+        writeln!(report, "## Property formulae projected out")?;
+        writeln!(report)?;
+        let projected = &result.projected_properties;
+        write_markdown_property_table(report, projected, valid_cardinality, format)?;
+    }
+    let relations = property_relations(result);
+    if !relations.is_empty() {
+        writeln!(report, "## Property relations")?;
+        writeln!(report)?;
+        for relation in relations {
+            writeln!(report, "- {}", relation_description(result, relation))?;
+        }
+        writeln!(report)?;
+    }
+
+    writeln!(report, "## Classes")?;
+    writeln!(report)?;
+    writeln!(
+        report,
+        "| Class | {} | Percentage |",
+        capitalized(S::ELEMENTS)
+    )?;
+    writeln!(report, "| --- | ---: | ---: |")?;
+    for category in report_categories(result, options) {
+        writeln!(
+            report,
+            "| `{}` | {} | {:.1}% |",
+            labels.title(&category.validity),
+            format.format(category.cardinality, &category.exact_cardinality),
+            percentage(category.cardinality, result.valid_cardinality),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Write a short summary regarding each category of the color decomposition, and dump a BDD
+/// encoding the colors, all into the `archive_name` zip.
+///
+///  - `assertion_formulae`: list of assertion formulae
+///  - `all_valid_colors`: represents a "unit color set", i.e. all colors satisfying the
+///    assertion formulae.
+///  - `named_property_formulae`: lists the property names with their HCTL formula strings.
+///  - `property_results`: lists the symbolic color set results for each property.
+///  - `archive_name`: name of the `.zip` archive with results.
+///  - `original_model_str`: original model in the aeon format
+///
+/// Each result category is given by a set of colors that satisfy exactly the same properties.
+/// The decomposition itself is computed by [build_classification_result].
+pub fn write_classifier_output(
+    assertion_formulae: &[String],
+    all_valid_colors: &GraphColors,
+    named_property_formulae: &[(String, String)],
+    property_results: &[GraphColors],
+    archive_name: &str,
+    original_model_str: &str,
+) -> Result<(), ClassifierError> {
+    let result = build_classification_result(
+        assertion_formulae,
+        all_valid_colors,
+        named_property_formulae,
+        property_results,
+    )?;
+    write_classification_result(&result, archive_name, original_model_str)
+}
+
+/// Compression settings for the (potentially large) BDD dumps in the result archive.
+#[derive(Clone, Copy, Debug)]
+pub struct CompressionOptions {
+    /// Compression method used for the BDD dumps.
+    pub method: CompressionMethod,
+    /// Compression level, its range depends on the method (`None` means the method's default).
+    pub level: Option<i32>,
+}
+
+impl Default for CompressionOptions {
+    fn default() -> Self {
+        CompressionOptions {
+            method: CompressionMethod::Deflated,
+            level: None,
+        }
+    }
+}
+
+impl CompressionOptions {
+    /// Zip file options corresponding to these compression settings.
+    fn file_options(&self, timestamp: SystemTime) -> FileOptions {
+        entry_options(timestamp)
+            .compression_method(self.method)
+            .compression_level(self.level)
+    }
+}
+
+/// Policy for retrying the creation of the output files, which can fail transiently on
+/// networked filesystems (e.g., NFS or SMB scratch space of HPC clusters).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Maximal number of attempts (at least one attempt is always made, so the default `1`
+    /// means no retries).
+    pub attempts: u32,
+    /// Delay before the first retry, which is doubled after each further failed attempt.
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 1,
+            backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Run the `operation` until it succeeds, fails with an error which is not `transient`,
+    /// or the attempts run out. The last error is returned in the latter two cases.
+    fn run<T, E>(
+        &self,
+        transient: impl Fn(&E) -> bool,
+        mut operation: impl FnMut() -> Result<T, E>,
+    ) -> Result<T, E> {
+        let mut backoff = self.backoff;
+        let mut attempt = 1;
+        loop {
+            match operation() {
+                Err(error) if transient(&error) && attempt < self.attempts => {
+                    std::thread::sleep(backoff);
+                    backoff = backoff.saturating_mul(2);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Fingerprint of the `source` of a classification (e.g., of the `.aeon` model), given as
+/// the hex digest of its SHA-256 hash.
+pub fn source_fingerprint(source: &str) -> String {
+    format!("{:x}", Sha256::digest(source.as_bytes()))
+}
+
+/// Names of the two archives of the split output of `archive_name` (see
+/// [OutputOptions::split_bdds]), i.e., `<name>.report.zip` and `<name>.bdds.zip`, where `<name>`
+/// is the `archive_name` without its `.zip` extension.
+pub fn split_archive_names(archive_name: &str) -> (String, String) {
+    let name = archive_name.strip_suffix(".zip").unwrap_or(archive_name);
+    (format!("{name}.report.zip"), format!("{name}.bdds.zip"))
+}
+
+/// Write the entries of the classification output (see [write_entries]) into the
+/// `archive_name` zip, or into its two split archives (see [OutputOptions::split_bdds]).
+/// The optional report sidecar is written afterwards.
+fn write_result_file(
+    result: &ClassificationResult,
+    archive_name: &str,
+    original_model_str: &str,
+    graph: Option<&SymbolicAsyncGraph>,
+    options: &OutputOptions,
+    hooks: WriterHooks,
+) -> Result<(), ClassifierError> {
+    if !options.split_bdds {
+        write_archive_file(
+            archive_name,
+            options.overwrite,
+            options.retry,
+            |zip_writer| {
+                write_entries(
+                    result,
+                    zip_writer,
+                    original_model_str,
+                    graph,
+                    options,
+                    hooks,
+                )
+            },
+        )?;
+        if options.report_sidecar {
+            write_report_sidecar(
+                archive_name,
+                &options.layout,
+                options.overwrite,
+                options.retry,
+            )?;
+        }
+        return Ok(());
+    }
+
+    let (report_name, bdds_name) = split_archive_names(archive_name);
+    // Each archive refers to both of them by their file names (they are kept side by side).
+    let file_name = |path: &str| {
+        let path = Path::new(path);
+        path.file_name()
+            .map_or(path.as_os_str(), |name| name)
+            .to_string_lossy()
+            .to_string()
+    };
+    let companion = format!(
+        "report={}\nbdds={}\n",
+        file_name(&report_name),
+        file_name(&bdds_name)
+    );
+    let layout = &options.layout;
+    let file_options = entry_options(options.timestamp.unwrap_or_else(SystemTime::now));
+    // The BDD archive is finished first, so it has to be removed if the report fails later.
+    let mut bdds_written = false;
+    let written = write_archive_file(&report_name, options.overwrite, options.retry, |report| {
+        write_archive_file(&bdds_name, options.overwrite, options.retry, |bdds| {
+            let mut entries = SplitWriter {
+                layout,
+                report: &mut *report,
+                bdds: &mut *bdds,
+                target: (true, true),
+            };
+            write_entries(
+                result,
+                &mut entries,
+                original_model_str,
+                graph,
+                options,
+                hooks,
+            )?;
+            entries.start_entry(&layout.entry(&layout.companion), file_options)?;
+            write!(entries, "{companion}")?;
+            Ok(())
+        })?;
+        bdds_written = true;
+        Ok(())
+    });
+    if written.is_err() && bdds_written {
+        let _ = std::fs::remove_file(&bdds_name);
+    }
+    written?;
+    if options.report_sidecar {
+        write_report_sidecar(&report_name, layout, options.overwrite, options.retry)?;
+    }
+    Ok(())
+}
+
+/// Default zip options of the archive entries, with the modification time set to `timestamp`
+/// (times outside of the range supported by zip are replaced with its earliest date, 1980-01-01).
+fn entry_options(timestamp: SystemTime) -> FileOptions {
+    let modified = zip::DateTime::try_from(OffsetDateTime::from(timestamp)).unwrap_or_default();
+    FileOptions::default().last_modified_time(modified)
+}
+
+/// Configuration of the optional parts of the classification output.
+#[derive(Clone, Debug)]
+pub struct OutputOptions {
+    /// Dump the BDD of each non-empty category. Without the dumps, the archive only contains
+    /// the reports (which are much smaller) and the categories cannot be loaded back.
+    pub include_bdds: bool,
+    /// Besides the standard BDD dump, also write each non-empty category BDD as a Graphviz
+    /// `bdd_dump_<bits>.dot` file.
+    pub dot_dumps: bool,
+    /// List only the non-empty categories in the report, sorted by their cardinality (largest
+    /// first). The BDD dumps are not affected.
+    pub sort_classes_by_size: bool,
+    /// Compression of the BDD dumps (other archive entries always use the default settings).
+    pub bdd_compression: CompressionOptions,
+    /// Dump the categories with more than half of the valid colors relative to the valid colors,
+    /// i.e., as the valid colors *outside* of the category, which is marked in the BDD index.
+    /// The valid colors are then dumped into the `valid_colors.txt` entry (always in the text
+    /// format), so that the loaders can complement the relative dumps back. The `.dot` dumps
+    /// of such categories are relative as well.
+    pub relative_bdds: bool,
+    /// Compress the BDD dumps by zstd, using a dictionary trained on the dumps themselves (which
+    /// beats the compression of the individual entries for many similar BDDs). The dictionary
+    /// is stored in the `bdd.dict` entry, and the compressed dumps get an extra `.zst` extension
+    /// (e.g., `bdd_dump_<bits>.txt.zst`), so that the loaders can decompress them. The `.dot`
+    /// dumps and the valid colors are not affected. If the dictionary cannot be trained (e.g.,
+    /// for too few dumps), the dumps are written as usual.
+    pub bdd_dictionary: bool,
+    /// Write the BDD dumps in the (much more compact) binary format of [Bdd::write_as_bytes]
+    /// as `bdd_dump_<bits>.bdd` files, instead of the text format.
+    pub binary_bdds: bool,
+    /// Besides the plain-text report, also write the report in the Markdown format.
+    pub markdown_report: bool,
+    /// Besides the plain-text report, also write the report in the XML format (with the same
+    /// structure as the JSON report).
+    pub xml_report: bool,
+    /// Before writing anything, check that the categories form an exact partition of the valid
+    /// colors (see [verify_partition]).
+    pub verify: bool,
+    /// In the report, aggregate the categories with less colors than this into one `other`
+    /// entry. The BDD dumps are not affected.
+    pub min_class_cardinality: f64,
+    /// Format of the cardinalities in the text report (see [CardinalityFormat]).
+    pub cardinality_format: CardinalityFormat,
+    /// At the end of the text report, list the absent classes (i.e., the combinations of
+    /// properties without any colors), as long as there are not too many properties (see
+    /// [ClassificationResult::absent_classes]).
+    pub absent_classes: bool,
+    /// Also write the matrix of the pairwise overlaps of the properties (see [property_overlaps])
+    /// into the `overlaps.csv` entry.
+    pub overlaps: bool,
+    /// Also write the lattice of the non-empty categories (where the categories differing in
+    /// one property are connected) in the GraphML format into the `lattice.graphml` entry.
+    pub lattice: bool,
+    /// Besides the report in the archive, also write a plain copy of the text report into the
+    /// `<archive>.report.txt` file next to the archive (e.g., for CI logs).
+    pub report_sidecar: bool,
+    /// Write the `timing.txt` entry with the duration (in milliseconds) of the phases of the
+    /// classification: the enumeration of the categories (see
+    /// [ClassificationResult::enumeration_time]), the serialization and the compression of the
+    /// BDD dumps, and the writing of the reports.
+    pub timing: bool,
+    /// Canonical order of the property names, which gives the bit positions of the properties
+    /// (see [crate::categories::property_order_indices]). This is only used by
+    /// [crate::classification::classify], which reorders the properties before the
+    /// classification, so that the reports of different runs are comparable.
+    pub property_order: Option<Vec<String>>,
+    /// Only compute the cardinalities of the classes instead of their color sets (see
+    /// [crate::categories::build_cardinality_classification_result]), so the output has no
+    /// BDD dumps. This is only used by [crate::classification::classify], since the other
+    /// writers are given the full result.
+    pub stats_only: bool,
+    /// Maximal number of categories that are enumerated exhaustively (see
+    /// [crate::categories::build_classification_result_with_limit]). For more property
+    /// combinations, only the non-empty categories are computed. This is only used by
+    /// [crate::classification::classify] and the [crate::classification::Classifier], which
+    /// build the result themselves.
+    pub max_classes: usize,
+    /// In the report, list the BDD variables that appear in the BDD of each non-empty category
+    /// (see [Category::support]), i.e., the parameters relevant to distinguishing the category.
+    pub bdd_support: bool,
+    /// In the report, describe a representative instance (i.e., the instantiated update
+    /// functions of one color) of each non-empty category.
+    pub representatives: bool,
+    /// Instead of one archive, write the BDD dumps (with the BDD index, the valid colors and the
+    /// checksums) into `<name>.bdds.zip` and the reports into `<name>.report.zip` (see
+    /// [split_archive_names]). Both archives contain the metadata and the model, so that each
+    /// of them can be opened on its own, and the `companion.txt` entry with the file names of
+    /// both archives, so that they can be recombined. This is only used by the writers of
+    /// a single result into a file (such as [write_classification_result_with_options]).
+    pub split_bdds: bool,
+    /// Record the SHA-256 hash of each BDD dump in the `checksums.txt` entry, so that the
+    /// integrity of the archive can be checked later (see [crate::load_inputs::verify_archive]).
+    pub checksums: bool,
+    /// Replace the output archive if it already exists (enabled by default). Otherwise,
+    /// a [ClassifierError::OutputExists] error is returned and the archive is not touched.
+    pub overwrite: bool,
+    /// Retries of the creation of the output files (no retries by default), e.g., for the
+    /// flaky networked filesystems.
+    pub retry: RetryPolicy,
+    /// Order of the bits in the class labels (MSB first by default). The order is also
+    /// recorded in the metadata.
+    pub bit_order: BitOrder,
+    /// Names of the entries in the archive.
+    pub layout: ArchiveLayout,
+    /// Fingerprint of the input which produced the result (usually the [source_fingerprint] of
+    /// the model), recorded as `source=...` in the metadata, so that the archive can be later
+    /// matched with its input.
+    pub source_fingerprint: Option<String>,
+    /// Time recorded in the metadata and as the modification time of all archive entries
+    /// (`None` means the current time). With a fixed timestamp, the same result is always
+    /// written as the same sequence of bytes.
+    pub timestamp: Option<SystemTime>,
+}
+
+impl Default for OutputOptions {
+    fn default() -> Self {
+        OutputOptions {
+            include_bdds: true,
+            dot_dumps: false,
+            sort_classes_by_size: false,
+            bdd_compression: CompressionOptions::default(),
+            binary_bdds: false,
+            bdd_dictionary: false,
+            relative_bdds: false,
+            markdown_report: false,
+            xml_report: false,
+            min_class_cardinality: 0.0,
+            verify: false,
+            cardinality_format: CardinalityFormat::default(),
+            absent_classes: false,
+            overlaps: false,
+            report_sidecar: false,
+            split_bdds: false,
+            lattice: false,
+            timing: false,
+            property_order: None,
+            stats_only: false,
+            max_classes: DEFAULT_MAX_CLASSES,
+            bdd_support: false,
+            representatives: false,
+            checksums: false,
+            overwrite: true,
+            retry: RetryPolicy::default(),
+            bit_order: BitOrder::default(),
+            layout: ArchiveLayout::default(),
+            timestamp: None,
+            source_fingerprint: None,
+        }
+    }
+}
+
+/// Write the report regarding the given [ClassificationResult] and dump a BDD for each
+/// non-empty category, all into the `archive_name` zip. The `original_model_str` is included
+/// in the archive as well.
+///
+/// If the result has no valid colors (i.e., the assertions cannot be satisfied), the archive
+/// has no classes and its report only describes the assertions (see [write_empty_report]),
+/// but all the other entries are written as usual (this holds for all the writers of the
+/// whole results).
+pub fn write_classification_result(
+    result: &ClassificationResult,
+    archive_name: &str,
+    original_model_str: &str,
+) -> Result<(), ClassifierError> {
+    write_classification_result_with_options(
+        result,
+        archive_name,
+        original_model_str,
+        &OutputOptions::default(),
+    )
+}
+
+/// Same as [write_classification_result], but the optional parts of the output are configured
+/// by the given [OutputOptions].
+pub fn write_classification_result_with_options(
+    result: &ClassificationResult,
+    archive_name: &str,
+    original_model_str: &str,
+    options: &OutputOptions,
+) -> Result<(), ClassifierError> {
+    write_result_file(
+        result,
+        archive_name,
+        original_model_str,
+        None,
+        options,
+        WriterHooks::default(),
+    )
+}
+
+/// Same as [write_classification_result_with_options], but the symbolic `graph` in which the
+/// color sets of the `result` were encoded is given explicitly (it must be based on the model
+/// in `original_model_str`, without any extra symbolic variables).
+///
+/// The `graph` is then used by all parts of the output that need the symbolic encoding (such as
+/// the `.dot` dumps or the representatives), instead of encoding the model again.
+pub fn write_classification_result_with_graph(
+    result: &ClassificationResult,
+    archive_name: &str,
+    original_model_str: &str,
+    graph: &SymbolicAsyncGraph,
+    options: &OutputOptions,
+) -> Result<(), ClassifierError> {
+    write_result_file(
+        result,
+        archive_name,
+        original_model_str,
+        Some(graph),
+        options,
+        WriterHooks::default(),
+    )
+}
+
+/// Same as [write_classification_result_with_graph], but the archive is written into an
+/// arbitrary seekable `writer` (e.g., an in-memory `Cursor<Vec<u8>>`) instead of a file,
+/// and the `graph` is optional (if it is needed but not given, it is built from the
+/// `original_model_str`).
+///
+/// Returns the `writer` once the archive is finished.
+pub fn write_classification_result_to_writer<W: Write + Seek>(
+    result: &ClassificationResult,
+    writer: W,
+    original_model_str: &str,
+    graph: Option<&SymbolicAsyncGraph>,
+    options: &OutputOptions,
+) -> Result<W, ClassifierError> {
+    write_archive(
+        result,
+        writer,
+        original_model_str,
+        graph,
+        options,
+        WriterHooks::default(),
+    )
+}
+
+/// Same as [write_classification_result_to_writer], but the entries are added to an existing
+/// `zip_writer` (e.g., one that already contains the figures of a larger export). The names of
+/// the entries are prefixed by the [ArchiveLayout::prefix] of the `options` (e.g.,
+/// `classification/`), so that they do not collide with the other entries. The archive is not
+/// finished, i.e., the caller can add more entries before finishing it.
+pub fn write_classification_entries<W: Write + Seek>(
+    result: &ClassificationResult,
+    zip_writer: &mut ZipWriter<W>,
+    original_model_str: &str,
+    graph: Option<&SymbolicAsyncGraph>,
+    options: &OutputOptions,
+) -> Result<(), ClassifierError> {
+    write_entries(
+        result,
+        zip_writer,
+        original_model_str,
+        graph,
+        options,
+        WriterHooks::default(),
+    )
+}
+
+/// Implementation of [write_classification_result_to_writer], with the optional callbacks
+/// given by the `hooks` (see [ClassificationWriter]).
+fn write_archive<W: Write + Seek>(
+    result: &ClassificationResult,
+    writer: W,
+    original_model_str: &str,
+    graph: Option<&SymbolicAsyncGraph>,
+    options: &OutputOptions,
+    hooks: WriterHooks,
+) -> Result<W, ClassifierError> {
+    let mut zip_writer = ZipWriter::new(writer);
+    write_entries(
+        result,
+        &mut zip_writer,
+        original_model_str,
+        graph,
+        options,
+        hooks,
+    )?;
+    Ok(zip_writer.finish()?)
+}
+
+/// Check that the [ClassificationResult::num_hctl_vars] recorded in the metadata is consistent
+/// with the BDDs of the `result`, given the symbolic `context` of the model: the BDDs are either
+/// sanitized (i.e., without any HCTL variables), or they have the extra state variables of
+/// exactly the declared number of HCTL variables. Otherwise, the loaders could not restore
+/// the dumps later.
+fn validate_hctl_vars(
+    result: &ClassificationResult,
+    context: &SymbolicContext,
+) -> Result<(), ClassifierError> {
+    let num_state_vars = context.num_state_variables();
+    let canonical_num_vars = num_state_vars + context.num_parameter_variables();
+    let bdd_vars = usize::from(result.valid_colors.as_bdd().num_vars());
+    let declared = result.num_hctl_vars;
+    if bdd_vars == canonical_num_vars || bdd_vars == canonical_num_vars + declared * num_state_vars
+    {
+        Ok(())
+    } else {
+        Err(ClassifierError::InconsistentHctlVars { declared, bdd_vars })
+    }
+}
+
+/// Write all entries of the classification output (see [write_archive]) into `entries`. Without
+/// valid colors, the report only describes the assertions (see [write_empty_report_text]).
+fn write_entries<E: EntryWriter>(
+    result: &ClassificationResult,
+    entries: &mut E,
+    original_model_str: &str,
+    graph: Option<&SymbolicAsyncGraph>,
+    options: &OutputOptions,
+    hooks: WriterHooks,
+) -> Result<(), ClassifierError> {
+    // Without the colors of the categories, there is no partition to verify.
+    if options.verify && !result.cardinalities_only {
+        verify_partition(result)?;
+    }
+    let labels = ClassLabels {
+        bit_order: options.bit_order,
+        names: hooks.class_names,
+    };
+    // The labels also name the BDD dumps, so they must identify the categories, and the custom
+    // names must not escape the output (e.g., the directory of `write_to_dir`).
+    let mut used_labels = HashSet::new();
+    for category in &result.categories {
+        let label = labels.label(&category.validity);
+        let is_custom = labels
+            .names
+            .is_some_and(|names| names(&category.validity).is_some());
+        if is_custom && !is_valid_class_name(&label) {
+            return Err(ClassifierError::InvalidClassLabel(label));
+        }
+        if !used_labels.insert(label.clone()) {
+            return Err(ClassifierError::DuplicateClassLabel(label));
+        }
+    }
+
+    // The symbolic encoding is only needed to give names to the BDD variables in `.dot` dumps
+    // (and in the report) and to instantiate the representatives of the categories.
+    let needs_dot = options.include_bdds && options.dot_dumps;
+    let encoded_graph = match graph {
+        None if needs_dot || options.representatives || options.bdd_support => {
+            let bn =
+                BooleanNetwork::try_from(original_model_str).map_err(ClassifierError::Model)?;
+            Some(SymbolicAsyncGraph::new(bn).map_err(ClassifierError::Model)?)
+        }
+        _ => None,
+    };
+    let graph = graph.or(encoded_graph.as_ref());
+    if let Some(graph) = graph {
+        validate_hctl_vars(result, graph.symbolic_context())?;
+    }
+    let dot_context = graph
+        .filter(|_| needs_dot)
+        .map(|graph| graph.symbolic_context());
+    let report_graph = graph.filter(|_| options.representatives || options.bdd_support);
+
+    // All entries share one timestamp, so that it can be fixed for reproducible output.
+    let timestamp = options.timestamp.unwrap_or_else(SystemTime::now);
+    let file_options = entry_options(timestamp);
+
+    // The progress is reported in terms of all the combinations of the properties, same as in
+    // the enumeration (see [build_classification_result_with_mode]). Each category of the result
+    // is finished once it is dumped (the empty ones right before the next dump), and the
+    // combinations that are not in the result are only finished at the end.
+    let classes_total = num_combinations(2, result.properties.len());
+    let classes_done = Cell::new(0);
+    let categories_done = Cell::new(0);
+    let finish_categories = |end: usize| {
+        let Some(progress) = hooks.progress else {
+            return;
+        };
+        for category in &result.categories[categories_done.get()..end] {
+            classes_done.set(
+                classes_done
+                    .get()
+                    .max(num_combinations_through(&category.validity)),
+            );
+            progress(classes_done.get(), classes_total);
+        }
+        categories_done.set(end);
+    };
+
+    // If the BDD is not empty, the results go directly into the archive.
+    let layout = &options.layout;
+    let dumps_start = Instant::now();
+    let mut timed_entries = TimingWriter::new(&mut *entries, options.timing);
+    let (bdd_index, dumped_nodes) = if options.include_bdds {
+        let non_empty: Vec<&Category> = result
+            .categories
+            .iter()
+            .filter(|category| !category.colors.is_empty())
+            .collect();
+        let non_empty_positions: Vec<usize> = result
+            .categories
+            .iter()
+            .enumerate()
+            .filter(|(_, category)| !category.colors.is_empty())
+            .map(|(position, _)| position)
+            .collect();
+        let dumps_progress =
+            |sets_done: usize, _| finish_categories(non_empty_positions[sets_done - 1] + 1);
+        // The optional transformation only affects the dumped sets (the report uses the
+        // original cardinalities).
+        let transform = |colors: &GraphColors| hooks.transform.map(|transform| transform(colors));
+        let transformed: Vec<Option<GraphColors>> = non_empty
+            .iter()
+            .map(|category| transform(&category.colors))
+            .collect();
+        let dumped: Vec<&GraphColors> = non_empty
+            .iter()
+            .zip(&transformed)
+            .map(|(category, colors)| colors.as_ref().unwrap_or(&category.colors))
+            .collect();
+        let transformed_valid = transform(&result.valid_colors);
+        let valid_colors = transformed_valid.as_ref().unwrap_or(&result.valid_colors);
+        // With relative dumps, the categories with more than half of the valid colors are
+        // dumped as their complement (which is usually much smaller).
+        let complements: Vec<Option<GraphColors>> = non_empty
+            .iter()
+            .zip(&dumped)
+            .map(|(category, colors)| {
+                let is_large = category.cardinality > result.valid_cardinality / 2.0;
+                (options.relative_bdds && is_large).then(|| valid_colors.minus(colors))
+            })
+            .collect();
+        let named_sets: Vec<(String, &GraphColors)> = non_empty
+            .iter()
+            .zip(&dumped)
+            .zip(&complements)
+            .map(|((category, colors), complement)| {
+                let colors = complement.as_ref().unwrap_or(colors);
+                (labels.label(&category.validity), colors)
+            })
+            .collect();
+        let relative: HashSet<String> = non_empty
+            .iter()
+            .zip(&complements)
+            .filter(|(_, complement)| complement.is_some())
+            .map(|(category, _)| labels.label(&category.validity))
+            .collect();
+
+        let mut checksums = Vec::new();
+        let bdd_index = write_bdd_dumps(
+            &mut timed_entries,
+            &named_sets,
+            options,
+            timestamp,
+            dot_context,
+            Some(&dumps_progress as &dyn Fn(usize, usize)),
+            Some(&mut checksums).filter(|_| options.checksums),
+        )?;
+        if !relative.is_empty() {
+            // The loaders need the valid colors to complement the relative dumps back.
+            write_bdd_entry(
+                &mut timed_entries,
+                &layout.entry(&layout.valid_colors),
+                options.bdd_compression.file_options(timestamp),
+                valid_colors.as_bdd(),
+                false,
+                Some(&mut checksums).filter(|_| options.checksums),
+            )?;
+        }
+        write_bdd_index(
+            &mut timed_entries,
+            layout,
+            &bdd_index,
+            &relative,
+            file_options,
+        )?;
+        if options.checksums {
+            write_checksums(&mut timed_entries, layout, &checksums, file_options)?;
+        }
+        // The categories that share a dump only count once.
+        let mut dumped_files = HashSet::new();
+        let dumped_nodes: usize = named_sets
+            .iter()
+            .zip(&bdd_index)
+            .filter(|(_, (_, file))| dumped_files.insert(file.as_str()))
+            .map(|((_, colors), _)| colors.as_bdd().size())
+            .sum();
+        (bdd_index, Some(dumped_nodes))
+    } else {
+        (Vec::new(), None)
+    };
+    finish_categories(result.categories.len());
+    if let Some(progress) = hooks
+        .progress
+        .filter(|_| classes_done.get() < classes_total)
+    {
+        progress(classes_total, classes_total);
+    }
+    let compression_time = timed_entries.elapsed;
+    let serialization_time = dumps_start.elapsed().saturating_sub(compression_time);
+
+    // Finally, we can write the metadata and the report.
+    let reports_start = Instant::now();
+    entries.start_entry(&layout.entry(&layout.metadata), file_options)?;
+    let mut bdd_nodes = Vec::new();
+    if result.peak_bdd_size > 0 {
+        bdd_nodes.push(("peak", result.peak_bdd_size));
+    }
+    if let Some(dumped_nodes) = dumped_nodes {
+        bdd_nodes.push(("dumped", dumped_nodes));
+    }
+    write_metadata(
+        entries,
+        result.num_hctl_vars,
+        &result.formula_hctl_vars,
+        &bdd_nodes,
+        options.bit_order,
+        timestamp,
+        options.source_fingerprint.as_deref(),
+    )?;
+
+    // The report is written directly into the archive (it only needs the cardinalities and
+    // the optional representatives of the categories).
+    entries.start_entry(&layout.entry(&layout.report), file_options)?;
+    if result.valid_colors.is_empty() {
+        // Without any valid colors, there are no classes to report.
+        write_empty_report_text(
+            entries,
+            &result.assertion_formulae,
+            &result.assertion_cardinalities,
+        )?;
+    } else {
+        write_report_intro(entries, result, options.cardinality_format)?;
+        write_report_classes(entries, result, options, labels, report_graph)?;
+        if options.absent_classes {
+            write_absent_classes(entries, result, labels)?;
+        }
+    }
+
+    // Machine-readable version of the report goes right next to it.
+    entries.start_entry(&layout.entry(&layout.report_json), file_options)?;
+    serde_json::to_writer_pretty(
+        &mut *entries,
+        &prepare_report_json(result, &bdd_index, labels),
+    )
+    .map_err(std::io::Error::from)?;
+
+    entries.start_entry(&layout.entry(&layout.classes_legend), file_options)?;
+    write_classes_legend(entries, result, labels)?;
+
+    // Class table for tools like pandas or R.
+    entries.start_entry(&layout.entry(&layout.classes_csv), file_options)?;
+    write_classes_csv(entries, result, &bdd_index, labels)?;
+
+    if options.overlaps {
+        entries.start_entry(&layout.entry(&layout.overlaps_csv), file_options)?;
+        write_overlaps_csv(entries, result, &property_overlaps(result))?;
+    }
+
+    if options.lattice {
+        entries.start_entry(&layout.entry(&layout.lattice), file_options)?;
+        write_lattice_graphml(entries, result, labels)?;
+    }
+
+    if options.xml_report {
+        entries.start_entry(&layout.entry(&layout.report_xml), file_options)?;
+        write_report_xml(entries, result, &bdd_index, labels)?;
+    }
+
+    if options.markdown_report {
+        entries.start_entry(&layout.entry(&layout.report_md), file_options)?;
+        write_report_markdown(entries, result, options, labels)?;
+    }
+
+    // Include the original model in the result bundle (we need to load it later).
+    entries.start_entry(&layout.entry(&layout.model), file_options)?;
+    write!(entries, "{original_model_str}")?;
+
+    // Together with the model, the formulae are enough to reproduce the classification.
+    if result
+        .properties
+        .iter()
+        .all(|property| !property.formula.is_empty())
+    {
+        entries.start_entry(&layout.entry(&layout.properties), file_options)?;
+        write_property_file(entries, result)?;
+    }
+
+    if options.timing {
+        let timings = [
+            ("enumeration", result.enumeration_time),
+            ("bdd_serialization", serialization_time),
+            ("bdd_compression", compression_time),
+            ("reports", reports_start.elapsed()),
+        ];
+        entries.start_entry(&layout.entry(&layout.timing), file_options)?;
+        for (phase, duration) in timings {
+            writeln!(entries, "{phase}_ms={:.3}", duration.as_secs_f64() * 1000.0)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Builder-style configuration of the classification output, as an alternative to
+/// [write_classification_result_with_options] and friends.
+///
+/// All options start with their default values (see [OutputOptions]), and the archive is then
+/// written using [ClassificationWriter::write] (or [ClassificationWriter::write_to]).
+pub struct ClassificationWriter<'a> {
+    original_model_str: &'a str,
+    graph: Option<&'a SymbolicAsyncGraph>,
+    options: OutputOptions,
+    hooks: WriterHooks<'a>,
+}
+
+impl<'a> ClassificationWriter<'a> {
+    /// New writer of results for the model given by `original_model_str` (which is also
+    /// included in the archive).
+    pub fn new(original_model_str: &'a str) -> ClassificationWriter<'a> {
+        ClassificationWriter {
+            original_model_str,
+            graph: None,
+            options: OutputOptions::default(),
+            hooks: WriterHooks::default(),
+        }
+    }
+
+    /// Use all the given [OutputOptions] at once.
+    pub fn with_options(mut self, options: OutputOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Use the symbolic `graph` in which the results were encoded (see
+    /// [write_classification_result_with_graph]).
+    pub fn with_graph(mut self, graph: &'a SymbolicAsyncGraph) -> Self {
+        self.graph = Some(graph);
+        self
+    }
+
+    /// See [OutputOptions::bdd_compression].
+    pub fn with_compression(mut self, compression: CompressionOptions) -> Self {
+        self.options.bdd_compression = compression;
+        self
+    }
+
+    /// See [OutputOptions::layout].
+    pub fn with_layout(mut self, layout: ArchiveLayout) -> Self {
+        self.options.layout = layout;
+        self
+    }
+
+    /// See [OutputOptions::include_bdds].
+    pub fn include_bdds(mut self, include_bdds: bool) -> Self {
+        self.options.include_bdds = include_bdds;
+        self
+    }
+
+    /// See [OutputOptions::dot_dumps].
+    pub fn dot_dumps(mut self, dot_dumps: bool) -> Self {
+        self.options.dot_dumps = dot_dumps;
+        self
+    }
+
+    /// See [OutputOptions::sort_classes_by_size].
+    pub fn sort_by_size(mut self, sort_by_size: bool) -> Self {
+        self.options.sort_classes_by_size = sort_by_size;
+        self
+    }
+
+    /// See [OutputOptions::cardinality_format].
+    pub fn cardinality_format(mut self, cardinality_format: CardinalityFormat) -> Self {
+        self.options.cardinality_format = cardinality_format;
+        self
+    }
+
+    /// Shorthand for the [CardinalityFormat::Scientific] (or the default
+    /// [CardinalityFormat::Fixed]) format of the cardinalities.
+    pub fn scientific_cardinalities(mut self, scientific: bool) -> Self {
+        self.options.cardinality_format = if scientific {
+            CardinalityFormat::Scientific
+        } else {
+            CardinalityFormat::Fixed
+        };
+        self
+    }
+
+    /// See [OutputOptions::absent_classes].
+    pub fn absent_classes(mut self, absent_classes: bool) -> Self {
+        self.options.absent_classes = absent_classes;
+        self
+    }
+
+    /// See [OutputOptions::overlaps].
+    pub fn overlaps(mut self, overlaps: bool) -> Self {
+        self.options.overlaps = overlaps;
+        self
+    }
+
+    /// See [OutputOptions::lattice].
+    pub fn lattice(mut self, lattice: bool) -> Self {
+        self.options.lattice = lattice;
+        self
+    }
+
+    /// See [OutputOptions::report_sidecar].
+    pub fn report_sidecar(mut self, report_sidecar: bool) -> Self {
+        self.options.report_sidecar = report_sidecar;
+        self
+    }
+
+    /// See [OutputOptions::retry].
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.options.retry = retry;
+        self
+    }
+
+    /// See [OutputOptions::bdd_support].
+    pub fn bdd_support(mut self, bdd_support: bool) -> Self {
+        self.options.bdd_support = bdd_support;
+        self
+    }
+
+    /// See [OutputOptions::split_bdds].
+    pub fn split_bdds(mut self, split_bdds: bool) -> Self {
+        self.options.split_bdds = split_bdds;
+        self
+    }
+
+    /// See [OutputOptions::timing].
+    pub fn timing(mut self, timing: bool) -> Self {
+        self.options.timing = timing;
+        self
+    }
+
+    /// See [OutputOptions::relative_bdds].
+    pub fn relative_bdds(mut self, relative_bdds: bool) -> Self {
+        self.options.relative_bdds = relative_bdds;
+        self
+    }
+
+    /// See [OutputOptions::bdd_dictionary].
+    pub fn bdd_dictionary(mut self, bdd_dictionary: bool) -> Self {
+        self.options.bdd_dictionary = bdd_dictionary;
+        self
+    }
+
+    /// See [OutputOptions::binary_bdds].
+    pub fn binary_bdds(mut self, binary_bdds: bool) -> Self {
+        self.options.binary_bdds = binary_bdds;
+        self
+    }
+
+    /// See [OutputOptions::xml_report].
+    pub fn xml_report(mut self, xml_report: bool) -> Self {
+        self.options.xml_report = xml_report;
+        self
+    }
+
+    /// See [OutputOptions::markdown_report].
+    pub fn markdown_report(mut self, markdown_report: bool) -> Self {
+        self.options.markdown_report = markdown_report;
+        self
+    }
+
+    /// See [OutputOptions::verify].
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.options.verify = verify;
+        self
+    }
+
+    /// See [OutputOptions::min_class_cardinality].
+    pub fn min_class_cardinality(mut self, min_class_cardinality: f64) -> Self {
+        self.options.min_class_cardinality = min_class_cardinality;
+        self
+    }
+
+    /// See [OutputOptions::checksums].
+    pub fn checksums(mut self, checksums: bool) -> Self {
+        self.options.checksums = checksums;
+        self
+    }
+
+    /// See [OutputOptions::overwrite].
+    pub fn overwrite(mut self, overwrite: bool) -> Self {
+        self.options.overwrite = overwrite;
+        self
+    }
+
+    /// See [OutputOptions::source_fingerprint].
+    pub fn source_fingerprint(mut self, fingerprint: &str) -> Self {
+        self.options.source_fingerprint = Some(fingerprint.to_string());
+        self
+    }
+
+    /// See [OutputOptions::timestamp].
+    pub fn timestamp(mut self, timestamp: SystemTime) -> Self {
+        self.options.timestamp = Some(timestamp);
+        self
+    }
+
+    /// See [OutputOptions::bit_order].
+    pub fn bit_order(mut self, bit_order: BitOrder) -> Self {
+        self.options.bit_order = bit_order;
+        self
+    }
+
+    /// See [OutputOptions::representatives].
+    pub fn representatives(mut self, representatives: bool) -> Self {
+        self.options.representatives = representatives;
+        self
+    }
+
+    /// Call `progress(classes_done, classes_total)` whenever some categories of the result are
+    /// finished, i.e., once the BDD dump of a category is written (the empty categories are
+    /// finished right before the next dump). Same as in
+    /// [crate::categories::build_classification_result_with_mode], the numbers count all `2^n`
+    /// combinations of the properties, so the combinations missing in the result are only
+    /// finished at the end.
+    pub fn on_progress(mut self, progress: &'a dyn Fn(usize, usize)) -> Self {
+        self.hooks.progress = Some(progress);
+        self
+    }
+
+    /// Name the classes using `class_names`, which maps the validity vector of a class to its
+    /// custom name (e.g., `bistable`). The names are then used in all reports and as the names
+    /// of the BDD dumps. Classes without a custom name (i.e., where `class_names` returns `None`)
+    /// keep their bit string.
+    ///
+    /// All names must be unique, otherwise writing fails with
+    /// [ClassifierError::DuplicateClassLabel]. They must also be non-empty, and must not contain
+    /// `/`, `\` or `..` (see [ClassifierError::InvalidClassLabel]). Note that the categories of
+    /// such an archive can only be loaded back by their names (using
+    /// [crate::load_inputs::load_classification_archive]).
+    pub fn with_class_names(mut self, class_names: &'a ClassNameFn) -> Self {
+        self.hooks.class_names = Some(class_names);
+        self
+    }
+
+    /// Apply `transform` to the colors of each category before its BDD is dumped (e.g., to
+    /// existentially quantify out the parameters that are not of interest). The reports are
+    /// not affected, i.e., the cardinalities are computed from the original colors.
+    ///
+    /// The valid colors are transformed as well (when they are dumped for the relative dumps),
+    /// so the complements of the relative dumps are taken w.r.t. the transformed colors.
+    pub fn transform_dumps(mut self, transform: &'a DumpTransformFn<'a>) -> Self {
+        self.hooks.transform = Some(transform);
+        self
+    }
+
+    /// Write the `result` into the `archive_name` zip.
+    pub fn write(
+        &self,
+        result: &ClassificationResult,
+        archive_name: &str,
+    ) -> Result<(), ClassifierError> {
+        write_result_file(
+            result,
+            archive_name,
+            self.original_model_str,
+            self.graph,
+            &self.options,
+            self.hooks,
+        )
+    }
+
+    /// Write the `result` into an arbitrary seekable `writer`, which is returned once
+    /// the archive is finished.
+    pub fn write_to<W: Write + Seek>(
+        &self,
+        result: &ClassificationResult,
+        writer: W,
+    ) -> Result<W, ClassifierError> {
+        write_archive(
+            result,
+            writer,
+            self.original_model_str,
+            self.graph,
+            &self.options,
+            self.hooks,
+        )
+    }
+
+    /// Write the entries of the `result` into an existing `zip_writer` (see
+    /// [write_classification_entries]), which is not finished.
+    pub fn write_into<W: Write + Seek>(
+        &self,
+        result: &ClassificationResult,
+        zip_writer: &mut ZipWriter<W>,
+    ) -> Result<(), ClassifierError> {
+        write_entries(
+            result,
+            zip_writer,
+            self.original_model_str,
+            self.graph,
+            &self.options,
+            self.hooks,
+        )
+    }
+
+    /// Write the `result` into the `dir_path` directory instead of a zip archive, i.e., each
+    /// entry of the archive is a file in the directory (which is created if necessary).
+    ///
+    /// Existing files are replaced, but other files in the directory are kept. If overwriting
+    /// is disabled, an existing `dir_path` results in [ClassifierError::OutputExists].
+    pub fn write_to_dir(
+        &self,
+        result: &ClassificationResult,
+        dir_path: &str,
+    ) -> Result<(), ClassifierError> {
+        let root = PathBuf::from(dir_path);
+        if !self.options.overwrite && root.exists() {
+            return Err(ClassifierError::OutputExists(dir_path.to_string()));
+        }
+        std::fs::create_dir_all(&root)?;
+
+        let mut entries = DirectoryWriter { root, file: None };
+        write_entries(
+            result,
+            &mut entries,
+            self.original_model_str,
+            self.graph,
+            &self.options,
+            self.hooks,
+        )?;
+        Ok(entries.finish()?)
+    }
+}
+
+/// Same as [write_classification_result], but the output is written into the `dir_path`
+/// directory instead of a zip archive (see [ClassificationWriter::write_to_dir]).
+pub fn write_class_report_to_dir(
+    result: &ClassificationResult,
+    dir_path: &str,
+    original_model_str: &str,
+) -> Result<(), ClassifierError> {
+    ClassificationWriter::new(original_model_str).write_to_dir(result, dir_path)
+}
+
+/// Write only the plain-text report of the `result` into the `writer`, i.e., the same report as
+/// `report.txt` in the archive (without the representatives of the categories).
+///
+/// Unlike the archive writers, this also accepts the results of other [ClassifiedSet]s than
+/// colors (e.g., of [biodivine_lib_param_bn::symbolic_async_graph::GraphColoredVertices]), in
+/// which case the report counts the classified elements instead of colors.
+pub fn write_text_report<W: Write, S: ClassifiedSet>(
+    writer: &mut W,
+    result: &ClassificationResult<S>,
+    options: &OutputOptions,
+) -> Result<(), ClassifierError> {
+    let labels = ClassLabels::from(options.bit_order);
+    write_report_intro(writer, result, options.cardinality_format)?;
+    write_report_classes(writer, result, options, labels, None)?;
+    if options.absent_classes {
+        write_absent_classes(writer, result, labels)?;
+    }
+    Ok(())
+}
+
+/// Label of a ternary category (see [crate::categories::build_ternary_classification_result]),
+/// with one `0`, `1` or `?` symbol per property (in the given `bit_order`).
+pub fn ternary_label(validity: &[Verdict], bit_order: BitOrder) -> String {
+    let symbols = validity.iter().map(|verdict| verdict.symbol());
+    match bit_order {
+        BitOrder::MsbFirst => symbols.collect(),
+        BitOrder::LsbFirst => symbols.rev().collect(),
+    }
+}
+
+/// Write the plain-text report of a ternary classification `result` into the `writer` (with
+/// the assertions, the properties, and the size of each category). Only the
+/// [OutputOptions::cardinality_format] and [OutputOptions::bit_order] of the `options`
+/// are used.
+pub fn write_ternary_report<W: Write, S: ClassifiedSet>(
+    writer: &mut W,
+    result: &TernaryClassificationResult<S>,
+    options: &OutputOptions,
+) -> Result<(), ClassifierError> {
+    let format = options.cardinality_format;
+    let non_empty = result
+        .categories
+        .iter()
+        .filter(|category| !category.colors.is_empty())
+        .count();
+    writeln!(writer, "{non_empty} non-empty ternary classes")?;
+    writeln!(writer)?;
+    write_assertion_section(writer, &result.assertion_formulae)?;
+    writeln!(
+        writer,
+        "{} {} satisfy all assertions",
+        format.format(result.valid_cardinality, &result.valid_exact_cardinality),
+        S::ELEMENTS
+    )?;
+    writeln!(writer)?;
+
+    writeln!(writer, "### Property formulae")?;
+    writeln!(writer)?;
+    for (name, formula) in &result.properties {
+        writeln!(writer, "# {name}  |  {formula}")?;
+    }
+    writeln!(writer)?;
+
+    writeln!(
+        writer,
+        "### Ternary classes (0 = refuted, 1 = satisfied, ? = unknown)"
+    )?;
+    writeln!(writer)?;
+    for category in &result.categories {
+        writeln!(
+            writer,
+            "# {}",
+            ternary_label(&category.validity, options.bit_order)
+        )?;
+        writeln!(
+            writer,
+            "{} {} in this category ({:.1}%)",
+            format.format(category.cardinality, &category.exact_cardinality),
+            S::ELEMENTS,
+            percentage(category.cardinality, result.valid_cardinality),
+        )?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// Classify the same properties within several labelled universes (e.g., the valid colors of
+/// different assertion sets) and write all the results into the `archive_name` zip. Each
+/// universe is given by its label, its assertion formulae and its colors.
+///
+/// The complete output of each universe (see [write_classification_result_with_options]) is
+/// nested in a directory named by its label. The top-level `report.txt` then contains one section
+/// with the (text) report of each universe, and the top-level `model.aeon` the original model.
+/// The labels must be unique plain directory names (see [ClassifierError::InvalidUniverseLabel]).
+/// The property results are restricted to each universe, so the property cardinalities only
+/// count its colors. The universes cannot be split into two archives, so
+/// [OutputOptions::split_bdds] is rejected, while the optional report sidecar contains the
+/// top-level report.
+pub fn write_universes_classification(
+    universes: &[(String, Vec<String>, GraphColors)],
+    named_property_formulae: &[(String, String)],
+    property_results: &[GraphColors],
+    original_model_str: &str,
+    archive_name: &str,
+    options: &OutputOptions,
+) -> Result<(), ClassifierError> {
+    if options.split_bdds {
+        return Err(ClassifierError::UnsupportedOption("split_bdds".to_string()));
+    }
+    let mut labels = HashSet::new();
+    for (label, _, _) in universes {
+        if !is_valid_class_name(label) || label == "." || !labels.insert(label) {
+            return Err(ClassifierError::InvalidUniverseLabel(label.clone()));
+        }
+    }
+    let mut results = Vec::with_capacity(universes.len());
+    for (label, assertion_formulae, universe) in universes {
+        let universe_results: Vec<GraphColors> = property_results
+            .iter()
+            .map(|colors| colors.intersect(universe))
+            .collect();
+        let result = build_classification_result(
+            assertion_formulae,
+            universe,
+            named_property_formulae,
+            &universe_results,
+        )?;
+        results.push((label, result));
+    }
+
+    write_archive_file(
+        archive_name,
+        options.overwrite,
+        options.retry,
+        |zip_writer| {
+            for (label, result) in &results {
+                let mut universe_options = options.clone();
+                universe_options.layout.prefix = format!("{}{label}/", options.layout.prefix);
+                let model = original_model_str;
+                write_entries(
+                    result,
+                    zip_writer,
+                    model,
+                    None,
+                    &universe_options,
+                    WriterHooks::default(),
+                )?;
+            }
+
+            let layout = &options.layout;
+            let file_options = entry_options(options.timestamp.unwrap_or_else(SystemTime::now));
+            let labels = ClassLabels::from(options.bit_order);
+            zip_writer.start_entry(&layout.entry(&layout.report), file_options)?;
+            for (label, result) in &results {
+                writeln!(zip_writer, "## Universe `{label}`")?;
+                writeln!(zip_writer)?;
+                write_report_intro(zip_writer, result, options.cardinality_format)?;
+                write_report_classes(zip_writer, result, options, labels, None)?;
+            }
+            zip_writer.start_entry(&layout.entry(&layout.model), file_options)?;
+            write!(zip_writer, "{original_model_str}")?;
+            Ok(())
+        },
+    )?;
+    if options.report_sidecar {
+        write_report_sidecar(
+            archive_name,
+            &options.layout,
+            options.overwrite,
+            options.retry,
+        )?;
+    }
+    Ok(())
+}
+
+/// Write a classification given by an explicit list of `named_sets` (e.g., clusters of colors),
+/// instead of the combinations of the property results, into the `archive_name` zip.
+///
+/// The sets must be pairwise disjoint subsets of `all_valid_colors` (otherwise, the error is
+/// [ClassifierError::PartitionInvariantViolated]), but they do not need to cover all of them.
+/// The output is the same as for a [ClassificationResult] (configured by the `options`), where
+/// the membership in each set is one property (with an empty formula), and each class is named
+/// by its set (see [ClassificationWriter::with_class_names]). The classes keep their order.
+pub fn write_named_classes(
+    named_sets: &[(String, GraphColors)],
+    all_valid_colors: &GraphColors,
+    archive_name: &str,
+    original_model_str: &str,
+    options: &OutputOptions,
+) -> Result<(), ClassifierError> {
+    let mut covered = all_valid_colors.minus(all_valid_colors);
+    for (name, colors) in named_sets {
+        if !colors.is_subset(all_valid_colors) || !colors.intersect(&covered).is_empty() {
+            return Err(ClassifierError::PartitionInvariantViolated(format!(
+                "class `{name}` is not a disjoint subset of the valid colors"
+            )));
+        }
+        covered = covered.union(colors);
+    }
+
+    let properties = named_sets
+        .iter()
+        .map(|(name, colors)| PropertyResult {
+            name: name.clone(),
+            formula: String::new(),
+            colors: colors.clone(),
+            cardinality: colors.approx_cardinality(),
+            exact_cardinality: colors.exact_cardinality(),
+            discriminating: !colors.is_empty() && !colors_equal(colors, all_valid_colors),
+        })
+        .collect();
+    let categories = named_sets
+        .iter()
+        .enumerate()
+        .map(|(i, (_, colors))| Category {
+            validity: (0..named_sets.len()).map(|j| i == j).collect(),
+            colors: colors.clone(),
+            cardinality: colors.approx_cardinality(),
+            exact_cardinality: colors.exact_cardinality(),
+        })
+        .collect();
+    let result = ClassificationResult {
+        assertion_formulae: Vec::new(),
+        valid_colors: all_valid_colors.clone(),
+        valid_cardinality: all_valid_colors.approx_cardinality(),
+        valid_exact_cardinality: all_valid_colors.exact_cardinality(),
+        total_cardinality: None,
+        assertion_cardinalities: Vec::new(),
+        properties,
+        projected_properties: Vec::new(),
+        num_hctl_vars: 0,
+        formula_hctl_vars: Vec::new(),
+        enumeration_time: Duration::ZERO,
+        peak_bdd_size: 0,
+        categories,
+        cardinalities_only: false,
+    };
+
+    let names: Vec<String> = named_sets.iter().map(|(name, _)| name.clone()).collect();
+    let class_names = move |validity: &[bool]| {
+        let i = validity.iter().position(|valid| *valid)?;
+        Some(names[i].clone())
+    };
+    let hooks = WriterHooks {
+        class_names: Some(&class_names),
+        ..WriterHooks::default()
+    };
+    write_result_file(
+        &result,
+        archive_name,
+        original_model_str,
+        None,
+        options,
+        hooks,
+    )
+}
+
+/// Create classification archive for an arbitrary "map" of `string -> color set`.
+///
+/// Categories with equal color sets share one BDD dump (see `bdd_index.txt` in the archive).
+pub fn build_classification_archive(
+    categories: HashMap<String, GraphColors>,
+    archive_name: &str,
+    original_model_str: &str,
+) -> Result<(), ClassifierError> {
+    write_archive_file(archive_name, true, RetryPolicy::default(), |zip_writer| {
+        // If the BDD is not empty, the results go directly into the zip archive. The categories
+        // are sorted by name, so that the deduplication of equal sets is deterministic.
+        let mut named_sets: Vec<(String, &GraphColors)> = categories
+            .iter()
+            .filter(|(_, colors)| !colors.is_empty())
+            .map(|(name, colors)| (name.clone(), colors))
+            .collect();
+        named_sets.sort_by(|(x, _), (y, _)| x.cmp(y));
+        let options = OutputOptions::default();
+        let layout = &options.layout;
+        let bdd_index = write_bdd_dumps(
+            zip_writer,
+            &named_sets,
+            &options,
+            SystemTime::now(),
+            None,
+            None,
+            None,
+        )?;
+        write_bdd_index(
+            zip_writer,
+            layout,
+            &bdd_index,
+            &HashSet::new(),
+            FileOptions::default(),
+        )?;
+
+        // Include the original model in the result bundle (we need to load it later).
+        zip_writer.start_file(layout.entry(&layout.model), FileOptions::default())?;
+        write!(zip_writer, "{original_model_str}")?;
+
+        Ok(())
+    })
+}
+
+/// Write the archive of a classification computation where the assertions were not satisfied
+/// by any color of the model given by `original_model_str`. As with the full results, the
+/// archive also contains the `metadata.txt` (with the number of HCTL variables `num_hctl_vars`
+/// and the counts of the individual formulae `formula_hctl_vars`), the model, the formulae and
+/// the machine-readable report (without any classes), so that it can be loaded and merged as
+/// any other archive.
+pub fn write_empty_report(
+    assertion_formulae: &[String],
+    num_hctl_vars: usize,
+    formula_hctl_vars: &[(String, usize)],
+    original_model_str: &str,
+    archive_name: &str,
+) -> Result<(), ClassifierError> {
+    write_empty_report_with_breakdown(
+        assertion_formulae,
+        &[],
+        num_hctl_vars,
+        formula_hctl_vars,
+        original_model_str,
+        archive_name,
+        &OutputOptions::default(),
+    )
+}
+
+/// Same as [write_empty_report], but the report also lists the number of colors satisfying
+/// each assertion individually (`assertion_cardinalities`, in the order of the assertions),
+/// which shows the assertions that cannot be satisfied (see
+/// [crate::classification::check_assertions_individually]). Without any cardinalities, the
+/// list is omitted. The archive is written according to the `options`.
+pub fn write_empty_report_with_breakdown(
+    assertion_formulae: &[String],
+    assertion_cardinalities: &[f64],
+    num_hctl_vars: usize,
+    formula_hctl_vars: &[(String, usize)],
+    original_model_str: &str,
+    archive_name: &str,
+    options: &OutputOptions,
+) -> Result<(), ClassifierError> {
+    let bn = BooleanNetwork::try_from(original_model_str).map_err(ClassifierError::Model)?;
+    let graph = SymbolicAsyncGraph::new(bn).map_err(ClassifierError::Model)?;
+    let mut result =
+        build_classification_result(assertion_formulae, &graph.mk_empty_colors(), &[], &[])?;
+    result.num_hctl_vars = num_hctl_vars;
+    result.formula_hctl_vars = formula_hctl_vars.to_vec();
+    result.total_cardinality = Some(graph.mk_unit_colors().approx_cardinality());
+    result.assertion_cardinalities = assertion_cardinalities.to_vec();
+    write_result_file(
+        &result,
+        archive_name,
+        original_model_str,
+        Some(&graph),
+        options,
+        WriterHooks::default(),
+    )
+}
+
+/// Write the text report of a result without any valid colors, i.e., the assertions and the
+/// optional numbers of colors satisfying them individually (in the order of the assertions).
+fn write_empty_report_text<W: Write>(
+    report: &mut W,
+    assertion_formulae: &[String],
+    assertion_cardinalities: &[f64],
+) -> Result<(), std::io::Error> {
+    writeln!(report, "0 non-empty classes covering 0 colors")?;
+    writeln!(report)?;
+    write_assertion_section(report, assertion_formulae)?;
+    writeln!(report, "0 colors satisfy combination of all assertions")?;
+    writeln!(report)?;
+    if !assertion_cardinalities.is_empty() {
+        writeln!(report, "### Satisfaction of individual assertions")?;
+        writeln!(report)?;
+        for (i, cardinality) in assertion_cardinalities.iter().enumerate() {
+            writeln!(
+                report,
+                "Assertion {} is satisfied by {cardinality:.0} colors",
+                i + 1
+            )?;
+        }
+        writeln!(report)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::categories::tests::prepare_graph_and_sets;
+    use crate::categories::{
+        build_classification_result, build_projected_classification_result,
+        build_ternary_classification_result, property_overlaps, ClassificationResult,
+    };
+    use crate::errors::ClassifierError;
+    use crate::write_output::{
+        bool_vec_to_string, escape_xml, find_duplicate_sets, format_cardinality, percentage,
+        prepare_bdd_dumps, prepare_report_json, split_archive_names, write_absent_classes,
+        write_bdd, write_class_report_to_dir, write_classes_csv, write_classes_legend,
+        write_classification_entries, write_classification_result_to_writer,
+        write_classification_result_with_graph, write_classification_result_with_options,
+        write_empty_report, write_empty_report_with_breakdown, write_lattice_graphml,
+        write_metadata, write_named_classes, write_overlaps_csv, write_report_classes,
+        write_report_intro, write_report_json_gz, write_report_markdown, write_report_xml,
+        write_ternary_report, write_text_report, write_universes_classification, ArchiveLayout,
+        BitOrder, CardinalityFormat, ClassificationWriter, CompressionOptions, JsonBdds,
+        OutputOptions, RetryPolicy,
+    };
+    use base64ct::{Base64, Encoding};
+    use biodivine_hctl_model_checker::mc_utils::get_extended_symbolic_graph;
+    use biodivine_lib_bdd::Bdd;
+    use biodivine_lib_param_bn::biodivine_std::traits::Set;
+    use biodivine_lib_param_bn::symbolic_async_graph::GraphColors;
+    use flate2::read::GzDecoder;
+    use num_bigint::BigInt;
+    use serde_json::json;
+    use std::cell::RefCell;
+    use std::fs::File;
+    use std::io::{Cursor, Read};
+    use std::path::Path;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    use zip::write::{FileOptions, ZipWriter};
+    use zip::{CompressionMethod, ZipArchive};
+
+    #[test]
+    fn test_bool_vec_to_string() {
+        assert_eq!(bool_vec_to_string(&[]), "".to_string());
+        assert_eq!(bool_vec_to_string(&[false]), "0".to_string());
+        assert_eq!(bool_vec_to_string(&[true]), "1".to_string());
+        assert_eq!(bool_vec_to_string(&[true, false]), "10".to_string());
+        assert_eq!(bool_vec_to_string(&[true, true, false]), "110".to_string());
+    }
+
+    #[test]
+    fn test_bit_order_label() {
+        assert_eq!(
+            BitOrder::MsbFirst.label(&[true, true, false]),
+            "110".to_string()
+        );
+        assert_eq!(
+            BitOrder::LsbFirst.label(&[true, true, false]),
+            "011".to_string()
+        );
+        assert_eq!(BitOrder::LsbFirst.label(&[]), "".to_string());
+    }
+
+    #[test]
+    /// Test that the metadata keep the HCTL variable count on the first line.
+    fn test_metadata() {
+        let mut metadata = Vec::new();
+        let formula_hctl_vars = vec![("assertion_1".to_string(), 3), ("p1".to_string(), 1)];
+        write_metadata(
+            &mut metadata,
+            3,
+            &formula_hctl_vars,
+            &[("peak", 12)],
+            BitOrder::MsbFirst,
+            SystemTime::now(),
+            None,
+        )
+        .unwrap();
+        let metadata = String::from_utf8(metadata).unwrap();
+        let lines: Vec<&str> = metadata.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[0], "3");
+        assert_eq!(lines[1], format!("version={}", env!("CARGO_PKG_VERSION")));
+        assert!(lines[2].starts_with("timestamp="));
+        assert!(lines[2].ends_with('Z'));
+        assert_eq!(lines[3], "hctl_vars.assertion_1=3");
+        assert_eq!(lines[4], "hctl_vars.p1=1");
+        assert_eq!(lines[5], "bdd_nodes.peak=12");
+        assert_eq!(lines[6], "bit_order=msb_first");
+    }
+
+    #[test]
+    fn test_percentage() {
+        assert_eq!(percentage(1.0, 4.0), 25.0);
+        assert_eq!(percentage(4.0, 4.0), 100.0);
+        assert_eq!(percentage(0.0, 0.0), 0.0);
+        assert_eq!(format!("{:.1}", percentage(123.0, 2674.0)), "4.6");
+    }
+
+    #[test]
+    /// Test that the JSON report lists exactly the non-empty categories.
+    fn test_prepare_report_json() {
+        let (graph, sets) = prepare_graph_and_sets();
+        // Second property is a subset of the first one, so category `01` is empty.
+        let implied = sets[0].intersect(&sets[1]);
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "3{x}: @{x}: AX (b & {x})".to_string()),
+        ];
+        let result = build_classification_result(
+            &["true".to_string()],
+            graph.unit_colors(),
+            &properties,
+            &[sets[0].clone(), implied],
+        )
+        .unwrap();
+
+        let index = vec![
+            ("00".to_string(), "bdd_dump_00.txt".to_string()),
+            ("10".to_string(), "bdd_dump_10.txt".to_string()),
+            ("11".to_string(), "bdd_dump_00.txt".to_string()),
+        ];
+        let report = prepare_report_json(&result, &index, BitOrder::MsbFirst.into());
+        assert_eq!(report["valid_cardinality"], 16.0);
+        assert_eq!(report["num_classes"], 3);
+        assert_eq!(report["classified_cardinality"], 16.0);
+        assert_eq!(report["classified_exact_cardinality"], "16");
+        assert_eq!(report["properties"][1]["name"], "p2");
+        assert_eq!(report["properties"][1]["cardinality"], 4.0);
+        assert_eq!(report["properties"][1]["network_variables"], json!(["b"]));
+        assert_eq!(report["properties"][1]["hctl_variables"], json!(["x"]));
+        assert_eq!(report["properties"][0]["network_variables"], json!([]));
+        let classes = report["classes"].as_array().unwrap();
+        let bits: Vec<&str> = classes
+            .iter()
+            .map(|it| it["class_bits"].as_str().unwrap())
+            .collect();
+        assert_eq!(bits, vec!["00", "10", "11"]);
+        assert_eq!(classes[1]["bdd_file"], "bdd_dump_10.txt");
+        assert_eq!(classes[2]["bdd_file"], "bdd_dump_00.txt");
+        assert_eq!(classes[1]["cardinality"], 4.0);
+        assert_eq!(classes[1]["exact_cardinality"], "4");
+        assert_eq!(classes[1]["bdd_size"], 4);
+    }
+
+    #[test]
+    /// Test that a failure to create the archive is propagated as an error.
+    fn test_write_error_propagated() {
+        // The temporary directory exists, but it is not a file we can create.
+        let dir = std::env::temp_dir();
+        let result = write_empty_report(&[], 0, &[], "a -?? b\n", dir.to_str().unwrap());
+        assert!(matches!(result, Err(ClassifierError::Io(_))));
+    }
+
+    #[test]
+    /// Test that the `.dot` dumps are only written when requested.
+    fn test_dot_dumps() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+        let model = "a -?? b\nb -?? a\n";
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-dot-dumps.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        let options = OutputOptions {
+            dot_dumps: true,
+            ..Default::default()
+        };
+        write_classification_result_with_options(&result, archive_name, model, &options).unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        assert!(archive.by_name("bdd_dump_1.txt").is_ok());
+        assert!(archive.by_name("bdd_dump_1.dot").is_ok());
+        assert!(archive.by_name("bdd_dump_0.dot").is_ok());
+
+        write_classification_result_with_options(
+            &result,
+            archive_name,
+            model,
+            &OutputOptions::default(),
+        )
+        .unwrap();
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        assert!(archive.by_name("bdd_dump_1.txt").is_ok());
+        assert!(archive.by_name("bdd_dump_1.dot").is_err());
+        std::fs::remove_file(archive_path).unwrap();
+    }
+
+    #[test]
+    /// Test that the dumps prepared in advance match the sequentially written ones, and that
+    /// duplicate sets are not prepared again.
+    fn test_prepare_bdd_dumps() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let named_sets = vec![
+            ("00".to_string(), &sets[0]),
+            ("01".to_string(), &sets[1]),
+            ("10".to_string(), &sets[0]),
+        ];
+        let duplicate_of = find_duplicate_sets(&named_sets);
+        assert_eq!(duplicate_of, vec![None, None, Some(0)]);
+
+        let context = graph.symbolic_context();
+        let prepared =
+            prepare_bdd_dumps(&named_sets, &duplicate_of, None, 0, false, Some(context)).unwrap();
+        assert_eq!(prepared.len(), 3);
+        assert!(prepared[2].is_none());
+        for (i, dump) in prepared.iter().take(2).enumerate() {
+            let dump = dump.as_ref().unwrap();
+            let mut expected = Vec::new();
+            write_bdd(&mut expected, sets[i].as_bdd(), false).unwrap();
+            assert_eq!(dump.data, expected);
+            assert_eq!(dump.dot, Some(sets[i].to_dot_string(context)));
+        }
+    }
+
+    #[test]
+    /// Test that sorted report only lists non-empty classes, largest first.
+    fn test_report_classes_sorted() {
+        let (graph, sets) = prepare_graph_and_sets();
+        // Sizes of categories `00`, `01`, `10`, `11` are 4, 0, 8, 4.
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let property_results = vec![sets[0].union(&sets[1]), sets[0].intersect(&sets[1])];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+
+        let options = OutputOptions {
+            sort_classes_by_size: true,
+            ..Default::default()
+        };
+        let mut report = Vec::new();
+        write_report_classes(
+            &mut report,
+            &result,
+            &options,
+            BitOrder::MsbFirst.into(),
+            None,
+        )
+        .unwrap();
+        let report = String::from_utf8(report).unwrap();
+        let headers: Vec<&str> = report.lines().filter(|it| it.starts_with('#')).collect();
+        assert_eq!(headers, vec!["# 10", "# 00", "# 11"]);
+    }
+
+    #[test]
+    /// Test that the configured compression is only applied to the BDD dumps.
+    fn test_bdd_compression() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-compression.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        let options = OutputOptions {
+            bdd_compression: CompressionOptions {
+                method: CompressionMethod::Stored,
+                level: None,
+            },
+            ..Default::default()
+        };
+        write_classification_result_with_options(&result, archive_name, "", &options).unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let bdd_method = archive.by_name("bdd_dump_1.txt").unwrap().compression();
+        let report_method = archive.by_name("report.txt").unwrap().compression();
+        assert_eq!(bdd_method, CompressionMethod::Stored);
+        assert_eq!(report_method, CompressionMethod::Deflated);
+        std::fs::remove_file(archive_path).unwrap();
+    }
+
+    #[test]
+    /// Test that the entry names follow the configured layout.
+    fn test_archive_layout() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-layout.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        let options = OutputOptions {
+            layout: ArchiveLayout {
+                prefix: "classification/".to_string(),
+                report: "summary.md".to_string(),
+                bdd_dump: "class_{name}.bdd".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        write_classification_result_with_options(&result, archive_name, "", &options).unwrap();
+
+        let archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "classification/bdd_index.txt",
+                "classification/class_0.bdd",
+                "classification/class_1.bdd",
+                "classification/classes.csv",
+                "classification/classes_legend.txt",
+                "classification/metadata.txt",
+                "classification/model.aeon",
+                "classification/properties.hctl",
+                "classification/report.json",
+                "classification/summary.md",
+            ]
+        );
+        std::fs::remove_file(archive_path).unwrap();
+    }
+
+    #[test]
+    /// Test the tables of the Markdown report.
+    fn test_write_report_markdown() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "a | b".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let mut report = Vec::new();
+        write_report_markdown(
+            &mut report,
+            &result,
+            &OutputOptions::default(),
+            BitOrder::MsbFirst.into(),
+        )
+        .unwrap();
+        let report = String::from_utf8(report).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert!(lines.contains(&"## Classes"));
+        assert!(lines.contains(&"| p1 | `a \\| b` | 8 | 50.0% |"));
+        assert!(lines.contains(&"| `0` | 8 | 50.0% |"));
+        assert!(lines.contains(&"| `1` | 8 | 50.0% |"));
+        assert!(lines.contains(&"| Class | Colors | Percentage |"));
+
+        // The cardinalities follow the chosen format.
+        let mut result = result;
+        result.valid_cardinality = 1234567.0;
+        result.valid_exact_cardinality = BigInt::from(1234567);
+        let options = OutputOptions {
+            cardinality_format: CardinalityFormat::Grouped,
+            ..Default::default()
+        };
+        let mut report = Vec::new();
+        write_report_markdown(&mut report, &result, &options, BitOrder::MsbFirst.into()).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.contains("1,234,567 colors satisfy all assertions."));
+    }
+
+    #[test]
+    /// Test that the CSV table has a column per property and a row per non-empty category.
+    fn test_classes_csv() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p, 2".to_string(), "true".to_string()),
+        ];
+        // Category `01` is empty.
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+        let bdd_index = vec![
+            ("00".to_string(), "bdd_dump_00.txt".to_string()),
+            ("10".to_string(), "bdd_dump_10.txt".to_string()),
+            ("11".to_string(), "bdd_dump_11.txt".to_string()),
+        ];
+
+        let mut csv = Vec::new();
+        write_classes_csv(&mut csv, &result, &bdd_index, BitOrder::MsbFirst.into()).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "p1,\"p, 2\",cardinality,bdd_file",
+                "false,false,8,bdd_dump_00.txt",
+                "true,false,4,bdd_dump_10.txt",
+                "true,true,4,bdd_dump_11.txt",
+            ]
+        );
+    }
+
+    #[test]
+    /// Test that the lattice connects the classes differing in one property.
+    fn test_lattice_graphml() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        // Category `01` is empty, so `00` and `11` are the only neighbours of `10`.
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+
+        let mut graphml = Vec::new();
+        write_lattice_graphml(&mut graphml, &result, BitOrder::MsbFirst.into()).unwrap();
+        let graphml = String::from_utf8(graphml).unwrap();
+        assert_eq!(graphml.matches("<node ").count(), 3);
+        assert!(graphml.contains("<data key=\"cardinality\">8</data>"));
+        let edges: Vec<&str> = graphml
+            .lines()
+            .filter(|line| line.contains("<edge "))
+            .map(|line| line.trim())
+            .collect();
+        assert_eq!(
+            edges,
+            vec![
+                r#"<edge source="00" target="10">"#,
+                r#"<edge source="10" target="11">"#
+            ]
+        );
+        assert!(graphml.contains("<data key=\"property\">p2</data>"));
+    }
+
+    #[test]
+    /// Test the CSV matrix of the pairwise property overlaps.
+    fn test_overlaps_csv() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p, 2".to_string(), "true".to_string()),
+        ];
+        let property_results = vec![sets[0].clone(), sets[1].clone()];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+
+        let mut csv = Vec::new();
+        write_overlaps_csv(&mut csv, &result, &property_overlaps(&result)).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            vec!["property,p1,\"p, 2\"", "p1,8,4", "\"p, 2\",4,8"]
+        );
+    }
+
+    #[test]
+    /// Test that the archive can be written into an in-memory buffer.
+    fn test_write_to_writer() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let buffer = write_classification_result_to_writer(
+            &result,
+            Cursor::new(Vec::new()),
+            "",
+            None,
+            &OutputOptions::default(),
+        )
+        .unwrap();
+        let archive = ZipArchive::new(Cursor::new(buffer.into_inner())).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "bdd_dump_0.txt",
+                "bdd_dump_1.txt",
+                "bdd_index.txt",
+                "classes.csv",
+                "classes_legend.txt",
+                "metadata.txt",
+                "model.aeon",
+                "properties.hctl",
+                "report.json",
+                "report.txt",
+            ]
+        );
+    }
+
+    #[test]
+    /// Test that an archive given by a bare file name is written into the current directory.
+    fn test_write_to_bare_file_name() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let archive_name = "bn-classifier-test-bare.zip";
+        write_classification_result_with_options(
+            &result,
+            archive_name,
+            "",
+            &OutputOptions::default(),
+        )
+        .unwrap();
+        assert!(std::path::Path::new(archive_name).is_file());
+        std::fs::remove_file(archive_name).unwrap();
+    }
+
+    #[test]
+    /// Test that no BDDs are written into a report-only archive.
+    fn test_report_only_archive() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let options = OutputOptions {
+            include_bdds: false,
+            dot_dumps: true,
+            ..Default::default()
+        };
+        let buffer = write_classification_result_to_writer(
+            &result,
+            Cursor::new(Vec::new()),
+            "",
+            None,
+            &options,
+        )
+        .unwrap();
+        let archive = ZipArchive::new(Cursor::new(buffer.into_inner())).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "classes.csv",
+                "classes_legend.txt",
+                "metadata.txt",
+                "model.aeon",
+                "properties.hctl",
+                "report.json",
+                "report.txt",
+            ]
+        );
+    }
+
+    #[test]
+    /// Test that the exact cardinality is only shown when the approximation is imprecise.
+    fn test_format_cardinality() {
+        assert_eq!(format_cardinality(16.0, &BigInt::from(16), false), "16");
+        let exact = BigInt::from(2).pow(60) + 1;
+        assert_eq!(
+            format_cardinality(2.0f64.powi(60), &exact, false),
+            "1152921504606846976 (exactly 1152921504606846977)"
+        );
+        // Small counts are written in full even when the scientific notation is enabled.
+        assert_eq!(format_cardinality(16.0, &BigInt::from(16), true), "16");
+        let exact = BigInt::from(2).pow(128);
+        assert_eq!(
+            format_cardinality(2.0f64.powi(128), &exact, true),
+            "3.40e38 (exactly 340282366920938463463374607431768211456)"
+        );
+    }
+
+    #[test]
+    /// Test that the report lists the implications between the properties.
+    fn test_report_implications() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let property_results = vec![sets[0].intersect(&sets[1]), sets[0].clone()];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+
+        let mut report = Vec::new();
+        write_report_intro(&mut report, &result, CardinalityFormat::Fixed).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.contains("### Property implications\n\np1 => p2\n\n### Classes"));
+    }
+
+    #[test]
+    /// Test that the declared number of HCTL variables must match the variables of the BDDs.
+    fn test_inconsistent_hctl_vars() {
+        let (graph, _) = prepare_graph_and_sets();
+        let extended_graph = get_extended_symbolic_graph(graph.as_network(), 2).unwrap();
+        let mut result =
+            build_classification_result(&[], extended_graph.unit_colors(), &[], &[]).unwrap();
+
+        let write = |result: &ClassificationResult| {
+            let writer = Cursor::new(Vec::new());
+            let options = OutputOptions::default();
+            write_classification_result_to_writer(result, writer, "", Some(&graph), &options)
+        };
+        let error = write(&result).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            ClassifierError::InconsistentHctlVars {
+                declared: 0,
+                bdd_vars: 10
+            }
+            .to_string()
+        );
+        result.num_hctl_vars = 2;
+        assert!(write(&result).is_ok());
+    }
+
+    #[test]
+    /// Test the rendering of the cardinalities in the individual formats.
+    fn test_cardinality_format() {
+        let exact = BigInt::from(2).pow(128);
+        let approx = 2.0f64.powi(128);
+        assert_eq!(
+            CardinalityFormat::default().format(1048576.0, &BigInt::from(1048576)),
+            "1048576"
+        );
+        assert_eq!(
+            CardinalityFormat::Exact.format(approx, &exact),
+            "340282366920938463463374607431768211456"
+        );
+        assert_eq!(
+            CardinalityFormat::Grouped.format(1048576.0, &BigInt::from(1048576)),
+            "1,048,576"
+        );
+        assert_eq!(
+            CardinalityFormat::Grouped.format(100.0, &BigInt::from(100)),
+            "100"
+        );
+        assert_eq!(
+            CardinalityFormat::Grouped.format(-1000.0, &BigInt::from(-1000)),
+            "-1,000"
+        );
+        let custom = CardinalityFormat::Custom(|approx, _| format!("~{approx:.1}"));
+        assert_eq!(custom.format(16.0, &BigInt::from(16)), "~16.0");
+    }
+
+    #[test]
+    /// Test the legend of the class names.
+    fn test_classes_legend() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        // Category `01` is empty.
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+
+        let mut legend = Vec::new();
+        write_classes_legend(&mut legend, &result, BitOrder::MsbFirst.into()).unwrap();
+        let legend = String::from_utf8(legend).unwrap();
+        assert_eq!(
+            legend,
+            "### Bit positions (from the left)\n\n1: p1\n2: p2\n\n\
+             ### Classes\n\n00: ¬p1 ∧ ¬p2\n10: p1 ∧ ¬p2\n11: p1 ∧ p2\n"
+        );
+
+        let mut legend = Vec::new();
+        write_classes_legend(&mut legend, &result, BitOrder::LsbFirst.into()).unwrap();
+        let legend = String::from_utf8(legend).unwrap();
+        assert_eq!(
+            legend,
+            "### Bit positions (from the left)\n\n1: p2\n2: p1\n\n\
+             ### Classes\n\n00: ¬p1 ∧ ¬p2\n01: p1 ∧ ¬p2\n11: p1 ∧ p2\n"
+        );
+    }
+
+    #[test]
+    /// Test that the non-discriminating properties are flagged in the report.
+    fn test_non_discriminating_warning() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("all".to_string(), "true".to_string()),
+        ];
+        let property_results = vec![sets[0].clone(), graph.unit_colors().clone()];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+
+        let mut report = Vec::new();
+        write_report_intro(&mut report, &result, CardinalityFormat::Fixed).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        let warnings: Vec<&str> = report
+            .lines()
+            .filter(|it| it.starts_with("WARNING"))
+            .collect();
+        assert_eq!(
+            warnings,
+            vec!["WARNING: This property is non-discriminating (it holds for all colors)"]
+        );
+    }
+
+    #[test]
+    /// Test that the ternary report labels the classes by `0/1/?` in the chosen bit order.
+    fn test_ternary_report() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let unit = graph.unit_colors();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let satisfied = vec![sets[0].clone(), sets[1].clone()];
+        let refuted = vec![
+            unit.minus(&sets[0]),
+            unit.minus(&sets[1]).intersect(&sets[2]),
+        ];
+        let result =
+            build_ternary_classification_result(&[], unit, &properties, &satisfied, &refuted)
+                .unwrap();
+
+        for (bit_order, label) in [(BitOrder::MsbFirst, "# 0?"), (BitOrder::LsbFirst, "# ?0")] {
+            let options = OutputOptions {
+                bit_order,
+                ..Default::default()
+            };
+            let mut report = Vec::new();
+            write_ternary_report(&mut report, &result, &options).unwrap();
+            let report = String::from_utf8(report).unwrap();
+            assert!(report.starts_with("6 non-empty ternary classes\n"));
+            assert!(report.contains(&format!("{label}\n2 colors in this category (12.5%)\n")));
+        }
+    }
+
+    #[test]
+    /// Test that the report lists the variables of each category BDD by their names.
+    fn test_bdd_support_report() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+        let options = OutputOptions {
+            bdd_support: true,
+            ..Default::default()
+        };
+
+        let variables = graph.symbolic_context().bdd_variable_set();
+        let parameter = graph.symbolic_context().parameter_variables()[0];
+        let expected = format!(
+            "BDD of this category depends on: {}",
+            variables.name_of(parameter)
+        );
+        for (graph, expected) in [
+            (Some(&graph), expected),
+            (
+                None,
+                format!("BDD of this category depends on: x_{parameter}"),
+            ),
+        ] {
+            let mut report = Vec::new();
+            write_report_classes(
+                &mut report,
+                &result,
+                &options,
+                BitOrder::MsbFirst.into(),
+                graph,
+            )
+            .unwrap();
+            let report = String::from_utf8(report).unwrap();
+            let lines: Vec<&str> = report
+                .lines()
+                .filter(|line| line.contains("depends on"))
+                .collect();
+            assert_eq!(lines, vec![expected.as_str(); 2]);
+        }
+    }
+
+    #[test]
+    /// Test that the cardinalities are also given as a fraction of the whole color space
+    /// when it is known.
+    fn test_total_cardinality_fraction() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let mut result =
+            build_classification_result(&[], &sets[1], &properties, &sets[0..1]).unwrap();
+        result.total_cardinality = Some(graph.unit_colors().approx_cardinality());
+
+        let mut report = Vec::new();
+        write_report_intro(&mut report, &result, CardinalityFormat::Fixed).unwrap();
+        write_report_classes(
+            &mut report,
+            &result,
+            &OutputOptions::default(),
+            BitOrder::MsbFirst.into(),
+            None,
+        )
+        .unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.contains("8 colors satisfy all assertions (50.0% of all parameterizations)"));
+        assert!(
+            report.contains("4 colors in this category (50.0%; 25.0% of all parameterizations)")
+        );
+    }
+
+    #[test]
+    /// Test that the metadata record the largest BDD of the classification and the total size
+    /// of the dumped BDDs (only if there are any dumps).
+    fn test_bdd_node_metadata() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+        // Both categories (and the property) are given by a single variable, i.e., three nodes.
+        assert_eq!(result.peak_bdd_size, 3);
+
+        let read_metadata = |include_bdds: bool| {
+            let buffer = ClassificationWriter::new("")
+                .include_bdds(include_bdds)
+                .write_to(&result, Cursor::new(Vec::new()))
+                .unwrap();
+            let mut archive = ZipArchive::new(buffer).unwrap();
+            let mut metadata = String::new();
+            archive
+                .by_name("metadata.txt")
+                .unwrap()
+                .read_to_string(&mut metadata)
+                .unwrap();
+            metadata
+        };
+        let metadata = read_metadata(true);
+        assert!(metadata.contains("bdd_nodes.peak=3\nbdd_nodes.dumped=6\n"));
+        let metadata = read_metadata(false);
+        assert!(metadata.contains("bdd_nodes.peak=3\n"));
+        assert!(!metadata.contains("bdd_nodes.dumped"));
+    }
+
+    #[test]
+    /// Test that the explicitly given classes are written with their names, and that
+    /// overlapping classes are rejected.
+    fn test_write_named_classes() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let unit = graph.unit_colors();
+        let low = unit.minus(&sets[0]).minus(&sets[1]);
+        let high = sets[0].intersect(&sets[1]);
+        let named_sets = vec![("low".to_string(), low), ("high".to_string(), high)];
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-named-classes.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        let options = OutputOptions::default();
+        write_named_classes(&named_sets, unit, archive_name, "", &options).unwrap();
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        assert!(archive.by_name("bdd_dump_low.txt").is_ok());
+        assert!(archive.by_name("bdd_dump_high.txt").is_ok());
+        let mut report = String::new();
+        archive
+            .by_name("report.txt")
+            .unwrap()
+            .read_to_string(&mut report)
+            .unwrap();
+        assert!(report.starts_with("2 non-empty classes covering 8 colors\n"));
+        assert!(report.contains("8 valid colors are not covered by any class"));
+        assert!(report.contains("# low\n4 colors in this category (25.0%)"));
+
+        let overlapping = vec![
+            ("a".to_string(), sets[0].clone()),
+            ("b".to_string(), sets[1].clone()),
+        ];
+        let error = write_named_classes(&overlapping, unit, archive_name, "", &options);
+        std::fs::remove_file(&archive_path).unwrap();
+        assert!(matches!(
+            error,
+            Err(ClassifierError::PartitionInvariantViolated(_))
+        ));
+    }
+
+    #[test]
+    /// Test that a result without valid colors is written as the empty report.
+    fn test_empty_result_report() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result = build_classification_result(
+            &["false".to_string()],
+            graph.empty_colors(),
+            &properties,
+            &sets[0..1],
+        )
+        .unwrap();
+
+        let buffer = ClassificationWriter::new("")
+            .write_to(&result, Cursor::new(Vec::new()))
+            .unwrap();
+        let mut archive = ZipArchive::new(buffer).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "bdd_index.txt",
+                "classes.csv",
+                "classes_legend.txt",
+                "metadata.txt",
+                "model.aeon",
+                "properties.hctl",
+                "report.json",
+                "report.txt"
+            ]
+        );
+        let mut report = String::new();
+        archive
+            .by_name("report.txt")
+            .unwrap()
+            .read_to_string(&mut report)
+            .unwrap();
+        assert!(report.contains("0 colors satisfy combination of all assertions"));
+    }
+
+    #[test]
+    /// Test that the empty report archive contains the same entries as the full results.
+    fn test_empty_report() {
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-empty.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        write_empty_report(&["false".to_string()], 2, &[], "a -?? b\n", archive_name).unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "bdd_index.txt",
+                "classes.csv",
+                "classes_legend.txt",
+                "metadata.txt",
+                "model.aeon",
+                "properties.hctl",
+                "report.json",
+                "report.txt"
+            ]
+        );
+        let mut metadata = String::new();
+        archive
+            .by_name("metadata.txt")
+            .unwrap()
+            .read_to_string(&mut metadata)
+            .unwrap();
+        assert_eq!(metadata.lines().next(), Some("2"));
+        let mut report = String::new();
+        archive
+            .by_name("report.txt")
+            .unwrap()
+            .read_to_string(&mut report)
+            .unwrap();
+        assert!(report.starts_with(
+            "0 non-empty classes covering 0 colors\n\n### Assertion formulae\n\n# false\n"
+        ));
+        std::fs::remove_file(archive_path).unwrap();
+    }
+
+    #[test]
+    /// Test that the empty report lists the cardinalities of the individual assertions.
+    fn test_empty_report_breakdown() {
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-empty-breakdown.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        let assertions = vec!["true".to_string(), "false".to_string()];
+        write_empty_report_with_breakdown(
+            &assertions,
+            &[16.0, 0.0],
+            0,
+            &[],
+            "a -?? b\n",
+            archive_name,
+            &OutputOptions::default(),
+        )
+        .unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let mut report = String::new();
+        archive
+            .by_name("report.txt")
+            .unwrap()
+            .read_to_string(&mut report)
+            .unwrap();
+        std::fs::remove_file(archive_path).unwrap();
+        assert!(report.ends_with(
+            "### Satisfaction of individual assertions\n\n\
+            Assertion 1 is satisfied by 16 colors\n\
+            Assertion 2 is satisfied by 0 colors\n\n"
+        ));
+    }
+
+    #[test]
+    /// Test that the representatives describe the unknown update functions.
+    fn test_representatives() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let options = OutputOptions {
+            representatives: true,
+            ..Default::default()
+        };
+        let mut report = Vec::new();
+        write_report_classes(
+            &mut report,
+            &result,
+            &options,
+            BitOrder::MsbFirst.into(),
+            Some(&graph),
+        )
+        .unwrap();
+        let report = String::from_utf8(report).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|it| **it == "Representative instance:")
+                .count(),
+            2
+        );
+        assert!(lines.iter().any(|it| it.starts_with("  a = ")));
+        assert!(lines.iter().any(|it| it.starts_with("  b = ")));
+    }
+
+    #[test]
+    /// Test that the small categories are aggregated in the report.
+    fn test_min_class_cardinality() {
+        let (graph, sets) = prepare_graph_and_sets();
+        // Sizes of categories `00`, `01`, `10`, `11` are 4, 0, 8, 4.
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let property_results = vec![sets[0].union(&sets[1]), sets[0].intersect(&sets[1])];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+
+        let options = OutputOptions {
+            min_class_cardinality: 5.0,
+            ..Default::default()
+        };
+        let mut report = Vec::new();
+        write_report_classes(
+            &mut report,
+            &result,
+            &options,
+            BitOrder::MsbFirst.into(),
+            None,
+        )
+        .unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert_eq!(
+            report,
+            "# 10\n8 colors in this category (50.0%)\nBDD of this category has 5 nodes\n\n\
+             # other\n8 colors in 2 other categories (50.0%)\n\n"
+        );
+    }
+
+    #[test]
+    /// Test that the gzip-compressed JSON report can be read back, with the BDDs encoded inline
+    /// or in the external files.
+    fn test_write_report_json_gz() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+        let read_report = |path: &Path| -> serde_json::Value {
+            serde_json::from_reader(GzDecoder::new(File::open(path).unwrap())).unwrap()
+        };
+
+        let dir = std::env::temp_dir();
+        let path = dir.join("bn-classifier-test-report.json.gz");
+        let options = OutputOptions::default();
+        write_report_json_gz(&result, path.to_str().unwrap(), JsonBdds::Base64, &options).unwrap();
+        let report = read_report(&path);
+        let classes = report["classes"].as_array().unwrap();
+        assert_eq!(classes.len(), 2);
+        assert!(classes[1].get("bdd_file").is_none());
+        let data = Base64::decode_vec(classes[1]["bdd"].as_str().unwrap()).unwrap();
+        let bdd = Bdd::read_as_bytes(&mut data.as_slice()).unwrap();
+        assert!(bdd.iff(sets[0].as_bdd()).is_true());
+
+        // The existing report is not replaced without the permission.
+        let keep = OutputOptions {
+            overwrite: false,
+            ..Default::default()
+        };
+        let error = write_report_json_gz(&result, path.to_str().unwrap(), JsonBdds::Omitted, &keep)
+            .unwrap_err();
+        assert!(matches!(error, ClassifierError::OutputExists(_)));
+        assert!(read_report(&path)["classes"][1].get("bdd").is_some());
+
+        write_report_json_gz(
+            &result,
+            path.to_str().unwrap(),
+            JsonBdds::External,
+            &options,
+        )
+        .unwrap();
+        let class = read_report(&path)["classes"][1].clone();
+        assert!(class.get("bdd").is_none());
+        assert_eq!(
+            class["bdd_file"],
+            "bn-classifier-test-report.bdds/bdd_dump_1.bdd"
+        );
+        let data = std::fs::read(dir.join(class["bdd_file"].as_str().unwrap())).unwrap();
+        let bdd = Bdd::read_as_bytes(&mut data.as_slice()).unwrap();
+        assert!(bdd.iff(sets[0].as_bdd()).is_true());
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_dir_all(dir.join("bn-classifier-test-report.bdds")).unwrap();
+    }
+
+    #[test]
+    /// Test that the given graph is used for the parts of the output that need the encoding.
+    fn test_write_with_graph() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-graph.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        let options = OutputOptions {
+            dot_dumps: true,
+            representatives: true,
+            ..Default::default()
+        };
+        // The model string is empty, so the encoding has to come from the given graph.
+        write_classification_result_with_graph(&result, archive_name, "", &graph, &options)
+            .unwrap();
+
+        let archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        assert!(archive.file_names().any(|it| it == "bdd_dump_1.dot"));
+        std::fs::remove_file(archive_path).unwrap();
+    }
+
+    #[test]
+    /// Test that the builder applies its options and reports the progress of the dumps
+    /// (including the empty categories).
+    fn test_classification_writer() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        // Second property is a subset of the first one, so category `01` is empty.
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+
+        let reported = RefCell::new(Vec::new());
+        let progress = |done: usize, total: usize| reported.borrow_mut().push((done, total));
+        let buffer = ClassificationWriter::new("")
+            .with_graph(&graph)
+            .dot_dumps(true)
+            .markdown_report(true)
+            .on_progress(&progress)
+            .write_to(&result, Cursor::new(Vec::new()))
+            .unwrap();
+        assert_eq!(reported.into_inner(), vec![(1, 4), (2, 4), (3, 4), (4, 4)]);
+
+        let archive = ZipArchive::new(Cursor::new(buffer.into_inner())).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        assert!(names.contains(&"bdd_dump_00.dot"));
+        assert!(names.contains(&"report.md"));
+    }
+
+    #[test]
+    /// Test that with a fixed timestamp, the same result is written as the same bytes.
+    fn test_fixed_timestamp() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let timestamp = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let write = || {
+            ClassificationWriter::new("")
+                .timestamp(timestamp)
+                .write_to(&result, Cursor::new(Vec::new()))
+                .unwrap()
+                .into_inner()
+        };
+        let first = write();
+        assert_eq!(first, write());
+
+        let mut archive = ZipArchive::new(Cursor::new(first)).unwrap();
+        let mut metadata = String::new();
+        let mut entry = archive.by_name("metadata.txt").unwrap();
+        let modified = entry.last_modified();
+        assert_eq!(
+            (modified.year(), modified.month(), modified.day()),
+            (2023, 11, 14)
+        );
+        entry.read_to_string(&mut metadata).unwrap();
+        assert!(metadata
+            .lines()
+            .any(|line| line == "timestamp=2023-11-14T22:13:20Z"));
+    }
+
+    #[test]
+    /// Test the dump files of a single property, and without any properties (where the one
+    /// category covering all valid colors is dumped into `bdd_dump_all.txt`).
+    fn test_single_and_no_property_dumps() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = [("p1".to_string(), "true".to_string())];
+
+        for (count, expected) in [(1, vec!["0", "1"]), (0, vec!["all"])] {
+            let result = build_classification_result(
+                &[],
+                graph.unit_colors(),
+                &properties[0..count],
+                &sets[0..count],
+            )
+            .unwrap();
+            let buffer = write_classification_result_to_writer(
+                &result,
+                Cursor::new(Vec::new()),
+                "",
+                None,
+                &OutputOptions::default(),
+            )
+            .unwrap();
+
+            let archive = ZipArchive::new(Cursor::new(buffer.into_inner())).unwrap();
+            let mut names: Vec<&str> = archive
+                .file_names()
+                .filter_map(|it| it.strip_prefix("bdd_dump_")?.strip_suffix(".txt"))
+                .collect();
+            names.sort();
+            assert_eq!(names, expected);
+        }
+    }
+
+    #[test]
+    /// Test that the properties projected out of the decomposition are listed in the reports.
+    fn test_projected_properties_report() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "false".to_string()),
+        ];
+        let result = build_projected_classification_result(
+            &[],
+            graph.unit_colors(),
+            &properties,
+            &sets[0..2],
+            &[1],
+        )
+        .unwrap();
+
+        let mut report = Vec::new();
+        write_report_intro(&mut report, &result, CardinalityFormat::Fixed).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.contains(
+            "### Property formulae individually\n\n# p2  |  false\n\
+             8 colors satisfy this property (50.0%)\n\n\
+             ### Property formulae projected out\n\n# p1  |  true\n\
+             8 colors satisfy this property (50.0%)\n\n### Classes"
+        ));
+
+        let json = prepare_report_json(&result, &[], BitOrder::MsbFirst.into());
+        assert_eq!(json["projected_properties"][0]["name"], "p1");
+        assert_eq!(json["classes"][0]["class_bits"], "0");
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(escape_xml("a & b"), "a &amp; b");
+        assert_eq!(
+            escape_xml("<x> \"y\" 'z'"),
+            "&lt;x&gt; &quot;y&quot; &apos;z&apos;"
+        );
+        assert_eq!(escape_xml("AG EF a"), "AG EF a");
+    }
+
+    #[test]
+    /// Test the structure of the XML report.
+    fn test_report_xml() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "a & b".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        // Category `01` is empty.
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let result = build_classification_result(
+            &["AG a".to_string()],
+            graph.unit_colors(),
+            &properties,
+            &property_results,
+        )
+        .unwrap();
+        let index = vec![("10".to_string(), "bdd_dump_10.txt".to_string())];
+
+        let mut xml = Vec::new();
+        write_report_xml(&mut xml, &result, &index, BitOrder::MsbFirst.into()).unwrap();
+        let xml = String::from_utf8(xml).unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<classification valid_cardinality="16" valid_exact_cardinality="16" num_classes="3" bit_order="msb_first">
+  <assertions>
+    <assertion>AG a</assertion>
+  </assertions>
+  <properties>
+    <property name="p1" formula="a &amp; b" cardinality="8" exact_cardinality="8" discriminating="true"/>
+    <property name="p2" formula="true" cardinality="4" exact_cardinality="4" discriminating="true"/>
+  </properties>
+  <projected_properties>
+  </projected_properties>
+  <classes>
+    <class class_bits="00" label="00" cardinality="8" exact_cardinality="8" bdd_size="3"/>
+    <class class_bits="10" label="10" cardinality="4" exact_cardinality="4" bdd_size="4" bdd_file="bdd_dump_10.txt"/>
+    <class class_bits="11" label="11" cardinality="4" exact_cardinality="4" bdd_size="4"/>
+  </classes>
+</classification>
+"#;
+        assert_eq!(xml, expected);
+    }
+
+    #[test]
+    /// Test that an existing archive is only replaced if overwriting is allowed.
+    fn test_no_overwrite() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-no-overwrite.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        std::fs::write(&archive_path, "previous result").unwrap();
+
+        let writer = ClassificationWriter::new("").overwrite(false);
+        let error = writer.write(&result, archive_name).unwrap_err();
+        assert!(matches!(error, ClassifierError::OutputExists(path) if path == archive_name));
+        let contents = std::fs::read_to_string(&archive_path).unwrap();
+        assert_eq!(contents, "previous result");
+
+        ClassificationWriter::new("")
+            .write(&result, archive_name)
+            .unwrap();
+        assert!(ZipArchive::new(File::open(&archive_path).unwrap()).is_ok());
+
+        std::fs::remove_file(&archive_path).unwrap();
+        let writer = ClassificationWriter::new("").overwrite(false);
+        writer.write(&result, archive_name).unwrap();
+        std::fs::remove_file(archive_path).unwrap();
+    }
+
+    #[test]
+    /// Test that the valid colors outside of all classes are flagged in the report.
+    fn test_unclassified_colors_report() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let mut result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let mut report = Vec::new();
+        write_report_intro(&mut report, &result, CardinalityFormat::Fixed).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.starts_with(
+            "2 non-empty classes covering 16 colors\n\
+             0 valid colors are not covered by any class\n\n"
+        ));
+
+        result.categories.remove(0);
+        let mut report = Vec::new();
+        write_report_intro(&mut report, &result, CardinalityFormat::Fixed).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.starts_with(
+            "1 non-empty classes covering 8 colors\n\
+             WARNING: 8 valid colors are not covered by any class\n\n"
+        ));
+        let json = prepare_report_json(&result, &[], BitOrder::MsbFirst.into());
+        assert_eq!(json["unclassified_cardinality"], 8.0);
+    }
+
+    #[test]
+    /// Test that the custom class names are used in the report and the BDD dumps, and that
+    /// they must be unique.
+    fn test_custom_class_names() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let names = |validity: &[bool]| validity[0].then(|| "active".to_string());
+        let buffer = ClassificationWriter::new("")
+            .with_class_names(&names)
+            .write_to(&result, Cursor::new(Vec::new()))
+            .unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(buffer.into_inner())).unwrap();
+        let mut dumps: Vec<&str> = archive
+            .file_names()
+            .filter(|it| it.starts_with("bdd_dump_"))
+            .collect();
+        dumps.sort();
+        assert_eq!(dumps, vec!["bdd_dump_0.txt", "bdd_dump_active.txt"]);
+
+        let mut report = String::new();
+        let mut file = archive.by_name("report.txt").unwrap();
+        file.read_to_string(&mut report).unwrap();
+        assert!(report.contains("# 0\n8 colors in this category"));
+        assert!(report.contains("# active\n8 colors in this category"));
+
+        let same_name = |_: &[bool]| Some("same".to_string());
+        let error = ClassificationWriter::new("")
+            .with_class_names(&same_name)
+            .write_to(&result, Cursor::new(Vec::new()))
+            .unwrap_err();
+        assert!(matches!(error, ClassifierError::DuplicateClassLabel(label) if label == "same"));
+
+        // The names must not point outside of the output directory.
+        let dir = std::env::temp_dir().join("bn-classifier-test-class-names");
+        for name in ["", "../escape", "sub/name", "a\\b", "/abs"] {
+            let invalid_name = move |validity: &[bool]| validity[0].then(|| name.to_string());
+            let error = ClassificationWriter::new("")
+                .with_class_names(&invalid_name)
+                .write_to_dir(&result, dir.to_str().unwrap())
+                .unwrap_err();
+            assert!(matches!(error, ClassifierError::InvalidClassLabel(label) if label == name));
+        }
+        assert!(!std::env::temp_dir().join("bdd_dump_escape.txt").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    /// Test that the dumped BDDs are transformed, but the report keeps the original sizes.
+    fn test_transform_dumps() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let property_results = vec![sets[0].intersect(&sets[1])];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+
+        // Project away the parameter variable of the second set.
+        let variable = graph.symbolic_context().parameter_variables()[1];
+        let transform = |colors: &GraphColors| colors.copy(colors.as_bdd().var_exists(variable));
+        let buffer = ClassificationWriter::new("")
+            .transform_dumps(&transform)
+            .write_to(&result, Cursor::new(Vec::new()))
+            .unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(buffer.into_inner())).unwrap();
+        let mut dump = String::new();
+        archive
+            .by_name("bdd_dump_1.txt")
+            .unwrap()
+            .read_to_string(&mut dump)
+            .unwrap();
+        assert_eq!(dump, sets[0].as_bdd().to_string());
+        let mut report = String::new();
+        archive
+            .by_name("report.txt")
+            .unwrap()
+            .read_to_string(&mut report)
+            .unwrap();
+        assert!(report.contains("4 colors in this category (25.0%)"));
+    }
+
+    #[test]
+    /// Test that colored vertices are classified and reported the same way as colors.
+    fn test_colored_vertices_classification() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let unit = graph.unit_colored_vertices();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        // The property holds in one of the four states, for half of the colors.
+        let state = unit.pick_vertex().vertices();
+        let property_results = vec![unit.intersect_colors(&sets[0]).intersect_vertices(&state)];
+        let result =
+            build_classification_result(&[], unit, &properties, &property_results).unwrap();
+        let cardinalities: Vec<f64> = result
+            .categories
+            .iter()
+            .map(|category| category.cardinality)
+            .collect();
+        assert_eq!(cardinalities, vec![56.0, 8.0]);
+
+        let mut report = Vec::new();
+        write_text_report(&mut report, &result, &OutputOptions::default()).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.starts_with("2 non-empty classes covering 64 colored vertices\n"));
+        assert!(report.contains("8 colored vertices in this category (12.5%)"));
+    }
+
+    #[test]
+    /// Test that the timing entry lists the duration of each phase.
+    fn test_timing_entry() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let buffer = ClassificationWriter::new("")
+            .timing(true)
+            .write_to(&result, Cursor::new(Vec::new()))
+            .unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(buffer.into_inner())).unwrap();
+        let mut timing = String::new();
+        archive
+            .by_name("timing.txt")
+            .unwrap()
+            .read_to_string(&mut timing)
+            .unwrap();
+        let phases: Vec<&str> = timing
+            .lines()
+            .map(|line| line.split_once('=').unwrap().0)
+            .collect();
+        assert_eq!(
+            phases,
+            vec![
+                "enumeration_ms",
+                "bdd_serialization_ms",
+                "bdd_compression_ms",
+                "reports_ms"
+            ]
+        );
+    }
+
+    #[test]
+    /// Test that the sidecar report is a copy of the report in the archive.
+    fn test_report_sidecar() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-sidecar.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        ClassificationWriter::new("")
+            .report_sidecar(true)
+            .write(&result, archive_name)
+            .unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let mut report = String::new();
+        archive
+            .by_name("report.txt")
+            .unwrap()
+            .read_to_string(&mut report)
+            .unwrap();
+        let sidecar_name = format!("{archive_name}.report.txt");
+        let sidecar = std::fs::read_to_string(&sidecar_name).unwrap();
+        std::fs::remove_file(&archive_path).unwrap();
+        std::fs::remove_file(&sidecar_name).unwrap();
+        assert_eq!(sidecar, report);
+    }
+
+    #[test]
+    /// Test that the transient failures are retried until the attempts run out, while the
+    /// other failures are returned immediately.
+    fn test_retry_policy() {
+        let retry = RetryPolicy {
+            attempts: 3,
+            backoff: Duration::ZERO,
+        };
+        let transient = |error: &&str| *error == "transient";
+        let mut calls = 0;
+        let result = retry.run(transient, || {
+            calls += 1;
+            if calls < 3 {
+                Err("transient")
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result, Ok(3));
+
+        calls = 0;
+        let result: Result<(), &str> = retry.run(transient, || {
+            calls += 1;
+            Err("transient")
+        });
+        assert_eq!((result, calls), (Err("transient"), 3));
+
+        calls = 0;
+        let result: Result<(), &str> = retry.run(transient, || {
+            calls += 1;
+            Err("fatal")
+        });
+        assert_eq!((result, calls), (Err("fatal"), 1));
+    }
+
+    #[test]
+    /// Test that the entries can be added (with a prefix) to an archive with other entries.
+    fn test_write_classification_entries() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let mut zip_writer = ZipWriter::new(Cursor::new(Vec::new()));
+        zip_writer
+            .start_file("figure.svg", FileOptions::default())
+            .unwrap();
+        let options = OutputOptions {
+            layout: ArchiveLayout {
+                prefix: "classification/".to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        write_classification_entries(&result, &mut zip_writer, "model", None, &options).unwrap();
+        zip_writer
+            .start_file("summary.txt", FileOptions::default())
+            .unwrap();
+        let archive = zip_writer.finish().unwrap();
+
+        let archive = ZipArchive::new(archive).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        assert!(names.contains(&"figure.svg"));
+        assert!(names.contains(&"summary.txt"));
+        assert!(names.contains(&"classification/report.txt"));
+        assert!(names.contains(&"classification/bdd_dump_1.txt"));
+        assert!(names.iter().all(|name| name.starts_with("classification/")
+            || ["figure.svg", "summary.txt"].contains(name)));
+    }
+
+    #[test]
+    /// Test that the split output puts the dumps and the reports into separate archives,
+    /// which both contain the model, the metadata and the cross-reference.
+    fn test_split_bdds() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-split.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        ClassificationWriter::new("model")
+            .split_bdds(true)
+            .checksums(true)
+            .write(&result, archive_name)
+            .unwrap();
+        assert!(!archive_path.exists());
+
+        let (report_name, bdds_name) = split_archive_names(archive_name);
+        let mut entries = Vec::new();
+        for archive_name in [&report_name, &bdds_name] {
+            let mut archive = ZipArchive::new(File::open(archive_name).unwrap()).unwrap();
+            let mut names: Vec<String> = archive.file_names().map(String::from).collect();
+            names.sort();
+            let mut companion = String::new();
+            archive
+                .by_name("companion.txt")
+                .unwrap()
+                .read_to_string(&mut companion)
+                .unwrap();
+            assert_eq!(
+                companion,
+                "report=bn-classifier-test-split.report.zip\nbdds=bn-classifier-test-split.bdds.zip\n"
+            );
+            std::fs::remove_file(archive_name).unwrap();
+            entries.push(names);
+        }
+        assert_eq!(
+            entries[0],
+            vec![
+                "classes.csv",
+                "classes_legend.txt",
+                "companion.txt",
+                "metadata.txt",
+                "model.aeon",
+                "properties.hctl",
+                "report.json",
+                "report.txt",
+            ]
+        );
+        assert_eq!(
+            entries[1],
+            vec![
+                "bdd_dump_0.txt",
+                "bdd_dump_1.txt",
+                "bdd_index.txt",
+                "checksums.txt",
+                "companion.txt",
+                "metadata.txt",
+                "model.aeon",
+                "properties.hctl",
+            ]
+        );
+    }
+
+    #[test]
+    /// Test that each universe has its own nested output and report section.
+    fn test_universes_classification() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let universes = vec![
+            (
+                "healthy".to_string(),
+                vec!["AG a".to_string()],
+                sets[1].clone(),
+            ),
+            (
+                "diseased".to_string(),
+                vec!["AG !a".to_string()],
+                graph.unit_colors().minus(&sets[1]),
+            ),
+        ];
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-universes.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        let sidecar_name = format!("{archive_name}.report.txt");
+        let options = OutputOptions {
+            report_sidecar: true,
+            ..Default::default()
+        };
+        write_universes_classification(
+            &universes,
+            &properties,
+            &sets[0..1],
+            "",
+            archive_name,
+            &options,
+        )
+        .unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&archive_path).unwrap()).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        assert!(names.contains(&"healthy/report.txt"));
+        assert!(names.contains(&"diseased/bdd_dump_1.txt"));
+        assert!(names.contains(&"model.aeon"));
+        let mut report = String::new();
+        archive
+            .by_name("report.txt")
+            .unwrap()
+            .read_to_string(&mut report)
+            .unwrap();
+        let mut healthy_report = String::new();
+        archive
+            .by_name("healthy/report.txt")
+            .unwrap()
+            .read_to_string(&mut healthy_report)
+            .unwrap();
+        let sidecar = std::fs::read_to_string(&sidecar_name).unwrap();
+        std::fs::remove_file(&archive_path).unwrap();
+        std::fs::remove_file(&sidecar_name).unwrap();
+        assert_eq!(sidecar, report);
+        assert!(healthy_report.contains("# AG a\n"));
+        assert!(report.contains("# AG !a\n"));
+        assert!(report.starts_with("## Universe `healthy`\n\n2 non-empty classes covering 8"));
+        assert!(report.contains("## Universe `diseased`\n\n2 non-empty classes covering 8"));
+        // The property is counted within each universe.
+        let property_lines: Vec<&str> = report
+            .lines()
+            .filter(|line| line.ends_with("satisfy this property (50.0%)"))
+            .collect();
+        assert_eq!(property_lines.len(), 2);
+        assert!(property_lines
+            .iter()
+            .all(|line| line.starts_with("4 colors")));
+
+        let duplicate = vec![universes[0].clone(), universes[0].clone()];
+        let error = write_universes_classification(
+            &duplicate,
+            &properties,
+            &sets[0..1],
+            "",
+            archive_name,
+            &OutputOptions::default(),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(error, ClassifierError::InvalidUniverseLabel(label) if label == "healthy")
+        );
+        for label in ["..", ".", "a\\b", ""] {
+            let invalid = vec![(label.to_string(), Vec::new(), sets[1].clone())];
+            let error = write_universes_classification(
+                &invalid,
+                &properties,
+                &sets[0..1],
+                "",
+                archive_name,
+                &OutputOptions::default(),
+            )
+            .unwrap_err();
+            assert!(matches!(error, ClassifierError::InvalidUniverseLabel(_)));
+        }
+        let split = OutputOptions {
+            split_bdds: true,
+            ..Default::default()
+        };
+        let error = write_universes_classification(
+            &universes,
+            &properties,
+            &sets[0..1],
+            "",
+            archive_name,
+            &split,
+        )
+        .unwrap_err();
+        assert!(matches!(error, ClassifierError::UnsupportedOption(_)));
+        assert!(!archive_path.exists());
+    }
+
+    #[test]
+    /// Test the report section with the absent classes.
+    fn test_absent_classes_report() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("p2".to_string(), "true".to_string()),
+        ];
+        let property_results = vec![sets[0].clone(), sets[0].intersect(&sets[1])];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+
+        let mut report = Vec::new();
+        write_absent_classes(&mut report, &result, BitOrder::MsbFirst.into()).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert_eq!(report, "### Absent classes\n\n# 01  |  ¬p1 ∧ p2\n\n");
+
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..2])
+                .unwrap();
+        let mut report = Vec::new();
+        write_absent_classes(&mut report, &result, BitOrder::MsbFirst.into()).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.contains("All combinations of properties occur."));
+    }
+
+    #[test]
+    /// Test that the archive file is removed when its writing fails.
+    fn test_failed_archive_is_removed() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let archive_path = std::env::temp_dir().join("bn-classifier-test-failed.zip");
+        let archive_name = archive_path.to_str().unwrap();
+        let same_name = |_: &[bool]| Some("same".to_string());
+        let error = ClassificationWriter::new("")
+            .with_class_names(&same_name)
+            .write(&result, archive_name)
+            .unwrap_err();
+        assert!(matches!(error, ClassifierError::DuplicateClassLabel(_)));
+        assert!(!archive_path.exists());
+    }
+
+    #[test]
+    /// Test that the relations of properties are described in the report.
+    fn test_property_relations_report() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![
+            ("p1".to_string(), "true".to_string()),
+            ("not_p1".to_string(), "true".to_string()),
+        ];
+        let property_results = vec![sets[0].clone(), graph.unit_colors().minus(&sets[0])];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &property_results)
+                .unwrap();
+
+        let mut report = Vec::new();
+        write_report_intro(&mut report, &result, CardinalityFormat::Fixed).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.contains(
+            "### Property relations\n\n\
+             p1 and not_p1 never hold together\n\
+             p1 or not_p1 always holds\n\n### Classes"
+        ));
+
+        let json = prepare_report_json(&result, &[], BitOrder::MsbFirst.into());
+        assert_eq!(json["relations"][0]["kind"], "never_both_true");
+        assert_eq!(json["relations"][1]["properties"][1], "not_p1");
+    }
+
+    #[test]
+    /// Test that the directory output contains the same entries as the zip archive.
+    fn test_write_to_dir() {
+        let (graph, sets) = prepare_graph_and_sets();
+        let properties = vec![("p1".to_string(), "true".to_string())];
+        let result =
+            build_classification_result(&[], graph.unit_colors(), &properties, &sets[0..1])
+                .unwrap();
+
+        let dir_path = std::env::temp_dir().join("bn-classifier-test-dir");
+        let dir_name = dir_path.to_str().unwrap();
+        let _ = std::fs::remove_dir_all(&dir_path);
+        write_class_report_to_dir(&result, dir_name, "a -?? b\n").unwrap();
+
+        let buffer = write_classification_result_to_writer(
+            &result,
+            Cursor::new(Vec::new()),
+            "a -?? b\n",
+            None,
+            &OutputOptions::default(),
+        )
+        .unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(buffer.into_inner())).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(|it| it.to_string()).collect();
+        names.sort();
+        let mut files: Vec<String> = std::fs::read_dir(&dir_path)
+            .unwrap()
+            .map(|it| it.unwrap().file_name().to_str().unwrap().to_string())
+            .collect();
+        files.sort();
+        assert_eq!(files, names);
+
+        for name in ["bdd_dump_1.txt", "classes.csv", "model.aeon"] {
+            let mut expected = String::new();
+            let mut entry = archive.by_name(name).unwrap();
+            entry.read_to_string(&mut expected).unwrap();
+            let contents = std::fs::read_to_string(dir_path.join(name)).unwrap();
+            assert_eq!(contents, expected);
+        }
+
+        let writer = ClassificationWriter::new("").overwrite(false);
+        let error = writer.write_to_dir(&result, dir_name).unwrap_err();
+        assert!(matches!(error, ClassifierError::OutputExists(_)));
+        std::fs::remove_dir_all(dir_path).unwrap();
     }
 }
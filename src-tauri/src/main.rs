@@ -8,9 +8,10 @@ extern crate json;
 
 use crate::bdt::{AttributeId, Bdt, BdtNodeId, Outcome};
 
-use biodivine_lib_bdd::{Bdd, BddPartialValuation};
+use biodivine_lib_bdd::BddPartialValuation;
 use biodivine_lib_param_bn::symbolic_async_graph::{GraphColors, SymbolicAsyncGraph};
 use biodivine_lib_param_bn::{BooleanNetwork, ModelAnnotation, RegulatoryGraph};
+use bn_classifier::load_inputs::{load_classification_archive, read_archive_properties};
 
 use biodivine_lib_param_bn::biodivine_std::traits::Set;
 use clap::Parser;
@@ -19,14 +20,13 @@ use rand::prelude::StdRng;
 use rand::SeedableRng;
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::Write;
 use std::ops::DerefMut;
 use std::path::Path;
 use std::sync::Mutex;
 use tauri::api::dialog;
 use tauri::{AppHandle, State};
 use zip::write::{FileOptions, ZipWriter};
-use zip::ZipArchive;
 
 pub mod bdt;
 pub mod util;
@@ -431,55 +431,38 @@ fn read_model_properties(annotations: &ModelAnnotation) -> Result<Vec<(String, S
     Ok(properties)
 }
 
-/// Read the contents of a file from a zip archive into a string.
-fn read_zip_file(reader: &mut ZipArchive<File>, file_name: &str) -> String {
-    let mut contents = String::new();
-    let mut file = reader.by_name(file_name).unwrap();
-    file.read_to_string(&mut contents).unwrap();
-    contents
-}
-
 /// Load a classification archive from the given path into a valid symbolic graph
 /// and decision tree.
 ///
 /// This can happen either before the application starts (if path is given as CLI arg),
 /// or after the first window is opened (if no path is given).
 fn setup_environment(archive_path: &str) -> (SymbolicAsyncGraph, Bdt) {
-    // Open the zip archive with classification results.
-    let archive_file = File::open(archive_path).unwrap();
-    let mut archive = ZipArchive::new(archive_file).unwrap();
+    // Load the classes (colored sets) and the BN model from the classification archive. The
+    // classifier's loader handles all the formats of the dumps (binary, compressed, relative to
+    // the valid colors, or in a split companion archive).
+    let (categories, aeon_str) = load_classification_archive(archive_path.to_string()).unwrap();
 
-    // Load the BN model (from the archive) and generate the extended STG.
-    let aeon_str = read_zip_file(&mut archive, "model.aeon");
+    // Generate the extended STG of the BN model.
     let bn = BooleanNetwork::try_from(aeon_str.as_str()).unwrap();
     let graph = SymbolicAsyncGraph::new(bn).unwrap();
 
-    // load the property names from model annotations (to later display them)
-    let annotations = ModelAnnotation::from_model_string(aeon_str.as_str());
-    let properties = read_model_properties(&annotations).unwrap();
+    // Load the property names (to later display them) in the order of the bits of the class
+    // labels, which is given by the property file of the archive. Only the archives without
+    // the property file fall back to the (alphabetically sorted) model annotations.
+    let properties = match read_archive_properties(archive_path) {
+        Ok((_, properties)) => properties,
+        Err(_) => {
+            let annotations = ModelAnnotation::from_model_string(aeon_str.as_str());
+            read_model_properties(&annotations).unwrap()
+        }
+    };
     let properties_map = HashMap::from_iter(properties.iter().cloned());
 
-    // collect the classification outcomes (colored sets) from the individual BDD dumps
+    // collect the classification outcomes (colored sets), re-encoded in the context of the graph
     let mut outcomes = HashMap::new();
-
-    // Load all class BDDs from files in the archive.
-    let files = archive
-        .file_names()
-        .map(|it| it.to_string())
-        .collect::<Vec<_>>();
-
-    for file in files {
-        if !file.starts_with("bdd_dump_") {
-            // Only read BDD dumps.
-            continue;
-        }
-
-        let bdd_string = read_zip_file(&mut archive, file.as_str());
-        let bdd = Bdd::from_string(bdd_string.as_str());
-        let color_set = GraphColors::new(bdd, graph.symbolic_context());
-
-        let outcome_id = file.strip_prefix("bdd_dump_").unwrap();
-        let outcome_id = outcome_id.strip_suffix(".txt").unwrap();
+    for (outcome_id, color_set) in categories {
+        let color_set = GraphColors::new(color_set.into_bdd(), graph.symbolic_context());
+        let outcome_id = outcome_id.as_str();
 
         let outcome = if properties_map.is_empty() {
             // This is "custom" archive that does not hold HCTL properties, in which case,